@@ -0,0 +1,189 @@
+//! Session audit log (`--audit-log`)
+//!
+//! Records what happened in a sandboxed session as JSON Lines, one event per
+//! line, so it can be reviewed after the fact. The log is written by the
+//! unsandboxed supervisor, never by the sandboxed command, and `nono run`
+//! refuses a log path the command could write to, so the command cannot
+//! tamper with it or make it unwritable by restricting paths.
+//!
+//! Every record carries a timestamp and nono's PID alongside the event:
+//!
+//! ```json
+//! {"timestamp":"2026-01-01T12:00:00Z","pid":4242,"event":"session_end","exit_code":0}
+//! ```
+//!
+//! Secret values are never logged; only the environment variable names.
+
+use crate::error::{NonoError, Result};
+use crate::sandbox_state::SandboxState;
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+
+/// A single audit event
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum AuditEvent<'a> {
+    /// Session started: what was run and with which capabilities
+    SessionStart {
+        command: &'a [String],
+        profile: Option<&'a str>,
+        security_lists_version: u64,
+        capabilities: SandboxState,
+    },
+    /// Sandbox diagnostic injected into the command's output
+    Diagnostic {
+        /// Stream the trigger was seen on ("stdout", "stderr"), or "exit"
+        stream: &'a str,
+        /// The output line (or exit status) that triggered the diagnostic
+        trigger: &'a str,
+        message: &'a str,
+    },
+    /// Command rejected by the dangerous command blocklist
+    CommandBlocked { command: &'a str, reason: &'a str },
+    /// Operator answered a `--prompt` permission request
+    PermissionPrompt {
+        operation: &'a str,
+        target: &'a str,
+        /// "once", "session" or "never"
+        decision: &'a str,
+    },
+    /// A time-limited capability ran out
+    CapabilityExpired { path: &'a Path, access: &'a str },
+    /// Secrets injected into the command's environment (names only)
+    SecretsInjected { env_vars: Vec<&'a str> },
+    /// Session ended with the command's exit status
+    SessionEnd { exit_code: i32 },
+}
+
+/// Envelope written for each event
+#[derive(Serialize)]
+struct AuditRecord<'a> {
+    timestamp: String,
+    pid: u32,
+    #[serde(flatten)]
+    event: &'a AuditEvent<'a>,
+}
+
+/// Append-only JSONL audit log, shareable across output threads
+#[derive(Clone)]
+pub struct AuditLog {
+    file: Arc<Mutex<File>>,
+}
+
+impl AuditLog {
+    /// Open (or create) the audit log for appending
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| NonoError::AuditLog {
+                path: path.to_path_buf(),
+                source: e,
+            })?;
+        Ok(Self {
+            file: Arc::new(Mutex::new(file)),
+        })
+    }
+
+    /// Append an event to the log.
+    ///
+    /// Write failures are logged but not fatal: losing an audit line should
+    /// not kill the sandboxed command mid-session.
+    pub fn record(&self, event: &AuditEvent<'_>) {
+        let record = AuditRecord {
+            timestamp: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            pid: std::process::id(),
+            event,
+        };
+        let mut line = match serde_json::to_string(&record) {
+            Ok(line) => line,
+            Err(e) => {
+                warn!("Failed to serialize audit event: {}", e);
+                return;
+            }
+        };
+        line.push('\n');
+
+        let mut file = match self.file.lock() {
+            Ok(file) => file,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        // One write per line keeps records whole under O_APPEND
+        if let Err(e) = file.write_all(line.as_bytes()).and_then(|_| file.flush()) {
+            warn!("Failed to write audit log: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn read_events(path: &Path) -> Vec<serde_json::Value> {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_audit_log_writes_jsonl() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("session.jsonl");
+        let log = AuditLog::open(&path).unwrap();
+
+        let command = vec!["echo".to_string(), "hi".to_string()];
+        log.record(&AuditEvent::SessionStart {
+            command: &command,
+            profile: Some("claude-code"),
+            security_lists_version: 3,
+            capabilities: SandboxState::default(),
+        });
+        log.record(&AuditEvent::SecretsInjected {
+            env_vars: vec!["OPENAI_API_KEY"],
+        });
+        log.record(&AuditEvent::SessionEnd { exit_code: 0 });
+
+        let events = read_events(&path);
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0]["event"], "session_start");
+        assert_eq!(events[0]["command"][1], "hi");
+        assert_eq!(events[0]["profile"], "claude-code");
+        assert_eq!(events[0]["security_lists_version"], 3);
+        assert!(events[0]["capabilities"]["fs"].is_array());
+        assert!(events[0]["timestamp"].is_string());
+        assert_eq!(events[0]["pid"], std::process::id());
+        assert_eq!(events[1]["event"], "secrets_injected");
+        assert_eq!(events[1]["env_vars"][0], "OPENAI_API_KEY");
+        assert_eq!(events[2]["event"], "session_end");
+        assert_eq!(events[2]["exit_code"], 0);
+    }
+
+    #[test]
+    fn test_audit_log_appends() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("session.jsonl");
+
+        AuditLog::open(&path)
+            .unwrap()
+            .record(&AuditEvent::SessionEnd { exit_code: 1 });
+        AuditLog::open(&path)
+            .unwrap()
+            .record(&AuditEvent::CommandBlocked {
+                command: "rm",
+                reason: "blocked",
+            });
+
+        let events = read_events(&path);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1]["event"], "command_blocked");
+        assert_eq!(events[1]["command"], "rm");
+    }
+}
@@ -1,8 +1,10 @@
 use crate::cli::SandboxArgs;
+use crate::config::security_lists::CommandRule;
 use crate::error::{NonoError, Result};
 use crate::profile::{self, Profile};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
 /// Filesystem access mode
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -13,6 +15,59 @@ pub enum FsAccess {
     Write,
     /// Read and write access
     ReadWrite,
+    /// Read-only access without executing files
+    ReadNoExec,
+    /// Execute files without listing them. On macOS the files cannot be
+    /// read either; on Linux they stay readable by name (see `reads_files`)
+    Execute,
+}
+
+impl FsAccess {
+    /// Whether files can be read and directories listed
+    pub fn reads(self) -> bool {
+        matches!(
+            self,
+            FsAccess::Read | FsAccess::ReadWrite | FsAccess::ReadNoExec
+        )
+    }
+
+    /// Whether files can be read by name. On Linux this includes exec-only
+    /// grants: Landlock checks ReadFile when execve opens a binary, so the
+    /// sandbox has to grant it with Execute
+    pub fn reads_files(self) -> bool {
+        self.reads() || (cfg!(target_os = "linux") && self == FsAccess::Execute)
+    }
+
+    /// Whether files can be written, created and removed
+    pub fn writes(self) -> bool {
+        matches!(self, FsAccess::Write | FsAccess::ReadWrite)
+    }
+
+    /// Whether files can be executed
+    pub fn executes(self) -> bool {
+        matches!(
+            self,
+            FsAccess::Read | FsAccess::ReadWrite | FsAccess::Execute
+        )
+    }
+
+    /// Whether a grant of this access allows an operation needing `requested`
+    /// (a `Read` request is a plain read, not an exec)
+    pub fn allows(self, requested: FsAccess) -> bool {
+        match requested {
+            FsAccess::Read | FsAccess::ReadNoExec => self.reads_files(),
+            FsAccess::Write => self.writes(),
+            FsAccess::ReadWrite => self.reads_files() && self.writes(),
+            FsAccess::Execute => self.executes(),
+        }
+    }
+
+    /// Whether this access grants everything `other` grants
+    pub fn includes(self, other: FsAccess) -> bool {
+        (self.reads() || !other.reads())
+            && (self.writes() || !other.writes())
+            && (self.executes() || !other.executes())
+    }
 }
 
 impl std::fmt::Display for FsAccess {
@@ -21,6 +76,9 @@ impl std::fmt::Display for FsAccess {
             FsAccess::Read => write!(f, "read"),
             FsAccess::Write => write!(f, "write"),
             FsAccess::ReadWrite => write!(f, "read+write"),
+            FsAccess::ReadNoExec => write!(f, "read, no exec"),
+            FsAccess::Execute if cfg!(target_os = "linux") => write!(f, "exec, files readable"),
+            FsAccess::Execute => write!(f, "exec only"),
         }
     }
 }
@@ -36,6 +94,12 @@ pub struct FsCapability {
     pub access: FsAccess,
     /// True if this is a single file, false if directory (recursive)
     pub is_file: bool,
+    /// How long the capability lasts once the session starts (None = forever)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<Duration>,
+    /// When the capability expires; set from `ttl` when the session starts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<SystemTime>,
 }
 
 impl FsCapability {
@@ -64,6 +128,8 @@ impl FsCapability {
             resolved,
             access,
             is_file: false,
+            ttl: None,
+            expires_at: None,
         })
     }
 
@@ -97,8 +163,30 @@ impl FsCapability {
             resolved,
             access,
             is_file: true,
+            ttl: None,
+            expires_at: None,
         })
     }
+
+    /// Limit the capability to `ttl` from the start of the session
+    pub fn with_ttl(mut self, ttl: Option<Duration>) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Whether the capability has expired at `now`
+    pub fn is_expired_at(&self, now: SystemTime) -> bool {
+        self.expires_at.is_some_and(|at| now >= at)
+    }
+
+    /// Whether `path` is this capability's file or lies under its directory
+    pub fn covers(&self, path: &Path) -> bool {
+        if self.is_file {
+            path == self.resolved
+        } else {
+            path.starts_with(&self.resolved)
+        }
+    }
 }
 
 impl FsCapability {
@@ -136,17 +224,146 @@ impl std::fmt::Display for FsCapability {
     }
 }
 
+/// Resource limits for the command, set with `setrlimit` before exec
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceLimits {
+    /// Address space in bytes (`RLIMIT_AS`)
+    pub memory: Option<u64>,
+    /// Processes of the user (`RLIMIT_NPROC`)
+    pub processes: Option<u64>,
+    /// CPU time (`RLIMIT_CPU`)
+    pub cpu_time: Option<Duration>,
+    /// Size of any file written, in bytes (`RLIMIT_FSIZE`)
+    pub file_size: Option<u64>,
+    /// Open file descriptors (`RLIMIT_NOFILE`)
+    pub open_files: Option<u64>,
+}
+
+impl ResourceLimits {
+    /// Whether no limit is set
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// These limits, with those set in `other` taking precedence
+    pub fn or(self, other: ResourceLimits) -> Self {
+        Self {
+            memory: other.memory.or(self.memory),
+            processes: other.processes.or(self.processes),
+            cpu_time: other.cpu_time.or(self.cpu_time),
+            file_size: other.file_size.or(self.file_size),
+            open_files: other.open_files.or(self.open_files),
+        }
+    }
+
+    /// The limits set, e.g. `["memory 2G", "CPU time 10m"]`
+    pub fn describe(&self) -> Vec<String> {
+        let mut out = Vec::new();
+        if let Some(bytes) = self.memory {
+            out.push(format!("memory {}", format_size(bytes)));
+        }
+        if let Some(n) = self.processes {
+            out.push(format!("processes {}", n));
+        }
+        if let Some(time) = self.cpu_time {
+            out.push(format!("CPU time {}", format_duration(time)));
+        }
+        if let Some(bytes) = self.file_size {
+            out.push(format!("file size {}", format_size(bytes)));
+        }
+        if let Some(n) = self.open_files {
+            out.push(format!("open files {}", n));
+        }
+        out
+    }
+
+    /// Limits from the CLI flags
+    fn from_args(args: &SandboxArgs) -> Self {
+        Self {
+            memory: args.max_memory,
+            processes: args.max_procs,
+            cpu_time: args.max_cpu_time,
+            file_size: args.max_file_size,
+            open_files: args.max_open_files,
+        }
+    }
+
+    /// Limits from a profile's `[limits]` section
+    fn from_profile(limits: &profile::LimitsConfig) -> Result<Self> {
+        let size = |value: &Option<String>, key: &str| {
+            value
+                .as_deref()
+                .map(|s| {
+                    parse_size(s).ok_or_else(|| {
+                        NonoError::ProfileParse(format!(
+                            "invalid {} '{}' in [limits] (expected e.g. 512M, 2G)",
+                            key, s
+                        ))
+                    })
+                })
+                .transpose()
+        };
+        let cpu_time = limits
+            .cpu_time
+            .as_deref()
+            .map(|s| {
+                parse_duration(s).ok_or_else(|| {
+                    NonoError::ProfileParse(format!(
+                        "invalid cpu_time '{}' in [limits] (expected e.g. 30s, 5m, 2h)",
+                        s
+                    ))
+                })
+            })
+            .transpose()?;
+        Ok(Self {
+            memory: size(&limits.memory, "memory")?,
+            processes: limits.processes,
+            cpu_time,
+            file_size: size(&limits.file_size, "file_size")?,
+            open_files: limits.open_files,
+        })
+    }
+}
+
 /// The complete set of capabilities granted to the sandbox
 #[derive(Debug, Clone, Default)]
 pub struct CapabilitySet {
     /// Filesystem capabilities
     pub fs: Vec<FsCapability>,
+    /// Paths hidden from the sandbox even where a capability covers them
+    pub deny: Vec<PathBuf>,
+    /// Devices (or directories of devices) that may receive ioctls (Linux)
+    pub ioctl_devices: Vec<PathBuf>,
     /// Network access blocked (network allowed by default; true = blocked)
     pub net_block: bool,
+    /// TCP ports the sandbox may connect to (only enforced when port filtering is on)
+    pub tcp_connect_ports: Vec<u16>,
+    /// TCP ports the sandbox may bind (only enforced when port filtering is on)
+    pub tcp_bind_ports: Vec<u16>,
+    /// Hosts reachable through the filtering proxy (exact or `*.domain`)
+    pub allow_hosts: Vec<String>,
+    /// Port of the running filtering proxy, set just before the sandbox is built
+    pub proxy_port: Option<u16>,
     /// Commands explicitly allowed (overrides default blocklist)
     pub allowed_commands: Vec<String>,
     /// Additional commands to block (extends default blocklist)
     pub blocked_commands: Vec<String>,
+    /// Argument rules for commands that may run, with paths expanded
+    pub command_rules: Vec<(String, CommandRule)>,
+    /// Dangerous syscalls the seccomp filter lets through anyway (Linux)
+    pub allowed_syscalls: Vec<String>,
+    /// Additional syscalls the seccomp filter denies (Linux)
+    pub denied_syscalls: Vec<String>,
+    /// Abstract UNIX sockets outside the sandbox reachable (Linux: Landlock scoping off)
+    pub allow_abstract_sockets: bool,
+    /// Processes outside the sandbox may be signalled (Linux: Landlock scoping off)
+    pub allow_signals: bool,
+    /// Refuse to run if the kernel cannot enforce a protection this set relies on
+    pub require_full_enforcement: bool,
+    /// Run the command in a PID namespace of its own, with a private /proc (Linux)
+    pub pid_namespace: bool,
+    /// Resource limits for the command
+    pub limits: ResourceLimits,
 }
 
 impl CapabilitySet {
@@ -161,32 +378,55 @@ impl CapabilitySet {
     }
 
     /// Deduplicate filesystem capabilities by resolved path
-    /// For duplicates, keeps the highest access level (ReadWrite > Read/Write)
+    /// For duplicates, keeps the one whose access includes the other's
+    /// (ReadWrite > Read > ReadNoExec); grants that only overlap are both kept
     pub fn deduplicate(&mut self) {
         use std::collections::HashMap;
 
         // Group by (resolved path, is_file)
         let mut seen: HashMap<(PathBuf, bool), usize> = HashMap::new();
         let mut to_remove = Vec::new();
+        let mut ttls = Vec::new();
 
         for (i, cap) in self.fs.iter().enumerate() {
             let key = (cap.resolved.clone(), cap.is_file);
             if let Some(&existing_idx) = seen.get(&key) {
                 // Duplicate found - decide which to keep
                 let existing = &self.fs[existing_idx];
-                if cap.access == FsAccess::ReadWrite && existing.access != FsAccess::ReadWrite {
+                // Grants of different access and lifetime cannot be merged
+                // without stretching one of them: keep both
+                if existing.access != cap.access && existing.ttl != cap.ttl {
+                    continue;
+                }
+                // Neither grant covers the other (e.g. read, no exec and
+                // exec only): keep both
+                if !existing.access.includes(cap.access) && !cap.access.includes(existing.access) {
+                    continue;
+                }
+                // The survivor lasts as long as the longer-lived of the two
+                let ttl = match (existing.ttl, cap.ttl) {
+                    (Some(a), Some(b)) => Some(a.max(b)),
+                    _ => None,
+                };
+                if cap.access != existing.access && cap.access.includes(existing.access) {
                     // New one has higher access, remove old
                     to_remove.push(existing_idx);
                     seen.insert(key, i);
+                    ttls.push((i, ttl));
                 } else {
                     // Keep existing, remove new
                     to_remove.push(i);
+                    ttls.push((existing_idx, ttl));
                 }
             } else {
                 seen.insert(key, i);
             }
         }
 
+        for (idx, ttl) in ttls {
+            self.fs[idx].ttl = ttl;
+        }
+
         // Remove duplicates in reverse order to maintain indices
         to_remove.sort_unstable();
         to_remove.reverse();
@@ -200,6 +440,185 @@ impl CapabilitySet {
         !self.fs.is_empty()
     }
 
+    /// Exclude a path (and everything beneath it) from the sandbox.
+    ///
+    /// The path must already be canonical. Paths inside an existing
+    /// exclusion are redundant and dropped; an exclusion that contains
+    /// existing ones replaces them.
+    pub fn add_deny(&mut self, path: PathBuf) {
+        if self.is_denied(&path) {
+            return;
+        }
+        self.deny.retain(|denied| !denied.starts_with(&path));
+        self.deny.push(path);
+    }
+
+    /// Allow ioctls on a device (a canonical path), ignoring repeats
+    pub fn add_ioctl_device(&mut self, path: PathBuf) {
+        if !self.ioctl_devices.contains(&path) {
+            self.ioctl_devices.push(path);
+        }
+    }
+
+    /// Check if a path lies inside an excluded path
+    pub fn is_denied(&self, path: &Path) -> bool {
+        self.deny.iter().any(|denied| path.starts_with(denied))
+    }
+
+    /// Check if any filesystem capability is time-limited
+    pub fn has_ttl(&self) -> bool {
+        self.fs.iter().any(|cap| cap.ttl.is_some())
+    }
+
+    /// Start the clock on time-limited capabilities
+    pub fn arm_expiry(&mut self, now: SystemTime) {
+        for cap in &mut self.fs {
+            cap.expires_at = cap.ttl.map(|ttl| now + ttl);
+        }
+    }
+
+    /// The capabilities still in effect at `now`
+    pub fn active_at(&self, now: SystemTime) -> CapabilitySet {
+        let mut active = self.clone();
+        active.fs.retain(|cap| !cap.is_expired_at(now));
+        active
+    }
+
+    /// Whether a write grant covers `path` (canonical)
+    pub fn grants_write_to(&self, path: &Path) -> bool {
+        self.fs
+            .iter()
+            .any(|cap| cap.access.writes() && cap.covers(path))
+    }
+
+    /// Whether `access` to `path` was granted only by capabilities that have
+    /// expired at `now` (and so must now be refused)
+    pub fn access_expired(&self, path: &Path, access: FsAccess, now: SystemTime) -> bool {
+        let grants = |cap: &&FsCapability| cap.covers(path) && cap.access.allows(access);
+        let mut granting = self.fs.iter().filter(grants).peekable();
+        granting.peek().is_some() && granting.all(|cap| cap.is_expired_at(now))
+    }
+
+    /// Check if TCP port filtering is enabled.
+    ///
+    /// Any connect or bind port rule switches the network from allow-all to
+    /// deny-by-default for TCP: both connects and binds are then restricted
+    /// to the listed ports. A host allowlist implies port filtering, so that
+    /// the proxy is the only route to other hosts. Has no effect when the
+    /// network is blocked.
+    pub fn has_port_rules(&self) -> bool {
+        !self.net_block
+            && (!self.tcp_connect_ports.is_empty()
+                || !self.tcp_bind_ports.is_empty()
+                || self.has_host_allowlist())
+    }
+
+    /// Check if outbound traffic must go through the filtering proxy
+    pub fn has_host_allowlist(&self) -> bool {
+        !self.net_block && !self.allow_hosts.is_empty()
+    }
+
+    /// TCP ports the sandbox may connect to, including the proxy port
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub fn effective_connect_ports(&self) -> Vec<u16> {
+        let mut ports = self.tcp_connect_ports.clone();
+        if let Some(port) = self.proxy_port {
+            if !ports.contains(&port) {
+                ports.push(port);
+            }
+        }
+        ports
+    }
+
+    /// Merge allowed hosts, normalized to lowercase and free of duplicates
+    fn add_allow_hosts(&mut self, hosts: &[String]) {
+        for host in hosts {
+            let host = host.trim().trim_end_matches('.').to_ascii_lowercase();
+            if !host.is_empty() && !self.allow_hosts.contains(&host) {
+                self.allow_hosts.push(host);
+            }
+        }
+    }
+
+    /// Merge command allow/block lists. A command named in one list is
+    /// removed from the other, so later lists override earlier ones.
+    fn add_command_lists(&mut self, allow: &[String], block: &[String]) {
+        for cmd in allow {
+            self.blocked_commands.retain(|c| c != cmd);
+            if !self.allowed_commands.contains(cmd) {
+                self.allowed_commands.push(cmd.clone());
+            }
+        }
+        for cmd in block {
+            self.allowed_commands.retain(|c| c != cmd);
+            if !self.blocked_commands.contains(cmd) {
+                self.blocked_commands.push(cmd.clone());
+            }
+        }
+    }
+
+    /// Merge syscall allow/deny lists the same way. On Linux, names the
+    /// seccomp filter does not know are an error.
+    fn add_syscall_rules(&mut self, allow: &[String], deny: &[String]) -> Result<()> {
+        #[cfg(target_os = "linux")]
+        if let Some(unknown) = allow
+            .iter()
+            .chain(deny)
+            .find(|name| crate::sandbox::seccomp::syscall_number(name).is_none())
+        {
+            return Err(NonoError::ProfileParse(format!(
+                "Unknown syscall '{}' in [syscalls]",
+                unknown
+            )));
+        }
+        for name in allow {
+            self.denied_syscalls.retain(|s| s != name);
+            if !self.allowed_syscalls.contains(name) {
+                self.allowed_syscalls.push(name.clone());
+            }
+        }
+        for name in deny {
+            self.allowed_syscalls.retain(|s| s != name);
+            if !self.denied_syscalls.contains(name) {
+                self.denied_syscalls.push(name.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Add argument rules for commands, expanding variables in their paths
+    /// against `workdir`. Rules only add restrictions, so all of them apply.
+    pub fn add_arg_rules<'a>(
+        &mut self,
+        rules: impl IntoIterator<Item = (&'a String, &'a CommandRule)>,
+        workdir: &Path,
+    ) {
+        for (command, rule) in rules {
+            let rule = rule.with_paths(|p| {
+                profile::expand_vars(p, workdir)
+                    .to_string_lossy()
+                    .into_owned()
+            });
+            if !self
+                .command_rules
+                .iter()
+                .any(|(c, r)| c == command && *r == rule)
+            {
+                self.command_rules.push((command.clone(), rule));
+            }
+        }
+    }
+
+    /// Merge TCP port rules, keeping the lists sorted and free of duplicates
+    fn add_port_rules(&mut self, connect: &[u16], bind: &[u16]) {
+        self.tcp_connect_ports.extend_from_slice(connect);
+        self.tcp_connect_ports.sort_unstable();
+        self.tcp_connect_ports.dedup();
+        self.tcp_bind_ports.extend_from_slice(bind);
+        self.tcp_bind_ports.sort_unstable();
+        self.tcp_bind_ports.dedup();
+    }
+
     /// Check if the given path is already covered by an existing directory capability.
     ///
     /// Uses component-wise Path::starts_with() to prevent path traversal issues
@@ -210,48 +629,79 @@ impl CapabilitySet {
             .any(|cap| !cap.is_file && path.starts_with(&cap.resolved))
     }
 
-    /// Build capabilities from CLI arguments
-    pub fn from_args(args: &SandboxArgs) -> Result<Self> {
-        let mut caps = Self::new();
-
-        // Process directory permissions
-        for path in &args.allow {
-            let cap = FsCapability::new_dir(path.clone(), FsAccess::ReadWrite)?;
-            caps.add_fs(cap);
+    /// Add the filesystem grants from `--allow`, `--read`, `--write` and
+    /// their single-file variants, each optionally suffixed with `:DURATION`
+    fn add_cli_paths(&mut self, args: &SandboxArgs) -> Result<()> {
+        let dirs = [
+            (&args.allow, FsAccess::ReadWrite),
+            (&args.read, FsAccess::Read),
+            (&args.write, FsAccess::Write),
+            (&args.read_noexec, FsAccess::ReadNoExec),
+            (&args.exec_only, FsAccess::Execute),
+        ];
+        for (paths, access) in dirs {
+            for path in paths {
+                let (path, ttl) = split_ttl(path);
+                self.add_fs(FsCapability::new_dir(path, access)?.with_ttl(ttl));
+            }
         }
 
-        for path in &args.read {
-            let cap = FsCapability::new_dir(path.clone(), FsAccess::Read)?;
-            caps.add_fs(cap);
+        let files = [
+            (&args.allow_file, FsAccess::ReadWrite),
+            (&args.read_file, FsAccess::Read),
+            (&args.write_file, FsAccess::Write),
+        ];
+        for (paths, access) in files {
+            for path in paths {
+                let (path, ttl) = split_ttl(path);
+                self.add_fs(FsCapability::new_file(path, access)?.with_ttl(ttl));
+            }
         }
 
-        for path in &args.write {
-            let cap = FsCapability::new_dir(path.clone(), FsAccess::Write)?;
-            caps.add_fs(cap);
+        for path in &args.deny {
+            if !path.exists() {
+                return Err(NonoError::PathNotFound(path.clone()));
+            }
+            let resolved = path
+                .canonicalize()
+                .map_err(|e| NonoError::PathCanonicalization {
+                    path: path.clone(),
+                    source: e,
+                })?;
+            self.add_deny(resolved);
         }
 
-        // Process file permissions
-        for path in &args.allow_file {
-            let cap = FsCapability::new_file(path.clone(), FsAccess::ReadWrite)?;
-            caps.add_fs(cap);
+        for path in &args.allow_ioctl {
+            if !path.exists() {
+                return Err(NonoError::PathNotFound(path.clone()));
+            }
+            let resolved = path
+                .canonicalize()
+                .map_err(|e| NonoError::PathCanonicalization {
+                    path: path.clone(),
+                    source: e,
+                })?;
+            self.add_ioctl_device(resolved);
         }
+        Ok(())
+    }
 
-        for path in &args.read_file {
-            let cap = FsCapability::new_file(path.clone(), FsAccess::Read)?;
-            caps.add_fs(cap);
-        }
+    /// Build capabilities from CLI arguments
+    pub fn from_args(args: &SandboxArgs) -> Result<Self> {
+        let mut caps = Self::new();
 
-        for path in &args.write_file {
-            let cap = FsCapability::new_file(path.clone(), FsAccess::Write)?;
-            caps.add_fs(cap);
-        }
+        caps.add_cli_paths(args)?;
 
-        // Process --net-block flag
+        // Process --net-block flag and TCP port rules
         caps.net_block = args.net_block;
+        caps.require_full_enforcement = args.require_full_enforcement;
+        caps.pid_namespace = args.isolate_pids || args.isolate;
+        caps.limits = ResourceLimits::from_args(args);
+        caps.add_port_rules(&args.allow_port, &args.allow_bind);
+        caps.add_allow_hosts(&args.allow_host);
 
         // Process command allow/block lists
-        caps.allowed_commands = args.allow_command.clone();
-        caps.blocked_commands = args.block_command.clone();
+        caps.add_command_lists(&args.allow_command, &args.block_command);
 
         caps.deduplicate();
         Ok(caps)
@@ -264,16 +714,28 @@ impl CapabilitySet {
         // Helper to process profile paths and add capabilities
         fn process_profile_paths(
             caps: &mut CapabilitySet,
-            paths: &[String],
+            paths: &[profile::PathEntry],
             workdir: &Path,
             access: FsAccess,
             is_file: bool,
         ) -> Result<()> {
-            for path_str in paths {
-                let path = profile::expand_vars(path_str, workdir);
+            for entry in paths {
+                let ttl = entry
+                    .ttl
+                    .as_deref()
+                    .map(|ttl| {
+                        parse_duration(ttl).ok_or_else(|| {
+                            NonoError::ProfileParse(format!(
+                                "invalid ttl '{}' for '{}' (expected e.g. 30s, 5m, 2h, 1d)",
+                                ttl, entry.path
+                            ))
+                        })
+                    })
+                    .transpose()?;
+                let path = profile::expand_vars(&entry.path, workdir);
                 if is_file {
                     if path.exists() && !path.is_dir() {
-                        caps.add_fs(FsCapability::new_file(path, access)?);
+                        caps.add_fs(FsCapability::new_file(path, access)?.with_ttl(ttl));
                     } else if path.exists() {
                         tracing::warn!(
                             "Profile path '{}' exists but is a directory, skipping (use allow/read/write for directories)",
@@ -283,7 +745,7 @@ impl CapabilitySet {
                         tracing::warn!("Profile path '{}' not found, skipping", path.display());
                     }
                 } else if path.exists() && path.is_dir() {
-                    caps.add_fs(FsCapability::new_dir(path, access)?);
+                    caps.add_fs(FsCapability::new_dir(path, access)?.with_ttl(ttl));
                 } else if path.exists() {
                     tracing::warn!(
                         "Profile path '{}' exists but is not a directory, skipping",
@@ -318,6 +780,20 @@ impl CapabilitySet {
             FsAccess::Write,
             false,
         )?;
+        process_profile_paths(
+            &mut caps,
+            &profile.filesystem.read_noexec,
+            workdir,
+            FsAccess::ReadNoExec,
+            false,
+        )?;
+        process_profile_paths(
+            &mut caps,
+            &profile.filesystem.exec_only,
+            workdir,
+            FsAccess::Execute,
+            false,
+        )?;
 
         // Process profile file permissions
         process_profile_paths(
@@ -342,44 +818,65 @@ impl CapabilitySet {
             true,
         )?;
 
-        // Merge CLI overrides (extend the profile)
-        for path in &args.allow {
-            let cap = FsCapability::new_dir(path.clone(), FsAccess::ReadWrite)?;
-            caps.add_fs(cap);
+        // Process profile exclusions. Unlike a missing grant, a missing
+        // exclusion cannot be skipped: there is nothing to mask, and the
+        // command could create the path and use it.
+        for entry in &profile.filesystem.deny {
+            let path = profile::expand_vars(entry, workdir);
+            let resolved = path.canonicalize().map_err(|_| {
+                NonoError::ProfileParse(format!(
+                    "deny path '{}' does not exist, so it cannot be protected",
+                    path.display()
+                ))
+            })?;
+            caps.add_deny(resolved);
         }
 
-        for path in &args.read {
-            let cap = FsCapability::new_dir(path.clone(), FsAccess::Read)?;
-            caps.add_fs(cap);
+        // Devices that may receive ioctls; missing ones are skipped
+        for entry in &profile.devices.ioctl {
+            let path = profile::expand_vars(entry, workdir);
+            match path.canonicalize() {
+                Ok(resolved) => caps.add_ioctl_device(resolved),
+                Err(_) => {
+                    tracing::warn!(
+                        "Profile ioctl device '{}' not found, skipping",
+                        path.display()
+                    )
+                }
+            }
         }
 
-        for path in &args.write {
-            let cap = FsCapability::new_dir(path.clone(), FsAccess::Write)?;
-            caps.add_fs(cap);
-        }
+        // Merge CLI overrides (extend the profile)
+        caps.add_cli_paths(args)?;
 
-        for path in &args.allow_file {
-            let cap = FsCapability::new_file(path.clone(), FsAccess::ReadWrite)?;
-            caps.add_fs(cap);
-        }
+        // Network: profile OR CLI flag can block network (network allowed by default)
+        caps.net_block = profile.network.block || args.net_block;
+        caps.require_full_enforcement = args.require_full_enforcement;
+        caps.pid_namespace = args.isolate_pids || args.isolate;
 
-        for path in &args.read_file {
-            let cap = FsCapability::new_file(path.clone(), FsAccess::Read)?;
-            caps.add_fs(cap);
-        }
+        // TCP port rules: profile and CLI lists are merged
+        caps.add_port_rules(&profile.network.connect_ports, &profile.network.bind_ports);
+        caps.add_port_rules(&args.allow_port, &args.allow_bind);
 
-        for path in &args.write_file {
-            let cap = FsCapability::new_file(path.clone(), FsAccess::Write)?;
-            caps.add_fs(cap);
-        }
+        // Host allowlist: profile and CLI lists are merged
+        caps.add_allow_hosts(&profile.network.allow_hosts);
+        caps.add_allow_hosts(&args.allow_host);
 
-        // Network: profile OR CLI flag can block network (network allowed by default)
-        caps.net_block = profile.network.block || args.net_block;
+        // Command allow/block lists: profile first, so the CLI wins on conflicts
+        caps.add_command_lists(&profile.commands.allow, &profile.commands.block);
+        caps.add_command_lists(&args.allow_command, &args.block_command);
+        caps.add_arg_rules(&profile.commands.rules, workdir);
+
+        // Syscall filter exceptions and additions (Linux)
+        caps.add_syscall_rules(&profile.syscalls.allow, &profile.syscalls.deny)?;
 
-        // Process command allow/block lists from CLI
-        // Profile support for commands will be added later
-        caps.allowed_commands = args.allow_command.clone();
-        caps.blocked_commands = args.block_command.clone();
+        // IPC scoping is on unless the profile opts out
+        caps.allow_abstract_sockets = profile.ipc.allow_abstract_sockets;
+        caps.allow_signals = profile.ipc.allow_signals;
+
+        // Resource limits: the CLI wins over the profile, limit by limit
+        caps.limits =
+            ResourceLimits::from_profile(&profile.limits)?.or(ResourceLimits::from_args(args));
 
         caps.deduplicate();
         Ok(caps)
@@ -394,18 +891,38 @@ impl CapabilitySet {
             lines.push("Filesystem:".to_string());
             for cap in &self.fs {
                 let kind = if cap.is_file { "file" } else { "dir" };
+                let ttl = cap
+                    .ttl
+                    .map(|ttl| format!(" for {}", format_duration(ttl)))
+                    .unwrap_or_default();
                 lines.push(format!(
-                    "  {} [{}] ({})",
+                    "  {} [{}] ({}){}",
                     cap.resolved.display(),
                     cap.access,
-                    kind
+                    kind,
+                    ttl
                 ));
             }
+            for path in &self.deny {
+                lines.push(format!("  {} [denied]", path.display()));
+            }
         }
 
         lines.push("Network:".to_string());
         if self.net_block {
             lines.push("  outbound: blocked".to_string());
+        } else if self.has_port_rules() {
+            if self.has_host_allowlist() {
+                lines.push(format!("  hosts: {}", self.allow_hosts.join(", ")));
+            }
+            lines.push(format!(
+                "  tcp connect: {}",
+                format_ports(&self.tcp_connect_ports)
+            ));
+            lines.push(format!(
+                "  tcp bind: {}",
+                format_ports(&self.tcp_bind_ports)
+            ));
         } else {
             lines.push("  outbound: allowed".to_string());
         }
@@ -418,6 +935,113 @@ impl CapabilitySet {
     }
 }
 
+/// Parse a duration like `30s`, `5m`, `2h`, `1d` or `1h30m`
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let mut total: u64 = 0;
+    let mut digits = String::new();
+    for c in s.trim().chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            _ => return None,
+        };
+        let n: u64 = digits.parse().ok()?;
+        total = total.checked_add(n.checked_mul(unit)?)?;
+        digits.clear();
+    }
+    if !digits.is_empty() || total == 0 {
+        return None;
+    }
+    Some(Duration::from_secs(total))
+}
+
+/// Split an optional `:DURATION` suffix off a path argument.
+///
+/// A path that exists as given is never split, so paths that happen to end
+/// in something like `:5m` still work.
+pub fn split_ttl(path: &Path) -> (PathBuf, Option<Duration>) {
+    if !path.exists() {
+        if let Some((base, suffix)) = path.to_str().and_then(|s| s.rsplit_once(':')) {
+            if let Some(ttl) = parse_duration(suffix) {
+                return (PathBuf::from(base), Some(ttl));
+            }
+        }
+    }
+    (path.to_path_buf(), None)
+}
+
+/// Format a duration compactly ("90s" becomes "1m30s")
+pub fn format_duration(duration: Duration) -> String {
+    let mut secs = duration.as_secs();
+    if secs == 0 {
+        return "0s".to_string();
+    }
+    let mut out = String::new();
+    for (unit, len) in [("d", 86_400), ("h", 3_600), ("m", 60), ("s", 1)] {
+        if secs >= len {
+            out.push_str(&format!("{}{}", secs / len, unit));
+            secs %= len;
+        }
+    }
+    out
+}
+
+/// Parse a size in bytes like `4096`, `512K`, `2G` or `1T` (binary units)
+pub fn parse_size(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let (digits, unit) = match s.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+        Some((i, _)) => s.split_at(i),
+        None => (s, ""),
+    };
+    let shift = match unit.to_ascii_uppercase().as_str() {
+        "" | "B" => 0,
+        "K" | "KB" => 10,
+        "M" | "MB" => 20,
+        "G" | "GB" => 30,
+        "T" | "TB" => 40,
+        _ => return None,
+    };
+    let n: u64 = digits.parse().ok()?;
+    if n == 0 {
+        return None;
+    }
+    n.checked_mul(1 << shift)
+}
+
+/// Format a size in bytes in the largest unit that divides it ("2G", "1536K")
+pub fn format_size(bytes: u64) -> String {
+    for (unit, shift) in [("T", 40), ("G", 30), ("M", 20), ("K", 10)] {
+        if bytes >= 1 << shift && bytes % (1 << shift) == 0 {
+            return format!("{}{}", bytes >> shift, unit);
+        }
+    }
+    bytes.to_string()
+}
+
+/// Format an expiry time as an RFC 3339 UTC timestamp
+pub fn format_timestamp(at: SystemTime) -> String {
+    chrono::DateTime::<chrono::Utc>::from(at).to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+/// Format a list of ports for display ("443, 8080" or "none")
+pub fn format_ports(ports: &[u16]) -> String {
+    if ports.is_empty() {
+        "none".to_string()
+    } else {
+        ports
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -524,17 +1148,7 @@ mod tests {
             allow_file: vec![],
             read_file: vec![],
             write_file: vec![],
-            net_block: false,
-            allow_command: vec![],
-            block_command: vec![],
-            secrets: None,
-            profile: None,
-            allow_cwd: false,
-            workdir: None,
-            trust_unsigned: false,
-            config: None,
-            verbose: 0,
-            dry_run: false,
+            ..Default::default()
         };
 
         let caps = CapabilitySet::from_args(&args).unwrap();
@@ -556,17 +1170,7 @@ mod tests {
             allow_file: vec![],
             read_file: vec![],
             write_file: vec![file_path],
-            net_block: false,
-            allow_command: vec![],
-            block_command: vec![],
-            secrets: None,
-            profile: None,
-            allow_cwd: false,
-            workdir: None,
-            trust_unsigned: false,
-            config: None,
-            verbose: 0,
-            dry_run: false,
+            ..Default::default()
         };
 
         let caps = CapabilitySet::from_args(&args).unwrap();
@@ -590,19 +1194,526 @@ mod tests {
             read_file: vec![],
             write_file: vec![],
             net_block: true,
-            allow_command: vec![],
-            block_command: vec![],
-            secrets: None,
-            profile: None,
-            allow_cwd: false,
-            workdir: None,
-            trust_unsigned: false,
-            config: None,
-            verbose: 0,
-            dry_run: false,
+            ..Default::default()
         };
 
         let caps = CapabilitySet::from_args(&args).unwrap();
         assert!(caps.net_block);
     }
+
+    #[test]
+    fn test_from_args_port_rules() {
+        let dir = tempdir().unwrap();
+        let args = SandboxArgs {
+            allow: vec![dir.path().to_path_buf()],
+            allow_port: vec![443, 80, 443],
+            allow_bind: vec![3000],
+            ..Default::default()
+        };
+
+        let caps = CapabilitySet::from_args(&args).unwrap();
+        assert!(caps.has_port_rules());
+        assert_eq!(caps.tcp_connect_ports, vec![80, 443]);
+        assert_eq!(caps.tcp_bind_ports, vec![3000]);
+    }
+
+    #[test]
+    fn test_port_rules_ignored_when_network_blocked() {
+        let caps = CapabilitySet {
+            net_block: true,
+            tcp_connect_ports: vec![443],
+            ..Default::default()
+        };
+        assert!(!caps.has_port_rules());
+
+        let caps = CapabilitySet::default();
+        assert!(!caps.has_port_rules());
+    }
+
+    #[test]
+    fn test_from_args_allow_hosts() {
+        let dir = tempdir().unwrap();
+        let args = SandboxArgs {
+            allow: vec![dir.path().to_path_buf()],
+            allow_host: vec![
+                "API.Anthropic.com".to_string(),
+                "api.anthropic.com.".to_string(),
+            ],
+            ..Default::default()
+        };
+
+        let mut caps = CapabilitySet::from_args(&args).unwrap();
+        assert_eq!(caps.allow_hosts, vec!["api.anthropic.com"]);
+        assert!(caps.has_host_allowlist());
+        // A host allowlist implies port filtering
+        assert!(caps.has_port_rules());
+        assert!(caps.effective_connect_ports().is_empty());
+
+        caps.proxy_port = Some(40000);
+        assert_eq!(caps.effective_connect_ports(), vec![40000]);
+    }
+
+    #[test]
+    fn test_from_profile_command_lists() {
+        let dir = tempdir().unwrap();
+        let profile: Profile = toml::from_str(
+            r#"
+            [commands]
+            allow = ["pip", "npm"]
+            block = ["curl", "wget"]
+            "#,
+        )
+        .unwrap();
+        let args = SandboxArgs {
+            allow_command: vec!["wget".to_string()],
+            block_command: vec!["npm".to_string(), "ssh".to_string()],
+            ..Default::default()
+        };
+
+        let caps = CapabilitySet::from_profile(&profile, dir.path(), &args).unwrap();
+        // The CLI overrides the profile where they disagree
+        assert_eq!(caps.allowed_commands, vec!["pip", "wget"]);
+        assert_eq!(caps.blocked_commands, vec!["curl", "npm", "ssh"]);
+    }
+
+    #[test]
+    fn test_from_profile_arg_rules() {
+        let dir = tempdir().unwrap();
+        let profile: Profile = toml::from_str(
+            r#"
+            [commands.rules]
+            rm = { deny_args = ["-r"], allow_paths = ["$WORKDIR/build"] }
+            git = { deny_subcommands = ["push --force"] }
+            "#,
+        )
+        .unwrap();
+
+        let caps =
+            CapabilitySet::from_profile(&profile, dir.path(), &SandboxArgs::default()).unwrap();
+        assert_eq!(caps.command_rules.len(), 2);
+        let (command, rule) = &caps.command_rules[1];
+        assert_eq!(command, "rm");
+        assert_eq!(rule.deny_args, vec!["-r"]);
+        assert_eq!(
+            rule.allow_paths,
+            vec![dir.path().join("build").display().to_string()]
+        );
+    }
+
+    #[test]
+    fn test_from_profile_ipc() {
+        let dir = tempdir().unwrap();
+        let args = SandboxArgs::default();
+
+        let caps = CapabilitySet::from_profile(&Profile::default(), dir.path(), &args).unwrap();
+        assert!(!caps.allow_abstract_sockets);
+        assert!(!caps.allow_signals);
+
+        let profile: Profile = toml::from_str("[ipc]\nallow_abstract_sockets = true").unwrap();
+        let caps = CapabilitySet::from_profile(&profile, dir.path(), &args).unwrap();
+        assert!(caps.allow_abstract_sockets);
+        assert!(!caps.allow_signals);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30s"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_duration("5m"), Some(Duration::from_secs(300)));
+        assert_eq!(parse_duration("2h"), Some(Duration::from_secs(7200)));
+        assert_eq!(parse_duration("1d"), Some(Duration::from_secs(86400)));
+        assert_eq!(parse_duration("1h30m"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("5"), None);
+        assert_eq!(parse_duration("0s"), None);
+        assert_eq!(parse_duration("5x"), None);
+        assert_eq!(parse_duration("m"), None);
+        assert_eq!(parse_duration("99999999999999999999d"), None);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(300)), "5m");
+        assert_eq!(format_duration(Duration::from_secs(5400)), "1h30m");
+        assert_eq!(format_duration(Duration::from_secs(90061)), "1d1h1m1s");
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("4096"), Some(4096));
+        assert_eq!(parse_size("512K"), Some(512 << 10));
+        assert_eq!(parse_size("512m"), Some(512 << 20));
+        assert_eq!(parse_size("2G"), Some(2 << 30));
+        assert_eq!(parse_size("2GB"), Some(2 << 30));
+        assert_eq!(parse_size("1T"), Some(1 << 40));
+        assert_eq!(parse_size(""), None);
+        assert_eq!(parse_size("0"), None);
+        assert_eq!(parse_size("G"), None);
+        assert_eq!(parse_size("1.5G"), None);
+        assert_eq!(parse_size("2X"), None);
+        assert_eq!(parse_size("99999999999T"), None);
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(2 << 30), "2G");
+        assert_eq!(format_size(1536 << 10), "1536K");
+        assert_eq!(format_size(1000), "1000");
+    }
+
+    #[test]
+    fn test_split_ttl() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().to_path_buf();
+
+        let with_ttl = PathBuf::from(format!("{}:5m", path.display()));
+        assert_eq!(
+            split_ttl(&with_ttl),
+            (path.clone(), Some(Duration::from_secs(300)))
+        );
+        assert_eq!(split_ttl(&path), (path.clone(), None));
+
+        // An existing path is taken literally even if it looks like it has a TTL
+        let literal = dir.path().join("build:5m");
+        fs::create_dir(&literal).unwrap();
+        assert_eq!(split_ttl(&literal), (literal.clone(), None));
+
+        // Not a duration: left alone (and will fail as a missing path)
+        let odd = PathBuf::from("/nonexistent:abc");
+        assert_eq!(split_ttl(&odd), (odd.clone(), None));
+    }
+
+    #[test]
+    fn test_from_args_ttl() {
+        let dir = tempdir().unwrap();
+        let args = SandboxArgs {
+            allow: vec![PathBuf::from(format!("{}:10m", dir.path().display()))],
+            ..Default::default()
+        };
+
+        let mut caps = CapabilitySet::from_args(&args).unwrap();
+        assert!(caps.has_ttl());
+        assert_eq!(caps.fs[0].ttl, Some(Duration::from_secs(600)));
+        assert_eq!(caps.fs[0].resolved, dir.path().canonicalize().unwrap());
+
+        let start = SystemTime::now();
+        caps.arm_expiry(start);
+        assert!(!caps.fs[0].is_expired_at(start));
+        assert!(caps.fs[0].is_expired_at(start + Duration::from_secs(600)));
+        assert!(caps
+            .active_at(start + Duration::from_secs(601))
+            .fs
+            .is_empty());
+    }
+
+    #[test]
+    fn test_deduplicate_keeps_longest_ttl() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().to_path_buf();
+        let cap = |access, ttl: Option<u64>| {
+            FsCapability::new_dir(path.clone(), access)
+                .unwrap()
+                .with_ttl(ttl.map(Duration::from_secs))
+        };
+
+        let mut caps = CapabilitySet::new();
+        caps.add_fs(cap(FsAccess::ReadWrite, Some(60)));
+        caps.add_fs(cap(FsAccess::ReadWrite, Some(600)));
+        caps.deduplicate();
+        assert_eq!(caps.fs.len(), 1);
+        assert_eq!(caps.fs[0].ttl, Some(Duration::from_secs(600)));
+
+        // A permanent grant outlives any TTL
+        let mut caps = CapabilitySet::new();
+        caps.add_fs(cap(FsAccess::Read, Some(60)));
+        caps.add_fs(cap(FsAccess::Read, None));
+        caps.deduplicate();
+        assert_eq!(caps.fs.len(), 1);
+        assert_eq!(caps.fs[0].ttl, None);
+
+        // A permanent read must not make a time-limited write permanent
+        let mut caps = CapabilitySet::new();
+        caps.add_fs(cap(FsAccess::ReadWrite, Some(60)));
+        caps.add_fs(cap(FsAccess::Read, None));
+        caps.deduplicate();
+        assert_eq!(caps.fs.len(), 2);
+    }
+
+    #[test]
+    fn test_deduplicate_by_included_access() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().to_path_buf();
+        let cap = |access| FsCapability::new_dir(path.clone(), access).unwrap();
+
+        // Read includes read-without-exec
+        let mut caps = CapabilitySet::new();
+        caps.add_fs(cap(FsAccess::ReadNoExec));
+        caps.add_fs(cap(FsAccess::Read));
+        caps.deduplicate();
+        assert_eq!(caps.fs.len(), 1);
+        assert_eq!(caps.fs[0].access, FsAccess::Read);
+
+        // Neither includes the other: both are needed
+        let mut caps = CapabilitySet::new();
+        caps.add_fs(cap(FsAccess::ReadNoExec));
+        caps.add_fs(cap(FsAccess::Execute));
+        caps.deduplicate();
+        assert_eq!(caps.fs.len(), 2);
+    }
+
+    #[test]
+    fn test_fs_access_modes() {
+        assert!(FsAccess::ReadNoExec.reads() && !FsAccess::ReadNoExec.executes());
+        assert!(FsAccess::Execute.executes() && !FsAccess::Execute.reads());
+        assert!(FsAccess::Read.includes(FsAccess::ReadNoExec));
+        assert!(FsAccess::Read.includes(FsAccess::Execute));
+        assert!(!FsAccess::ReadNoExec.includes(FsAccess::Read));
+        assert!(!FsAccess::Write.includes(FsAccess::Execute));
+
+        // A read-no-exec grant serves reads, not execs
+        assert!(FsAccess::ReadNoExec.allows(FsAccess::Read));
+        assert!(!FsAccess::ReadNoExec.allows(FsAccess::Execute));
+        assert!(FsAccess::Execute.allows(FsAccess::Execute));
+        assert!(FsAccess::Read.allows(FsAccess::Execute));
+
+        // Landlock needs ReadFile for execve, so exec-only files stay
+        // readable on Linux
+        assert_eq!(
+            FsAccess::Execute.allows(FsAccess::Read),
+            cfg!(target_os = "linux")
+        );
+        assert!(!FsAccess::Execute.allows(FsAccess::Write));
+    }
+
+    #[test]
+    fn test_from_args_noexec_and_exec_only() {
+        let dir = tempdir().unwrap();
+        let bin = dir.path().join("bin");
+        fs::create_dir(&bin).unwrap();
+        let args = SandboxArgs {
+            read_noexec: vec![dir.path().to_path_buf()],
+            exec_only: vec![bin.clone()],
+            ..Default::default()
+        };
+
+        let caps = CapabilitySet::from_args(&args).unwrap();
+        assert_eq!(caps.fs.len(), 2);
+        assert_eq!(caps.fs[0].access, FsAccess::ReadNoExec);
+        assert_eq!(caps.fs[1].access, FsAccess::Execute);
+        assert_eq!(caps.fs[1].resolved, bin.canonicalize().unwrap());
+    }
+
+    #[test]
+    fn test_grants_write_to() {
+        let dir = tempdir().unwrap();
+        let sub = dir.path().join("sub");
+        fs::create_dir(&sub).unwrap();
+
+        let mut caps = CapabilitySet::new();
+        caps.add_fs(FsCapability::new_dir(sub.clone(), FsAccess::Read).unwrap());
+        let root = dir.path().canonicalize().unwrap();
+        let sub = sub.canonicalize().unwrap();
+        assert!(!caps.grants_write_to(&sub.join("log")));
+
+        caps.add_fs(FsCapability::new_dir(dir.path().to_path_buf(), FsAccess::Write).unwrap());
+        assert!(caps.grants_write_to(&sub.join("log")));
+        assert!(caps.grants_write_to(&root.join("log")));
+        assert!(!caps.grants_write_to(Path::new("/elsewhere/log")));
+    }
+
+    #[test]
+    fn test_access_expired() {
+        let dir = tempdir().unwrap();
+        let sub = dir.path().join("sub");
+        fs::create_dir(&sub).unwrap();
+
+        let mut caps = CapabilitySet::new();
+        caps.add_fs(
+            FsCapability::new_dir(dir.path().to_path_buf(), FsAccess::ReadWrite)
+                .unwrap()
+                .with_ttl(Some(Duration::from_secs(60))),
+        );
+        caps.add_fs(FsCapability::new_dir(sub.clone(), FsAccess::Read).unwrap());
+        let start = SystemTime::now();
+        caps.arm_expiry(start);
+        let later = start + Duration::from_secs(61);
+
+        let root = dir.path().canonicalize().unwrap();
+        let sub = sub.canonicalize().unwrap();
+        assert!(!caps.access_expired(&root.join("f"), FsAccess::Write, start));
+        assert!(caps.access_expired(&root.join("f"), FsAccess::Write, later));
+        // Still readable through the permanent grant, but no longer writable
+        assert!(!caps.access_expired(&sub.join("f"), FsAccess::Read, later));
+        assert!(caps.access_expired(&sub.join("f"), FsAccess::Write, later));
+        // Never granted at all: not an expiry (the sandbox denies it anyway)
+        assert!(!caps.access_expired(Path::new("/elsewhere"), FsAccess::Read, later));
+    }
+
+    #[test]
+    fn test_from_args_deny() {
+        let dir = tempdir().unwrap();
+        let hooks = dir.path().join(".git/hooks");
+        fs::create_dir_all(&hooks).unwrap();
+        let args = SandboxArgs {
+            allow: vec![dir.path().to_path_buf()],
+            deny: vec![hooks.clone()],
+            ..Default::default()
+        };
+
+        let caps = CapabilitySet::from_args(&args).unwrap();
+        let hooks = hooks.canonicalize().unwrap();
+        assert_eq!(caps.deny, vec![hooks.clone()]);
+        assert!(caps.is_denied(&hooks.join("pre-commit")));
+        assert!(!caps.is_denied(&hooks.with_file_name("config")));
+
+        // A missing path cannot be protected, so it is an error like any other flag
+        let args = SandboxArgs {
+            allow: vec![dir.path().to_path_buf()],
+            deny: vec![dir.path().join(".env")],
+            ..Default::default()
+        };
+        assert!(matches!(
+            CapabilitySet::from_args(&args),
+            Err(NonoError::PathNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_profile_deny() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".git/hooks")).unwrap();
+        let profile: Profile =
+            toml::from_str("[filesystem]\ndeny = [\"$WORKDIR/.git/hooks\"]").unwrap();
+        let caps =
+            CapabilitySet::from_profile(&profile, dir.path(), &SandboxArgs::default()).unwrap();
+        assert_eq!(
+            caps.deny,
+            vec![dir.path().join(".git/hooks").canonicalize().unwrap()]
+        );
+
+        // A missing entry is an error, as with --deny
+        let profile: Profile = toml::from_str("[filesystem]\ndeny = [\"$WORKDIR/.env\"]").unwrap();
+        assert!(matches!(
+            CapabilitySet::from_profile(&profile, dir.path(), &SandboxArgs::default()),
+            Err(NonoError::ProfileParse(_))
+        ));
+    }
+
+    #[test]
+    fn test_ioctl_devices() {
+        let dir = tempdir().unwrap();
+        let profile: Profile =
+            toml::from_str("[devices]\nioctl = [\"/dev/null\", \"/dev/no-such-device\"]").unwrap();
+        let args = SandboxArgs {
+            allow_ioctl: vec![PathBuf::from("/dev/null"), PathBuf::from("/dev/zero")],
+            ..Default::default()
+        };
+
+        // Missing profile devices are skipped; repeats are merged
+        let caps = CapabilitySet::from_profile(&profile, dir.path(), &args).unwrap();
+        assert_eq!(
+            caps.ioctl_devices,
+            vec![PathBuf::from("/dev/null"), PathBuf::from("/dev/zero")]
+        );
+
+        let args = SandboxArgs {
+            allow_ioctl: vec![PathBuf::from("/dev/no-such-device")],
+            ..Default::default()
+        };
+        assert!(matches!(
+            CapabilitySet::from_args(&args),
+            Err(NonoError::PathNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_syscall_rules() {
+        let dir = tempdir().unwrap();
+        let profile: Profile = toml::from_str(
+            "[syscalls]\nallow = [\"ptrace\", \"bpf\"]\ndeny = [\"io_uring_setup\", \"bpf\"]",
+        )
+        .unwrap();
+
+        // A syscall in both lists ends up denied, as the deny list comes last
+        let caps =
+            CapabilitySet::from_profile(&profile, dir.path(), &SandboxArgs::default()).unwrap();
+        assert_eq!(caps.allowed_syscalls, vec!["ptrace"]);
+        assert_eq!(caps.denied_syscalls, vec!["io_uring_setup", "bpf"]);
+
+        let denied = crate::config::denied_syscalls(&caps.allowed_syscalls, &caps.denied_syscalls);
+        assert!(!denied.contains(&"ptrace".to_string()));
+        assert!(denied.contains(&"bpf".to_string()));
+        assert!(denied.contains(&"io_uring_setup".to_string()));
+
+        #[cfg(target_os = "linux")]
+        {
+            let profile: Profile = toml::from_str("[syscalls]\ndeny = [\"no_such_call\"]").unwrap();
+            assert!(matches!(
+                CapabilitySet::from_profile(&profile, dir.path(), &SandboxArgs::default()),
+                Err(NonoError::ProfileParse(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_from_profile_limits() {
+        let dir = tempdir().unwrap();
+        let profile: Profile = toml::from_str(
+            "[limits]\nmemory = \"2G\"\nprocesses = 256\ncpu_time = \"10m\"\nopen_files = 1024",
+        )
+        .unwrap();
+
+        // The CLI overrides the profile limit by limit
+        let args = SandboxArgs {
+            max_memory: Some(512 << 20),
+            max_file_size: Some(1 << 30),
+            ..Default::default()
+        };
+        let caps = CapabilitySet::from_profile(&profile, dir.path(), &args).unwrap();
+        assert_eq!(
+            caps.limits,
+            ResourceLimits {
+                memory: Some(512 << 20),
+                processes: Some(256),
+                cpu_time: Some(Duration::from_secs(600)),
+                file_size: Some(1 << 30),
+                open_files: Some(1024),
+            }
+        );
+        assert_eq!(
+            caps.limits.describe(),
+            vec![
+                "memory 512M",
+                "processes 256",
+                "CPU time 10m",
+                "file size 1G",
+                "open files 1024"
+            ]
+        );
+
+        let profile: Profile = toml::from_str("[limits]\nmemory = \"lots\"").unwrap();
+        assert!(matches!(
+            CapabilitySet::from_profile(&profile, dir.path(), &SandboxArgs::default()),
+            Err(NonoError::ProfileParse(_))
+        ));
+    }
+
+    #[test]
+    fn test_add_deny_drops_nested_paths() {
+        let mut caps = CapabilitySet::new();
+        caps.add_deny(PathBuf::from("/work/.git/hooks"));
+        caps.add_deny(PathBuf::from("/work/.git"));
+        caps.add_deny(PathBuf::from("/work/.git/config"));
+        caps.add_deny(PathBuf::from("/work/.env"));
+        assert_eq!(
+            caps.deny,
+            vec![PathBuf::from("/work/.git"), PathBuf::from("/work/.env")]
+        );
+    }
+
+    #[test]
+    fn test_format_ports() {
+        assert_eq!(format_ports(&[]), "none");
+        assert_eq!(format_ports(&[443]), "443");
+        assert_eq!(format_ports(&[80, 443]), "80, 443");
+    }
 }
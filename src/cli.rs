@@ -1,5 +1,6 @@
 use clap::{Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
+use std::time::Duration;
 
 /// nono - The opposite of YOLO
 ///
@@ -118,17 +119,45 @@ pub enum Commands {
     nono setup -v --profiles
 ")]
     Setup(SetupArgs),
+
+    /// Manage user profiles
+    #[command(after_help = "EXAMPLES:
+    # Sign a user profile with your default minisign key (writes my-agent.toml.minisig)
+    nono profile sign my-agent
+
+    # Sign with a specific secret key
+    nono profile sign ./my-agent.toml --key ~/.minisign/team.key
+
+    # Embed the signature in the profile instead of a .minisig file
+    nono profile sign my-agent --embed
+")]
+    Profile(ProfileArgs),
+
+    /// Show or update the security lists
+    #[command(after_help = "EXAMPLES:
+    # Show the sensitive paths, dangerous commands and system read paths in effect
+    nono lists show
+
+    # Only categories matching 'ssh'
+    nono lists show --category ssh
+
+    # Install a signed update (security-lists.toml + security-lists.toml.minisig)
+    nono lists update --from ./security-lists.toml
+")]
+    Lists(ListsArgs),
 }
 
-#[derive(Parser, Debug, Clone)]
+#[derive(Parser, Debug, Clone, Default)]
 pub struct SandboxArgs {
     // === Directory permissions (recursive) ===
     /// Directories to allow read+write access (recursive).
     /// Combines full read and write permissions (see --read and --write for details).
+    /// Append :DURATION (e.g. DIR:30m) to revoke the grant after that long (Linux).
     #[arg(long, short = 'a', value_name = "DIR")]
     pub allow: Vec<PathBuf>,
 
-    /// Directories to allow read-only access (recursive)
+    /// Directories to allow read-only access (recursive).
+    /// Accepts a :DURATION suffix like --allow.
     #[arg(long, short = 'r', value_name = "DIR")]
     pub read: Vec<PathBuf>,
 
@@ -136,9 +165,23 @@ pub struct SandboxArgs {
     /// Write access includes: creating files/dirs, modifying content, deleting files,
     /// renaming/moving files (atomic writes), and truncating files.
     /// Note: Directory deletion is NOT included for safety.
+    /// Accepts a :DURATION suffix like --allow.
     #[arg(long, short = 'w', value_name = "DIR")]
     pub write: Vec<PathBuf>,
 
+    /// Directories to allow read-only access without executing files (recursive),
+    /// e.g. downloads or node_modules. Accepts a :DURATION suffix like --allow.
+    #[arg(long, value_name = "DIR")]
+    pub read_noexec: Vec<PathBuf>,
+
+    /// Directories whose files may be executed but not listed (recursive).
+    /// On macOS the files cannot be read either, so scripts do not run. On
+    /// Linux the files stay readable by name (Landlock needs read access to
+    /// execute), so exec-only cannot be enforced there.
+    /// Accepts a :DURATION suffix like --allow.
+    #[arg(long, value_name = "DIR")]
+    pub exec_only: Vec<PathBuf>,
+
     // === Single file permissions ===
     /// Single files to allow read+write access
     #[arg(long, value_name = "FILE")]
@@ -153,11 +196,41 @@ pub struct SandboxArgs {
     #[arg(long, value_name = "FILE")]
     pub write_file: Vec<PathBuf>,
 
+    // === Exclusions ===
+    /// Hide a file or directory from the sandbox even inside an allowed directory
+    /// (e.g. --allow . --deny .git/hooks). The path must exist.
+    /// On Linux this uses a private user and mount namespace and implies --supervised.
+    #[arg(long, value_name = "PATH")]
+    pub deny: Vec<PathBuf>,
+
+    // === Devices ===
+    /// Allow ioctls on this device, or on the devices in this directory (repeatable,
+    /// e.g. /dev/tty or /dev/pts). All other device ioctls are denied (Linux 6.10+).
+    /// Opening the device still needs read or write access.
+    #[arg(long, value_name = "DEVICE")]
+    pub allow_ioctl: Vec<PathBuf>,
+
     /// Block network access (network allowed by default; use this flag to block)
-    /// Note: Per-host filtering not supported by OS sandbox; this is on/off only
+    /// Note: Per-host filtering is done by a local proxy; see --allow-host
     #[arg(long)]
     pub net_block: bool,
 
+    /// Allow outgoing TCP connections to this port only (repeatable).
+    /// Once any port rule is given, all other TCP connects and binds are denied.
+    #[arg(long, value_name = "PORT")]
+    pub allow_port: Vec<u16>,
+
+    /// Allow binding and listening on this TCP port (repeatable).
+    /// Once any port rule is given, all other TCP connects and binds are denied.
+    #[arg(long, value_name = "PORT")]
+    pub allow_bind: Vec<u16>,
+
+    /// Allow connections to this host through nono's filtering proxy (repeatable).
+    /// Accepts exact names or wildcards like '*.github.com'. Implies --supervised;
+    /// the command is pointed at the proxy via HTTP_PROXY/HTTPS_PROXY.
+    #[arg(long, value_name = "HOST")]
+    pub allow_host: Vec<String>,
+
     // === Command blocking ===
     /// Allow a normally-blocked dangerous command (use with caution).
     /// By default, destructive commands like rm, dd, chmod are blocked.
@@ -168,6 +241,75 @@ pub struct SandboxArgs {
     #[arg(long, value_name = "CMD")]
     pub block_command: Vec<String>,
 
+    // === Resource limits ===
+    /// Limit the command's address space (e.g. 512M, 2G). Allocations beyond
+    /// it fail. Runtimes that reserve a lot of virtual memory up front, such as
+    /// Go, Node.js or the JVM, need a generous value.
+    #[arg(long, value_name = "SIZE", value_parser = parse_size_arg)]
+    pub max_memory: Option<u64>,
+
+    /// Limit the number of processes of your user (RLIMIT_NPROC), e.g. to stop
+    /// fork bombs. Counts processes outside the sandbox too; not enforced for root.
+    #[arg(long, value_name = "N")]
+    pub max_procs: Option<u64>,
+
+    /// Limit the CPU time the command and each of its processes may use
+    /// (e.g. 30s, 10m). It is killed with SIGXCPU when it runs out.
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration_arg)]
+    pub max_cpu_time: Option<Duration>,
+
+    /// Limit the size of files the command writes (e.g. 100M). Writing
+    /// beyond it kills the command with SIGXFSZ.
+    #[arg(long, value_name = "SIZE", value_parser = parse_size_arg)]
+    pub max_file_size: Option<u64>,
+
+    /// Limit the number of files the command may have open at once
+    #[arg(long, value_name = "N")]
+    pub max_open_files: Option<u64>,
+
+    // === Execution options ===
+    /// Fail instead of running best-effort when the kernel cannot enforce a
+    /// requested protection, such as network filtering on Linux before 6.7.
+    #[arg(long)]
+    pub require_full_enforcement: bool,
+
+    /// Run the command under an unsandboxed supervisor process.
+    /// Only the command is sandboxed; nono stays alive outside the sandbox to
+    /// wait for it, report diagnostics, and clean up after it exits.
+    #[arg(long)]
+    pub supervised: bool,
+
+    /// Append a JSONL audit log of the session to this file: capabilities,
+    /// diagnostics, blocked commands, injected secret names, and exit status.
+    /// Implies --supervised so the log is written from outside the sandbox.
+    #[arg(long, value_name = "FILE")]
+    pub audit_log: Option<PathBuf>,
+
+    /// Ask on the terminal before the sandbox denies a file open or TCP
+    /// connect, and allow it once, for the session, or never (Linux only).
+    /// Implies --supervised.
+    #[arg(long)]
+    pub prompt: bool,
+
+    /// Capture all writes to granted directories in a copy-on-write overlay.
+    /// When the command exits, review the changes and commit, discard, or
+    /// export them as a patch (Linux only). Implies --supervised.
+    #[arg(long)]
+    pub ephemeral: bool,
+
+    /// Run the command in a fresh root that contains only the granted paths,
+    /// system read paths, a private /tmp and a private /proc, so nothing else
+    /// even exists for it (Linux only, needs user namespaces). Implies
+    /// --isolate-pids and --supervised.
+    #[arg(long)]
+    pub isolate: bool,
+
+    /// Run the command in a PID namespace of its own, with a private /proc,
+    /// so it cannot see or signal other processes (Linux only, needs user
+    /// namespaces). Implied by --isolate. Implies --supervised.
+    #[arg(long)]
+    pub isolate_pids: bool,
+
     // === Secrets options ===
     /// Load secrets from system keystore and inject as environment variables.
     /// Use with --profile to load secrets defined in the profile's [secrets] section,
@@ -257,6 +399,62 @@ pub struct SetupArgs {
     pub verbose: u8,
 }
 
+#[derive(Parser, Debug)]
+pub struct ProfileArgs {
+    #[command(subcommand)]
+    pub command: ProfileCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ProfileCommands {
+    /// Sign a user profile with minisign
+    Sign(ProfileSignArgs),
+}
+
+#[derive(Parser, Debug)]
+pub struct ProfileSignArgs {
+    /// Profile name (in ~/.config/nono/profiles/) or path to a profile file
+    #[arg(value_name = "NAME_OR_PATH")]
+    pub profile: String,
+
+    /// minisign secret key (defaults to minisign's own default key)
+    #[arg(long, short = 'k', value_name = "FILE")]
+    pub key: Option<PathBuf>,
+
+    /// Embed the signature in the profile's [meta] section instead of
+    /// writing a detached .minisig file
+    #[arg(long)]
+    pub embed: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct ListsArgs {
+    #[command(subcommand)]
+    pub command: ListsCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ListsCommands {
+    /// Print the effective security lists and where each entry comes from
+    Show(ListsShowArgs),
+    /// Install a signed security lists update
+    Update(ListsUpdateArgs),
+}
+
+#[derive(Parser, Debug)]
+pub struct ListsShowArgs {
+    /// Only show categories whose name contains this (case-insensitive)
+    #[arg(long, value_name = "NAME")]
+    pub category: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+pub struct ListsUpdateArgs {
+    /// security-lists.toml to install; its signature is read from FILE.minisig
+    #[arg(long, value_name = "FILE")]
+    pub from: PathBuf,
+}
+
 #[derive(Parser, Debug)]
 pub struct WhyArgs {
     /// Path to check
@@ -308,6 +506,10 @@ pub struct WhyArgs {
     #[arg(long, value_name = "FILE")]
     pub write_file: Vec<PathBuf>,
 
+    /// Paths hidden from the sandbox (for query context)
+    #[arg(long, value_name = "PATH")]
+    pub deny: Vec<PathBuf>,
+
     /// Block network access (for query context)
     #[arg(long)]
     pub net_block: bool,
@@ -366,6 +568,20 @@ pub enum WhyOp {
     /// Read and write access
     #[value(name = "readwrite")]
     ReadWrite,
+    /// Executing a file
+    Exec,
+}
+
+/// Parse a `--max-*` size argument such as `512M`
+fn parse_size_arg(s: &str) -> Result<u64, String> {
+    crate::capability::parse_size(s)
+        .ok_or_else(|| format!("invalid size '{}' (expected e.g. 512M, 2G)", s))
+}
+
+/// Parse a `--max-cpu-time` argument such as `10m`
+fn parse_duration_arg(s: &str) -> Result<Duration, String> {
+    crate::capability::parse_duration(s)
+        .ok_or_else(|| format!("invalid duration '{}' (expected e.g. 30s, 10m, 2h)", s))
 }
 
 #[cfg(test)]
@@ -384,6 +600,164 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_run_supervised() {
+        let cli = Cli::parse_from(["nono", "run", "--supervised", "--allow", ".", "echo"]);
+        match cli.command {
+            Commands::Run(args) => {
+                assert!(args.sandbox.supervised);
+                assert_eq!(args.command, vec!["echo"]);
+            }
+            _ => panic!("Expected Run command"),
+        }
+    }
+
+    #[test]
+    fn test_run_audit_log() {
+        let cli = Cli::parse_from([
+            "nono",
+            "run",
+            "--audit-log",
+            "./session.jsonl",
+            "--allow",
+            ".",
+            "echo",
+        ]);
+        match cli.command {
+            Commands::Run(args) => {
+                assert_eq!(
+                    args.sandbox.audit_log,
+                    Some(PathBuf::from("./session.jsonl"))
+                );
+            }
+            _ => panic!("Expected Run command"),
+        }
+    }
+
+    #[test]
+    fn test_run_prompt() {
+        let cli = Cli::parse_from(["nono", "run", "--prompt", "--allow", ".", "echo"]);
+        match cli.command {
+            Commands::Run(args) => {
+                assert!(args.sandbox.prompt);
+                assert!(!args.sandbox.supervised);
+            }
+            _ => panic!("Expected Run command"),
+        }
+    }
+
+    #[test]
+    fn test_run_ephemeral() {
+        let cli = Cli::parse_from(["nono", "run", "--ephemeral", "--allow", ".", "make"]);
+        match cli.command {
+            Commands::Run(args) => {
+                assert!(args.sandbox.ephemeral);
+                assert!(!args.sandbox.supervised);
+            }
+            _ => panic!("Expected Run command"),
+        }
+    }
+
+    #[test]
+    fn test_run_isolate() {
+        let cli = Cli::parse_from(["nono", "run", "--isolate", "--allow", ".", "ls"]);
+        match cli.command {
+            Commands::Run(args) => {
+                assert!(args.sandbox.isolate);
+                assert!(!args.sandbox.ephemeral);
+            }
+            _ => panic!("Expected Run command"),
+        }
+    }
+
+    #[test]
+    fn test_run_isolate_pids() {
+        let cli = Cli::parse_from(["nono", "run", "--isolate-pids", "--allow", ".", "ls"]);
+        match cli.command {
+            Commands::Run(args) => {
+                assert!(args.sandbox.isolate_pids);
+                assert!(!args.sandbox.isolate);
+            }
+            _ => panic!("Expected Run command"),
+        }
+    }
+
+    #[test]
+    fn test_run_resource_limits() {
+        let cli = Cli::parse_from([
+            "nono",
+            "run",
+            "--max-memory",
+            "2G",
+            "--max-procs",
+            "256",
+            "--max-cpu-time",
+            "10m",
+            "--max-file-size",
+            "100M",
+            "--max-open-files",
+            "1024",
+            "make",
+        ]);
+        match cli.command {
+            Commands::Run(args) => {
+                assert_eq!(args.sandbox.max_memory, Some(2 << 30));
+                assert_eq!(args.sandbox.max_procs, Some(256));
+                assert_eq!(args.sandbox.max_cpu_time, Some(Duration::from_secs(600)));
+                assert_eq!(args.sandbox.max_file_size, Some(100 << 20));
+                assert_eq!(args.sandbox.max_open_files, Some(1024));
+            }
+            _ => panic!("Expected Run command"),
+        }
+
+        assert!(Cli::try_parse_from(["nono", "run", "--max-memory", "lots", "make"]).is_err());
+        assert!(Cli::try_parse_from(["nono", "run", "--max-cpu-time", "10", "make"]).is_err());
+    }
+
+    #[test]
+    fn test_run_port_rules() {
+        let cli = Cli::parse_from([
+            "nono",
+            "run",
+            "--allow-port",
+            "443",
+            "--allow-port",
+            "80",
+            "--allow-bind",
+            "3000",
+            "echo",
+        ]);
+        match cli.command {
+            Commands::Run(args) => {
+                assert_eq!(args.sandbox.allow_port, vec![443, 80]);
+                assert_eq!(args.sandbox.allow_bind, vec![3000]);
+            }
+            _ => panic!("Expected Run command"),
+        }
+    }
+
+    #[test]
+    fn test_run_allow_host() {
+        let cli = Cli::parse_from([
+            "nono",
+            "run",
+            "--allow-host",
+            "api.anthropic.com",
+            "--allow-host",
+            "*.github.com",
+            "echo",
+        ]);
+        match cli.command {
+            Commands::Run(args) => {
+                assert_eq!(
+                    args.sandbox.allow_host,
+                    vec!["api.anthropic.com", "*.github.com"]
+                );
+            }
+            _ => panic!("Expected Run command"),
+        }
+    }
+
     #[test]
     fn test_run_with_separator() {
         let cli = Cli::parse_from(["nono", "run", "--allow", ".", "--", "echo", "hello"]);
@@ -538,4 +912,50 @@ mod tests {
             _ => panic!("Expected Shell command"),
         }
     }
+
+    #[test]
+    fn test_profile_sign() {
+        let cli = Cli::parse_from([
+            "nono",
+            "profile",
+            "sign",
+            "my-agent",
+            "--key",
+            "/tmp/k.key",
+            "--embed",
+        ]);
+        match cli.command {
+            Commands::Profile(args) => match args.command {
+                ProfileCommands::Sign(sign) => {
+                    assert_eq!(sign.profile, "my-agent");
+                    assert_eq!(sign.key, Some(PathBuf::from("/tmp/k.key")));
+                    assert!(sign.embed);
+                }
+            },
+            _ => panic!("Expected Profile command"),
+        }
+    }
+
+    #[test]
+    fn test_lists_commands() {
+        let cli = Cli::parse_from(["nono", "lists", "show", "--category", "ssh"]);
+        match cli.command {
+            Commands::Lists(args) => match args.command {
+                ListsCommands::Show(show) => assert_eq!(show.category.as_deref(), Some("ssh")),
+                _ => panic!("Expected lists show"),
+            },
+            _ => panic!("Expected Lists command"),
+        }
+
+        let cli = Cli::parse_from(["nono", "lists", "update", "--from", "lists.toml"]);
+        match cli.command {
+            Commands::Lists(args) => match args.command {
+                ListsCommands::Update(update) => {
+                    assert_eq!(update.from, PathBuf::from("lists.toml"))
+                }
+                _ => panic!("Expected lists update"),
+            },
+            _ => panic!("Expected Lists command"),
+        }
+    }
 }
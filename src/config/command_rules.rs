@@ -0,0 +1,342 @@
+//! Argument rules for commands
+//!
+//! The command blocklist decides *whether* a command may run. A command that
+//! may run can still be held to rules on its arguments, from the security
+//! lists (`[command_rules]`) or a profile (`[commands.rules]`):
+//!
+//! - `deny_args`: options it may not be given. Bundled short options count
+//!   (`-r` matches `-fr`), and so do long options with a value (`--force`
+//!   matches `--force=yes`). After `--` everything is an operand, and
+//!   operands only match a pattern exactly (e.g. `/`).
+//! - `allow_paths`: directories every operand must be inside. Operands are
+//!   resolved against the caller's working directory, with the parent
+//!   directory canonicalized; an operand that climbs out with `..` past a
+//!   missing directory is refused.
+//! - `deny_subcommands`: word sequences such as `push --force`. The first
+//!   word must be one of the arguments and the rest must follow it, matched
+//!   like `deny_args`.
+//!
+//! Every rule for the command applies. Option values are not told apart from
+//! operands, so with `allow_paths` an option taking a separate value (like
+//! `cp -t DIR`) has its value checked as a path.
+
+use super::security_lists::CommandRule;
+use std::ffi::{OsStr, OsString};
+use std::path::{Component, Path, PathBuf};
+
+/// Check the arguments of `command` (a name or path) against `rules`.
+///
+/// `args` excludes `argv[0]`; relative operands resolve against `cwd`.
+/// Returns why the invocation is refused, or None if every rule allows it.
+pub fn check_command_args<S: AsRef<OsStr>>(
+    command: impl AsRef<OsStr>,
+    args: &[S],
+    cwd: &Path,
+    rules: &[(String, CommandRule)],
+) -> Option<String> {
+    let command = command.as_ref();
+    let name = Path::new(command).file_name().unwrap_or(command);
+    let args: Vec<&OsStr> = args.iter().map(|a| a.as_ref()).collect();
+
+    rules
+        .iter()
+        .filter(|(rule_command, _)| OsStr::new(rule_command) == name)
+        .find_map(|(rule_command, rule)| check_rule(rule_command, rule, &args, cwd))
+}
+
+fn check_rule(command: &str, rule: &CommandRule, args: &[&OsStr], cwd: &Path) -> Option<String> {
+    let (options, operands) = split_args(args);
+
+    for pattern in &rule.deny_args {
+        // A pattern that is not an option (like "/") matches operands exactly
+        let denied = if pattern.starts_with('-') {
+            options.iter().find(|arg| arg_matches(arg, pattern))
+        } else {
+            operands.iter().find(|arg| **arg == OsStr::new(pattern))
+        };
+        if let Some(arg) = denied {
+            return Some(format!(
+                "argument '{}' is not allowed for {} (rule: {})",
+                arg.to_string_lossy(),
+                command,
+                pattern
+            ));
+        }
+    }
+
+    for subcommand in &rule.deny_subcommands {
+        if has_subcommand(args, subcommand) {
+            return Some(format!("'{} {}' is not allowed", command, subcommand));
+        }
+    }
+
+    if !rule.allow_paths.is_empty() {
+        let allowed: Vec<PathBuf> = rule
+            .allow_paths
+            .iter()
+            .map(|p| {
+                Path::new(p)
+                    .canonicalize()
+                    .unwrap_or_else(|_| PathBuf::from(p))
+            })
+            .collect();
+        for operand in &operands {
+            let inside = resolve_operand(cwd, operand)
+                .is_some_and(|path| allowed.iter().any(|a| path.starts_with(a)));
+            if !inside {
+                return Some(format!(
+                    "'{}' is outside the paths {} may use ({})",
+                    operand.to_string_lossy(),
+                    command,
+                    rule.allow_paths.join(", ")
+                ));
+            }
+        }
+    }
+
+    None
+}
+
+/// Split arguments into options and operands (everything after `--`, and
+/// every argument not starting with `-`, is an operand)
+fn split_args<'a>(args: &[&'a OsStr]) -> (Vec<&'a OsStr>, Vec<&'a OsStr>) {
+    let mut options = Vec::new();
+    let mut operands = Vec::new();
+    let mut after_separator = false;
+    for &arg in args {
+        let bytes = arg.as_encoded_bytes();
+        if after_separator {
+            operands.push(arg);
+        } else if bytes == b"--" {
+            after_separator = true;
+        } else if bytes.len() > 1 && bytes[0] == b'-' {
+            options.push(arg);
+        } else {
+            operands.push(arg);
+        }
+    }
+    (options, operands)
+}
+
+/// Whether `arg` is, or includes, the option `pattern`
+fn arg_matches(arg: &OsStr, pattern: &str) -> bool {
+    let arg = arg.to_string_lossy();
+    if arg == pattern {
+        return true;
+    }
+    if pattern.starts_with("--") {
+        return arg
+            .strip_prefix(pattern)
+            .is_some_and(|rest| rest.starts_with('='));
+    }
+    // Bundled short options: -r matches -fr, and -rf matches any bundle with
+    // both letters, such as -fvr. Letters are case-sensitive, so -rf does
+    // not match -fR.
+    match (pattern.strip_prefix('-'), arg.strip_prefix('-')) {
+        (Some(wanted), Some(given))
+            if !wanted.is_empty() && !wanted.starts_with('-') && !given.starts_with('-') =>
+        {
+            wanted.chars().all(|c| given.contains(c))
+        }
+        _ => false,
+    }
+}
+
+/// Whether the words of `subcommand` appear in `args`: the first one
+/// exactly, the others after it
+fn has_subcommand(args: &[&OsStr], subcommand: &str) -> bool {
+    let mut words = subcommand.split_whitespace();
+    let Some(first) = words.next() else {
+        return false;
+    };
+    let rest: Vec<&str> = words.collect();
+
+    args.iter().enumerate().any(|(i, arg)| {
+        *arg == OsStr::new(first)
+            && rest
+                .iter()
+                .all(|word| args[i + 1..].iter().any(|a| arg_matches(a, word)))
+    })
+}
+
+/// Where an operand points: its parent directory canonicalized (as far as
+/// it exists) and the final component kept, since commands like `rm` act
+/// on a symlink rather than its target.
+///
+/// None if the path cannot be placed reliably (`..` past a missing
+/// directory, or a path ending in `..`).
+fn resolve_operand(cwd: &Path, operand: &OsStr) -> Option<PathBuf> {
+    let path = cwd.join(operand);
+    let name = path.file_name()?;
+    let mut existing = path.parent()?;
+    let mut missing: Vec<OsString> = Vec::new();
+
+    let base = loop {
+        if let Ok(resolved) = existing.canonicalize() {
+            break resolved;
+        }
+        match existing.components().next_back()? {
+            Component::Normal(component) => missing.push(component.to_os_string()),
+            _ => return None,
+        }
+        existing = existing.parent()?;
+    };
+
+    let mut resolved = base;
+    for component in missing.iter().rev() {
+        resolved.push(component);
+    }
+    resolved.push(name);
+    Some(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(command: &str, rule: CommandRule) -> Vec<(String, CommandRule)> {
+        vec![(command.to_string(), rule)]
+    }
+
+    fn check(command: &str, args: &[&str], cwd: &Path, rules: &[(String, CommandRule)]) -> bool {
+        check_command_args(command, args, cwd, rules).is_none()
+    }
+
+    #[test]
+    fn test_deny_args() {
+        let rules = rules(
+            "rm",
+            CommandRule {
+                deny_args: vec![
+                    "-r".to_string(),
+                    "--no-preserve-root".to_string(),
+                    "/".to_string(),
+                ],
+                ..Default::default()
+            },
+        );
+        let cwd = Path::new("/");
+
+        assert!(check("rm", &["file"], cwd, &rules));
+        assert!(check("rm", &["-f", "file"], cwd, &rules));
+        assert!(!check("rm", &["-r", "dir"], cwd, &rules));
+        assert!(!check("/usr/bin/rm", &["-fr", "dir"], cwd, &rules));
+        assert!(!check("rm", &["--no-preserve-root=yes"], cwd, &rules));
+        // After --, "-r" is a file name
+        assert!(check("rm", &["--", "-r"], cwd, &rules));
+        assert!(!check("rm", &["-f", "/"], cwd, &rules));
+        // Other commands are not affected
+        assert!(check("ls", &["-r"], cwd, &rules));
+    }
+
+    #[test]
+    fn test_bundled_options() {
+        let rules = rules(
+            "rm",
+            CommandRule {
+                deny_args: vec!["-rf".to_string()],
+                ..Default::default()
+            },
+        );
+        let cwd = Path::new("/");
+
+        assert!(!check("rm", &["-rf", "dir"], cwd, &rules));
+        assert!(!check("rm", &["-fvr", "dir"], cwd, &rules));
+        // Both letters are needed
+        assert!(check("rm", &["-r", "dir"], cwd, &rules));
+        // Letters are case-sensitive: -R is another option
+        assert!(check("rm", &["-fR", "dir"], cwd, &rules));
+    }
+
+    #[test]
+    fn test_deny_subcommands() {
+        let rules = rules(
+            "git",
+            CommandRule {
+                deny_subcommands: vec!["push --force".to_string(), "config --global".to_string()],
+                ..Default::default()
+            },
+        );
+        let cwd = Path::new("/");
+
+        assert!(check("git", &["push", "origin", "main"], cwd, &rules));
+        assert!(check("git", &["commit", "-m", "push --force"], cwd, &rules));
+        assert!(!check("git", &["push", "--force"], cwd, &rules));
+        assert!(!check(
+            "git",
+            &["-C", "repo", "push", "origin", "--force=yes"],
+            cwd,
+            &rules
+        ));
+        assert!(!check(
+            "git",
+            &["config", "--global", "user.name", "x"],
+            cwd,
+            &rules
+        ));
+        assert!(check("git", &["config", "user.name", "x"], cwd, &rules));
+    }
+
+    #[test]
+    fn test_allow_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let work = dir.path().join("work");
+        std::fs::create_dir(&work).unwrap();
+        std::os::unix::fs::symlink("/etc", work.join("etc-link")).unwrap();
+        let rules = rules(
+            "rm",
+            CommandRule {
+                allow_paths: vec![work.display().to_string()],
+                ..Default::default()
+            },
+        );
+
+        assert!(check("rm", &["file"], &work, &rules));
+        assert!(check("rm", &["-f", "sub/new/file"], &work, &rules));
+        // The link itself is inside, whatever it points to
+        assert!(check("rm", &["etc-link"], &work, &rules));
+        assert!(!check("rm", &["etc-link/passwd"], &work, &rules));
+        assert!(!check("rm", &["../file"], &work, &rules));
+        assert!(!check("rm", &["/etc/passwd"], &work, &rules));
+        assert!(!check("rm", &["missing/../../file"], &work, &rules));
+        assert!(!check("rm", &[".."], &work, &rules));
+    }
+
+    #[test]
+    fn test_every_rule_applies() {
+        let mut rules = rules(
+            "rm",
+            CommandRule {
+                deny_args: vec!["-r".to_string()],
+                ..Default::default()
+            },
+        );
+        rules.push((
+            "rm".to_string(),
+            CommandRule {
+                deny_args: vec!["-f".to_string()],
+                ..Default::default()
+            },
+        ));
+        let cwd = Path::new("/");
+
+        assert!(check("rm", &["file"], cwd, &rules));
+        assert!(!check("rm", &["-r", "dir"], cwd, &rules));
+        assert!(!check("rm", &["-f", "file"], cwd, &rules));
+    }
+
+    #[test]
+    fn test_refusal_reason() {
+        let rules = rules(
+            "git",
+            CommandRule {
+                deny_subcommands: vec!["push --force".to_string()],
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            check_command_args("git", &["push", "--force"], Path::new("/"), &rules).as_deref(),
+            Some("'git push --force' is not allowed")
+        );
+    }
+}
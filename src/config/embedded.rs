@@ -12,10 +12,10 @@ const EMBEDDED_SECURITY_LISTS: &str =
 
 #[allow(dead_code)]
 /// Author public key for verifying signatures
-/// This is the root of trust - embedded at compile time
-/// Luke: Implementing signature verification is a future step, but we need to have the key in place for when we do. In fact it will be rotating, so we need to have the infrastructure for it in place before we can generate the first key pair. For now, this is a placeholder value that will be replaced once we have a real key pair generated.
+///
+/// A placeholder until the release key pair exists (see RELEASING.md), so it
+/// is not trusted for anything yet.
 pub const AUTHOR_PUBLIC_KEY: &str = "RWTk1xXqcTODeYttYMCqEwcLg+KiX+Vpu1v6iV3D0sGabcdef12345678";
-// TODO: Replace with actual public key when generated
 
 /// Check if security lists are signed (runtime check)
 fn is_signed() -> bool {
@@ -38,7 +38,7 @@ pub fn load_security_lists() -> Result<SecurityLists> {
         tracing::debug!("Running with unsigned security lists (development mode)");
     }
 
-    // TODO: Check version against stored state for downgrade protection
+    // The version is checked for downgrades in load_effective_config
 
     Ok(lists)
 }
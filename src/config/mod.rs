@@ -2,52 +2,82 @@
 //!
 //! This module handles loading and merging configuration from multiple sources:
 //! - Embedded author-signed security lists (highest trust)
+//! - A signed security lists update in the user state dir (additive only)
 //! - System-level config at /etc/nono/ (admin-signed, additive only)
 //! - User-level config at ~/.config/nono/ (overrides with acknowledgment)
 //! - CLI flags (highest precedence)
 
+pub mod command_rules;
 pub mod embedded;
 pub mod security_lists;
+pub mod system;
+pub mod update;
 pub mod user;
 pub mod verify;
 pub mod version;
 
-use crate::error::Result;
+use crate::capability::FsAccess;
+use crate::error::{NonoError, Result};
 use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use tracing::warn;
 
 // ============================================================================
-// Override system (implemented, not yet integrated)
-// These types and functions are used for the full override system which
-// will be integrated in a future PR. For now, allow dead_code.
+// Effective configuration
 // ============================================================================
 
 /// Effective configuration after merging all sources
-#[allow(dead_code)]
 #[derive(Debug, Default)]
 pub struct EffectiveConfig {
     /// All sensitive paths that should be blocked
     pub sensitive_paths: HashSet<String>,
 
+    /// Sensitive paths grouped by category (for reporting), user extensions included
+    pub sensitive_categories: Vec<(String, Vec<String>)>,
+
     /// Sensitive paths that have been explicitly allowed (with reason)
     pub allowed_sensitive: HashMap<String, OverrideInfo>,
 
     /// All dangerous commands that should be blocked
     pub dangerous_commands: HashSet<String>,
 
+    /// Dangerous commands grouped by category (for reporting)
+    pub command_categories: Vec<(String, Vec<String>)>,
+
     /// Commands that have been explicitly allowed (with reason)
     pub allowed_commands: HashMap<String, OverrideInfo>,
 
+    /// Syscalls the seccomp filter denies (Linux)
+    pub dangerous_syscalls: HashSet<String>,
+
+    /// Dangerous syscalls grouped by category (for reporting)
+    pub syscall_categories: Vec<(String, Vec<String>)>,
+
+    /// Argument rules for commands, as (command, rule) with paths unexpanded
+    pub command_rules: Vec<(String, security_lists::CommandRule)>,
+
+    /// Argument rules grouped by source (for reporting)
+    pub command_rule_categories: Vec<(String, Vec<String>)>,
+
     /// System read paths for the current platform
     pub system_read_paths: Vec<String>,
 
+    /// System read paths grouped by source (for reporting)
+    pub system_read_categories: Vec<(String, Vec<String>)>,
+
     /// Version information for downgrade protection
     pub security_lists_version: u64,
+
+    /// Signer of the security lists update in effect, if any
+    pub lists_update_signer: Option<String>,
+
+    /// Admin policy from /etc/nono, whose restrictions users cannot lift
+    pub system_policy: Option<system::SystemPolicy>,
 }
 
 /// Information about an override (for audit trail)
-#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct OverrideInfo {
     /// Reason provided by user for the override
@@ -55,6 +85,7 @@ pub struct OverrideInfo {
     /// When the override was acknowledged (if from config file)
     pub acknowledged: Option<String>,
     /// Source of the override
+    #[allow(dead_code)]
     pub source: OverrideSource,
     /// Access level for path overrides (read, write, or both)
     pub access: Option<String>,
@@ -81,6 +112,111 @@ impl OverrideInfo {
             access: None,
         }
     }
+
+    /// Whether a path override opens the path up for `access`
+    pub fn allows(&self, access: FsAccess) -> bool {
+        match self.access.as_deref() {
+            Some("read") => !access.writes(),
+            Some("write") => access == FsAccess::Write,
+            _ => true,
+        }
+    }
+}
+
+/// An acknowledged user override that takes effect
+#[derive(Debug, Clone)]
+pub struct AppliedOverride {
+    /// "sensitive path" or "command"
+    pub kind: &'static str,
+    /// The path or command as written in the user config
+    pub target: String,
+    pub info: OverrideInfo,
+}
+
+impl fmt::Display for AppliedOverride {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.kind, self.target)?;
+        if let Some(ref access) = self.info.access {
+            write!(f, " ({})", access)?;
+        }
+        write!(f, ": {}", self.info.reason)?;
+        if let Some(ref date) = self.info.acknowledged {
+            write!(f, " [acknowledged {}]", date)?;
+        }
+        Ok(())
+    }
+}
+
+impl EffectiveConfig {
+    /// Whether the system policy adds `command` to the dangerous commands
+    pub fn is_policy_command(&self, command: &str) -> bool {
+        self.system_policy.as_ref().is_some_and(|policy| {
+            policy
+                .dangerous_commands
+                .values()
+                .flatten()
+                .any(|c| c == command)
+        })
+    }
+
+    /// The only TCP ports the system policy lets the sandbox connect to
+    /// (empty = no restriction)
+    pub fn policy_connect_ports(&self) -> &[u16] {
+        self.system_policy
+            .as_ref()
+            .map_or(&[], |policy| &policy.network.connect_ports)
+    }
+
+    /// Whether `path` is, contains or lies inside a system policy sensitive path
+    pub fn overlaps_policy_path(&self, path: &Path) -> bool {
+        self.system_policy.as_ref().is_some_and(|policy| {
+            policy.all_sensitive_paths().any(|protected| {
+                let protected = PathBuf::from(expand_home(protected));
+                path.starts_with(&protected) || protected.starts_with(path)
+            })
+        })
+    }
+
+    /// The acknowledged override for `path` (or the closest parent of it)
+    pub fn sensitive_override(&self, path: &Path) -> Option<(&str, &OverrideInfo)> {
+        self.allowed_sensitive
+            .iter()
+            .map(|(target, info)| (target.as_str(), info, PathBuf::from(expand_home(target))))
+            .filter(|(_, _, overridden)| path.starts_with(overridden))
+            .max_by_key(|(_, _, overridden)| overridden.components().count())
+            .map(|(target, info, _)| (target, info))
+    }
+
+    /// Overrides that change what is blocked: command overrides for
+    /// dangerous commands, and path overrides that overlap a sensitive path
+    pub fn applied_overrides(&self) -> Vec<AppliedOverride> {
+        let mut applied = Vec::new();
+        for (target, info) in &self.allowed_sensitive {
+            let overridden = PathBuf::from(expand_home(target));
+            let overlaps = self.sensitive_paths.iter().any(|sensitive| {
+                let sensitive = PathBuf::from(expand_home(sensitive));
+                overridden.starts_with(&sensitive) || sensitive.starts_with(&overridden)
+            });
+            if overlaps {
+                applied.push(AppliedOverride {
+                    kind: "sensitive path",
+                    target: target.clone(),
+                    info: info.clone(),
+                });
+            }
+        }
+        for (command, info) in &self.allowed_commands {
+            if self.dangerous_commands.contains(command) {
+                applied.push(AppliedOverride {
+                    kind: "command",
+                    target: command.clone(),
+                    info: info.clone(),
+                });
+            }
+        }
+        applied.sort_by(|a, b| (a.kind, &a.target).cmp(&(b.kind, &b.target)));
+        applied
+    }
 }
 
 /// Load effective configuration by merging all sources
@@ -90,89 +226,398 @@ impl OverrideInfo {
 /// 2. User config (~/.config/nono/config.toml)
 /// 3. System config (/etc/nono/) - additive only
 /// 4. Embedded defaults
-#[allow(dead_code)]
-pub fn load_effective_config() -> Result<EffectiveConfig> {
-    // Start with embedded security lists
+///
+/// The system policy's additions are a floor: neither the user config nor
+/// CLI flags can lift them.
+///
+/// The `meta.version` of each signed source (the security lists, their
+/// update and the system policy) is checked against the versions seen
+/// before (see [`version`]); loading an older one is refused. The versions
+/// are only recorded if `record_versions` is set.
+pub fn load_effective_config(record_versions: bool) -> Result<EffectiveConfig> {
+    let mut config = embedded_config()?;
+    let embedded_version = config.security_lists_version;
+    // Inside a sandbox the state directory is usually out of reach
+    let lists_update = match update::load_installed_update() {
+        Err(e) if is_out_of_reach(&e) => {
+            tracing::debug!("Not loading the security lists update: {}", e);
+            None
+        }
+        result => result?,
+    };
+    if let Some(update) = lists_update {
+        if update.lists.meta.version > config.security_lists_version {
+            apply_lists_update(&mut config, update);
+        } else {
+            tracing::debug!(
+                "Ignoring security lists update v{}: this binary has v{}",
+                update.lists.meta.version,
+                config.security_lists_version
+            );
+        }
+    }
+    let policy = system::load_system_policy()?;
+    let user_config = user::load_user_config()?;
+
+    // The embedded lists and the update are tracked apart, so removing the
+    // update falls back to the embedded lists instead of being a downgrade
+    let mut loaded = vec![(version::SECURITY_LISTS, embedded_version, "embedded")];
+    if config.lists_update_signer.is_some() {
+        loaded.push((
+            version::LISTS_UPDATE,
+            config.security_lists_version,
+            "update",
+        ));
+    }
+    if let Some(ref policy) = policy {
+        loaded.push((version::SYSTEM_POLICY, policy.meta.version, "system"));
+    }
+    if record_versions {
+        version::check_and_update_versions(&loaded)?;
+    } else {
+        version::check_versions(&loaded)?;
+    }
+
+    if let Some(policy) = policy {
+        apply_system_policy(&mut config, policy);
+    }
+
+    // Load user config if it exists (optional)
+    if let Some(user_config) = user_config {
+        apply_user_config(&mut config, user_config)?;
+    }
+
+    Ok(config)
+}
+
+/// Effective configuration from the embedded security lists alone
+fn embedded_config() -> Result<EffectiveConfig> {
     let security_lists = embedded::load_security_lists()?;
 
-    let mut config = EffectiveConfig {
+    let mut sensitive_categories =
+        categories(security_lists::sensitive_paths_by_category(&security_lists));
+    sensitive_categories.sort();
+    let mut command_categories = categories(security_lists::dangerous_commands_by_category(
+        &security_lists,
+    ));
+    command_categories.sort();
+    let mut syscall_categories = categories(security_lists::dangerous_syscalls_by_category(
+        &security_lists,
+    ));
+    syscall_categories.sort();
+    let system_read_paths = security_lists.system_paths_for_platform();
+    let command_rules: Vec<_> = security_lists.command_rules.clone().into_iter().collect();
+    let command_rule_categories = rule_categories("Command rules", &command_rules);
+
+    Ok(EffectiveConfig {
         sensitive_paths: security_lists.all_sensitive_paths(),
+        sensitive_categories,
         dangerous_commands: security_lists.all_dangerous_commands(),
-        system_read_paths: security_lists.system_paths_for_platform(),
+        command_categories,
+        dangerous_syscalls: security_lists.all_dangerous_syscalls(),
+        syscall_categories,
+        command_rules,
+        command_rule_categories,
+        system_read_categories: vec![("System paths".to_string(), system_read_paths.clone())],
+        system_read_paths,
         security_lists_version: security_lists.meta.version,
         ..Default::default()
-    };
+    })
+}
 
-    // Load user config if it exists (optional)
-    if let Some(user_config) = user::load_user_config()? {
-        // Apply user extensions (additions to blocklists)
-        for path in user_config.extensions.sensitive_paths.values().flatten() {
-            config.sensitive_paths.insert(path.clone());
+fn categories(by_category: HashMap<&str, &Vec<String>>) -> Vec<(String, Vec<String>)> {
+    by_category
+        .into_iter()
+        .filter(|(_, entries)| !entries.is_empty())
+        .map(|(category, entries)| (category.to_string(), entries.clone()))
+        .collect()
+}
+
+/// One reporting category holding `rules` as "command: rule" lines, or
+/// none if there are no rules
+fn rule_categories(
+    title: &str,
+    rules: &[(String, security_lists::CommandRule)],
+) -> Vec<(String, Vec<String>)> {
+    if rules.is_empty() {
+        return Vec::new();
+    }
+    let lines = rules
+        .iter()
+        .map(|(command, rule)| format!("{}: {}", command, rule))
+        .collect();
+    vec![(title.to_string(), lines)]
+}
+
+/// Merge what a security lists update adds to the embedded lists into
+/// `config`. Entries it leaves out stay in place.
+fn apply_lists_update(config: &mut EffectiveConfig, update: update::ListsUpdate) {
+    let label = |category: &str| format!("{} (lists update)", category);
+    let lists = &update.lists;
+
+    let mut sensitive: Vec<_> = security_lists::sensitive_paths_by_category(lists)
+        .into_iter()
+        .collect();
+    sensitive.sort();
+    for (category, paths) in sensitive {
+        let added: Vec<String> = paths
+            .iter()
+            .filter(|p| config.sensitive_paths.insert(p.to_string()))
+            .cloned()
+            .collect();
+        if !added.is_empty() {
+            config.sensitive_categories.push((label(category), added));
         }
+    }
 
-        for cmd in user_config.extensions.dangerous_commands.values().flatten() {
-            config.dangerous_commands.insert(cmd.clone());
+    let mut commands: Vec<_> = security_lists::dangerous_commands_by_category(lists)
+        .into_iter()
+        .collect();
+    commands.sort();
+    for (category, cmds) in commands {
+        let added: Vec<String> = cmds
+            .iter()
+            .filter(|c| config.dangerous_commands.insert(c.to_string()))
+            .cloned()
+            .collect();
+        if !added.is_empty() {
+            config.command_categories.push((label(category), added));
         }
+    }
 
-        // Apply user overrides (acknowledged exceptions)
-        for (path, override_info) in user_config.overrides.sensitive_paths {
-            if override_info.acknowledged.is_some() {
-                config.allowed_sensitive.insert(
-                    path,
-                    OverrideInfo {
-                        reason: override_info.reason,
-                        acknowledged: override_info.acknowledged,
-                        source: OverrideSource::UserConfig,
-                        access: override_info.access,
-                    },
-                );
-            }
+    let mut syscalls: Vec<_> = security_lists::dangerous_syscalls_by_category(lists)
+        .into_iter()
+        .collect();
+    syscalls.sort();
+    for (category, calls) in syscalls {
+        let added: Vec<String> = calls
+            .iter()
+            .filter(|c| config.dangerous_syscalls.insert(c.to_string()))
+            .cloned()
+            .collect();
+        if !added.is_empty() {
+            config.syscall_categories.push((label(category), added));
         }
+    }
 
-        for (cmd, override_info) in user_config.overrides.commands {
-            if override_info.acknowledged.is_some() {
-                config.allowed_commands.insert(
-                    cmd,
-                    OverrideInfo {
-                        reason: override_info.reason,
-                        acknowledged: override_info.acknowledged,
-                        source: OverrideSource::UserConfig,
-                        access: None,
-                    },
-                );
-            }
+    let added: Vec<String> = lists
+        .system_paths_for_platform()
+        .into_iter()
+        .filter(|p| !config.system_read_paths.contains(p))
+        .collect();
+    if !added.is_empty() {
+        config.system_read_paths.extend(added.iter().cloned());
+        config
+            .system_read_categories
+            .push((label("System paths"), added));
+    }
+
+    // Rules only restrict, so the update's rules apply alongside the
+    // embedded ones (even for the same command)
+    let rules: Vec<_> = lists
+        .command_rules
+        .iter()
+        .filter(|(command, rule)| {
+            !config
+                .command_rules
+                .iter()
+                .any(|(c, r)| c == *command && r == *rule)
+        })
+        .map(|(command, rule)| (command.clone(), rule.clone()))
+        .collect();
+    config
+        .command_rule_categories
+        .extend(rule_categories(&label("Command rules"), &rules));
+    config.command_rules.extend(rules);
+
+    config.security_lists_version = lists.meta.version;
+    config.lists_update_signer = Some(update.signed_by);
+}
+
+/// Merge the system policy's additions into `config`
+fn apply_system_policy(config: &mut EffectiveConfig, policy: system::SystemPolicy) {
+    let mut categories: Vec<_> = policy.sensitive_paths.iter().collect();
+    categories.sort();
+    for (category, paths) in categories {
+        config.sensitive_paths.extend(paths.iter().cloned());
+        config
+            .sensitive_categories
+            .push((format!("{} (system policy)", category), paths.clone()));
+    }
+
+    let mut commands: Vec<_> = policy.dangerous_commands.iter().collect();
+    commands.sort();
+    for (category, cmds) in commands {
+        config.dangerous_commands.extend(cmds.iter().cloned());
+        config
+            .command_categories
+            .push((format!("{} (system policy)", category), cmds.clone()));
+    }
+
+    config.system_policy = Some(policy);
+}
+
+/// Merge a user config into `config`: extensions add to the blocklists and
+/// acknowledged overrides make exceptions to them. Overrides without an
+/// `acknowledged` date are ignored.
+fn apply_user_config(config: &mut EffectiveConfig, user_config: user::UserConfig) -> Result<()> {
+    // Apply user extensions (additions to blocklists)
+    let mut extensions: Vec<_> = user_config.extensions.sensitive_paths.into_iter().collect();
+    extensions.sort();
+    for (category, paths) in extensions {
+        config.sensitive_paths.extend(paths.iter().cloned());
+        config
+            .sensitive_categories
+            .push((format!("{} (user config)", category), paths));
+    }
+
+    let mut commands: Vec<_> = user_config
+        .extensions
+        .dangerous_commands
+        .into_iter()
+        .collect();
+    commands.sort();
+    for (category, cmds) in commands {
+        config.dangerous_commands.extend(cmds.iter().cloned());
+        config
+            .command_categories
+            .push((format!("{} (user config)", category), cmds));
+    }
+
+    // Apply user overrides (acknowledged exceptions)
+    for (path, override_info) in user_config.overrides.sensitive_paths {
+        if override_info.acknowledged.is_none() {
+            warn!(
+                "Ignoring override for sensitive path '{}': it has no acknowledged date",
+                path
+            );
+            continue;
+        }
+        if config.overlaps_policy_path(Path::new(&expand_home(&path))) {
+            warn!(
+                "Ignoring override for sensitive path '{}': the system policy protects it",
+                path
+            );
+            continue;
         }
+        if !matches!(
+            override_info.access.as_deref(),
+            None | Some("read") | Some("write") | Some("both")
+        ) {
+            return Err(NonoError::ConfigParse(format!(
+                "invalid access {:?} in override for '{}' (expected read, write or both)",
+                override_info.access.unwrap_or_default(),
+                path
+            )));
+        }
+        config.allowed_sensitive.insert(
+            path,
+            OverrideInfo {
+                reason: override_info.reason,
+                acknowledged: override_info.acknowledged,
+                source: OverrideSource::UserConfig,
+                access: override_info.access,
+            },
+        );
     }
 
-    Ok(config)
+    for (cmd, override_info) in user_config.overrides.commands {
+        if override_info.acknowledged.is_none() {
+            warn!(
+                "Ignoring override for command '{}': it has no acknowledged date",
+                cmd
+            );
+            continue;
+        }
+        if config.is_policy_command(&cmd) {
+            warn!(
+                "Ignoring override for command '{}': the system policy blocks it",
+                cmd
+            );
+            continue;
+        }
+        config.allowed_commands.insert(
+            cmd,
+            OverrideInfo {
+                reason: override_info.reason,
+                acknowledged: override_info.acknowledged,
+                source: OverrideSource::UserConfig,
+                access: None,
+            },
+        );
+    }
+
+    Ok(())
 }
 
-/// Check if a path is in the sensitive paths list
-#[allow(dead_code)]
-pub fn is_sensitive_path(path: &str, config: &EffectiveConfig) -> bool {
-    let home = std::env::var("HOME").unwrap_or_default();
-    let expanded = path.replace('~', &home);
-
-    for sensitive in &config.sensitive_paths {
-        let expanded_sensitive = sensitive.replace('~', &home);
-        if expanded == expanded_sensitive
-            || expanded.starts_with(&format!("{}/", expanded_sensitive))
-        {
-            // Check if explicitly allowed
-            if config.allowed_sensitive.contains_key(sensitive) {
-                return false;
-            }
-            return true;
+/// The configuration in effect for this process
+static EFFECTIVE_CONFIG: OnceLock<EffectiveConfig> = OnceLock::new();
+
+/// Load the effective configuration, user config included, for this process.
+///
+/// Call once at startup, before anything consults the security lists, so
+/// that a malformed user config is reported instead of ignored.
+///
+/// `record_versions` is set by the commands that start a sandbox and by
+/// `nono setup`: commands that merely inspect the config (`nono setup
+/// --check-only`, `nono why`, `nono lists show`) check the versions without
+/// writing `versions.json`.
+pub fn init_effective_config(record_versions: bool) -> Result<()> {
+    if EFFECTIVE_CONFIG.get().is_none() {
+        let config = load_effective_config(record_versions)?;
+        let _ = EFFECTIVE_CONFIG.set(config);
+    }
+    Ok(())
+}
+
+/// The effective configuration of this process.
+///
+/// Until [`init_effective_config`] has run (as in unit tests), this is the
+/// embedded security lists alone.
+pub fn effective_config() -> &'static EffectiveConfig {
+    EFFECTIVE_CONFIG.get_or_init(|| {
+        embedded_config().unwrap_or_else(|err| {
+            warn!("Failed to load embedded security lists: {}", err);
+            EffectiveConfig::default()
+        })
+    })
+}
+
+/// Apply the system policy, if there is one, to `caps`
+///
+/// See [`system::SystemPolicy::enforce`].
+pub fn enforce_system_policy(caps: &mut crate::capability::CapabilitySet) -> Result<()> {
+    match effective_config().system_policy {
+        Some(ref policy) => policy.enforce(caps),
+        None => Ok(()),
+    }
+}
+
+/// Add the security lists' argument rules to `caps`, expanding variables in
+/// their paths against `workdir`
+pub fn apply_command_rules(caps: &mut crate::capability::CapabilitySet, workdir: &Path) {
+    caps.add_arg_rules(
+        effective_config()
+            .command_rules
+            .iter()
+            .map(|(command, rule)| (command, rule)),
+        workdir,
+    );
+}
+
+/// Expand a leading `~` to the home directory
+pub fn expand_home(path: &str) -> String {
+    if path == "~" || path.starts_with("~/") {
+        if let Ok(home) = std::env::var("HOME") {
+            return path.replacen('~', &home, 1);
         }
     }
-    false
+    path.to_string()
 }
 
 /// Check if a command is in the dangerous commands list
-#[allow(dead_code)]
 pub fn is_dangerous_command(cmd: &str, config: &EffectiveConfig) -> bool {
     use std::ffi::OsStr;
-    use std::path::Path;
 
     // Extract just the binary name (handle paths like /bin/rm)
     let binary_os = Path::new(cmd)
@@ -194,8 +639,17 @@ pub fn user_config_dir() -> Option<PathBuf> {
     dirs::config_dir().map(|p| p.join("nono"))
 }
 
+/// Whether `err` is a state file that could not be read for lack of
+/// permission, as happens inside a sandbox.
+///
+/// Decided by the error rather than by `NONO_CAP_FILE`, which any process
+/// can set.
+fn is_out_of_reach(err: &NonoError) -> bool {
+    matches!(err, NonoError::ConfigRead { source, .. }
+        if source.kind() == std::io::ErrorKind::PermissionDenied)
+}
+
 /// Get the user state directory path (for version tracking)
-#[allow(dead_code)]
 pub fn user_state_dir() -> Option<PathBuf> {
     dirs::state_dir()
         .or_else(dirs::data_local_dir)
@@ -204,53 +658,30 @@ pub fn user_state_dir() -> Option<PathBuf> {
 
 // ============================================================================
 // Helper functions for main.rs compatibility
-// These provide access to embedded config data without requiring full config loading
+// These read the process-wide effective config (embedded lists plus user config)
 // ============================================================================
 
-/// Get all sensitive paths from embedded config
+/// Get all sensitive paths from the effective config
 pub fn get_sensitive_paths() -> Vec<String> {
-    match embedded::load_security_lists() {
-        Ok(lists) => lists.all_sensitive_paths().into_iter().collect(),
-        Err(err) => {
-            warn!(
-                "Failed to load embedded security lists for sensitive paths: {}",
-                err
-            );
-            Vec::new()
-        }
-    }
+    effective_config().sensitive_paths.iter().cloned().collect()
 }
 
-/// Get all dangerous commands from embedded config
+/// Get all dangerous commands from the effective config
 pub fn get_dangerous_commands() -> HashSet<String> {
-    match embedded::load_security_lists() {
-        Ok(lists) => lists.all_dangerous_commands(),
-        Err(err) => {
-            warn!(
-                "Failed to load embedded security lists for dangerous commands: {}",
-                err
-            );
-            HashSet::new()
-        }
-    }
+    effective_config().dangerous_commands.clone()
+}
+
+/// Get the version of the security lists in effect
+pub fn get_security_lists_version() -> u64 {
+    effective_config().security_lists_version
 }
 
 /// Get system read paths for the current platform
-#[allow(dead_code)]
 pub fn get_system_read_paths() -> Vec<String> {
-    match embedded::load_security_lists() {
-        Ok(lists) => lists.system_paths_for_platform(),
-        Err(err) => {
-            warn!(
-                "Failed to load embedded security lists for system read paths: {}",
-                err
-            );
-            Vec::new()
-        }
-    }
+    effective_config().system_read_paths.clone()
 }
 
-/// Check if a command is blocked by the default dangerous commands list
+/// Check if a command is blocked by the dangerous commands list
 /// Returns Some(command_name) if blocked, None if allowed
 pub fn check_blocked_command(
     cmd: impl AsRef<std::ffi::OsStr>,
@@ -258,13 +689,17 @@ pub fn check_blocked_command(
     extra_blocked: &[String],
 ) -> Option<String> {
     use std::ffi::OsStr;
-    use std::path::Path;
 
     let cmd = cmd.as_ref();
 
     // Extract just the binary name (handle paths like /bin/rm)
     let binary_os = Path::new(cmd).file_name().unwrap_or(cmd);
 
+    // The system policy's commands cannot be allowed
+    if effective_config().is_policy_command(&binary_os.to_string_lossy()) {
+        return Some(binary_os.to_string_lossy().into_owned());
+    }
+
     // Check if explicitly allowed (overrides default blocklist)
     if allowed_commands.iter().any(|a| OsStr::new(a) == binary_os) {
         return None;
@@ -275,53 +710,77 @@ pub fn check_blocked_command(
         return Some(binary_os.to_string_lossy().into_owned());
     }
 
-    // Check default dangerous commands list from config
-    let dangerous = get_dangerous_commands();
-    let binary_str = binary_os.to_string_lossy();
-    if dangerous.contains(binary_str.as_ref()) {
-        return Some(binary_str.into_owned());
+    // Check the dangerous commands list, minus user config overrides
+    if is_dangerous_command(&binary_os.to_string_lossy(), effective_config()) {
+        return Some(binary_os.to_string_lossy().into_owned());
     }
 
     None
 }
 
-/// Check if a path is in the sensitive paths list (for `nono why` command)
-/// Returns Some(reason) if blocked, None if not in list
-pub fn check_sensitive_path(path_str: &str) -> Option<&'static str> {
-    use security_lists::sensitive_paths_by_category;
-
-    let home = std::env::var("HOME").unwrap_or_default();
-    let expanded = if path_str.starts_with("~/") {
-        path_str.replacen("~", &home, 1)
-    } else if path_str == "~" {
-        home.clone()
+/// Every command name `check_blocked_command` blocks with these overrides, sorted
+pub fn blocked_commands(allowed_commands: &[String], extra_blocked: &[String]) -> Vec<String> {
+    let mut blocked: Vec<String> = effective_config()
+        .dangerous_commands
+        .iter()
+        .chain(extra_blocked)
+        .filter(|cmd| check_blocked_command(cmd, allowed_commands, extra_blocked).is_some())
+        .cloned()
+        .collect();
+    blocked.sort();
+    blocked.dedup();
+    blocked
+}
+
+/// Every syscall the seccomp filter denies with these profile exceptions
+/// and additions, sorted
+pub fn denied_syscalls(allowed: &[String], extra_denied: &[String]) -> Vec<String> {
+    let mut denied: Vec<String> = effective_config()
+        .dangerous_syscalls
+        .iter()
+        .filter(|name| !allowed.contains(name))
+        .chain(extra_denied)
+        .cloned()
+        .collect();
+    denied.sort();
+    denied.dedup();
+    denied
+}
+
+/// Why `command` (as returned by `check_blocked_command`) is blocked
+pub fn blocked_command_reason(command: &str, extra_blocked: &[String]) -> &'static str {
+    if effective_config().is_policy_command(command) {
+        "This command is blocked by the system policy in /etc/nono."
+    } else if extra_blocked.iter().any(|b| b == command) {
+        "This command is blocked by --block-command or the profile's [commands] section."
     } else {
-        path_str.to_string()
-    };
+        "This command is blocked by default due to destructive potential. \
+         Use --allow-command to override if you understand the risks."
+    }
+}
 
-    // Load security lists and get paths organized by category
-    let lists = match embedded::load_security_lists() {
-        Ok(l) => l,
-        Err(err) => {
-            warn!(
-                "Failed to load embedded security lists while checking sensitive path: {}",
-                err
-            );
+/// Check if a path is in the sensitive paths list (for `nono why` command)
+/// Returns Some(category) if `access` to it is blocked, None if not in the
+/// list or a user config override allows that access
+pub fn check_sensitive_path(path_str: &str, access: FsAccess) -> Option<String> {
+    let config = effective_config();
+    let expanded = expand_home(path_str);
+
+    if let Some((_, info)) = config.sensitive_override(Path::new(&expanded)) {
+        if info.allows(access) {
             return None;
         }
-    };
-
-    let categories = sensitive_paths_by_category(&lists);
+    }
 
     // Check each category's paths
-    for (category_name, paths) in categories {
+    for (category_name, paths) in &config.sensitive_categories {
         for sensitive in paths {
-            let expanded_sensitive = sensitive.replace('~', &home);
+            let expanded_sensitive = expand_home(sensitive);
 
             if expanded == expanded_sensitive
                 || expanded.starts_with(&format!("{}/", expanded_sensitive))
             {
-                return Some(category_name);
+                return Some(category_name.clone());
             }
         }
     }
@@ -366,4 +825,226 @@ mod tests {
         // Should not be considered dangerous when explicitly allowed
         assert!(!is_dangerous_command("pip", &config));
     }
+
+    fn with_user_config(toml: &str) -> Result<EffectiveConfig> {
+        let mut config = embedded_config()?;
+        let user_config: user::UserConfig = toml::from_str(toml).expect("Failed to parse");
+        apply_user_config(&mut config, user_config)?;
+        Ok(config)
+    }
+
+    #[test]
+    fn test_apply_user_config() {
+        let config = with_user_config(
+            r#"
+[overrides.sensitive_paths]
+"/home/u/.ssh/id_rsa.pub" = { reason = "Public key for git", acknowledged = "2025-01-15", access = "read" }
+"/home/u/.aws" = { reason = "Not acknowledged" }
+
+[overrides.commands]
+pip = { reason = "Python development", acknowledged = "2025-01-15" }
+rm = { reason = "Not acknowledged" }
+
+[extensions.sensitive_paths]
+work = ["/home/u/work/secrets"]
+
+[extensions.dangerous_commands]
+custom = ["my-dangerous-tool"]
+"#,
+        )
+        .expect("Failed to apply user config");
+
+        // Extensions add to the lists, with their own category
+        assert!(config.sensitive_paths.contains("/home/u/work/secrets"));
+        assert!(config
+            .sensitive_categories
+            .iter()
+            .any(|(name, paths)| name == "work (user config)"
+                && paths.contains(&"/home/u/work/secrets".to_string())));
+        assert!(is_dangerous_command("my-dangerous-tool", &config));
+
+        // Only acknowledged overrides take effect
+        assert!(!is_dangerous_command("pip", &config));
+        assert!(is_dangerous_command("rm", &config));
+        assert!(config
+            .sensitive_override(Path::new("/home/u/.aws/credentials"))
+            .is_none());
+        let (target, info) = config
+            .sensitive_override(Path::new("/home/u/.ssh/id_rsa.pub"))
+            .expect("override should apply");
+        assert_eq!(target, "/home/u/.ssh/id_rsa.pub");
+        assert!(info.allows(FsAccess::Read));
+        assert!(!info.allows(FsAccess::Write));
+        assert!(config
+            .sensitive_override(Path::new("/home/u/.ssh/id_rsa"))
+            .is_none());
+    }
+
+    #[test]
+    fn test_apply_user_config_invalid_access() {
+        let result = with_user_config(
+            r#"
+[overrides.sensitive_paths]
+"~/.ssh" = { reason = "typo", acknowledged = "2025-01-15", access = "rw" }
+"#,
+        );
+        assert!(matches!(result, Err(NonoError::ConfigParse(_))));
+    }
+
+    #[test]
+    fn test_applied_overrides() {
+        let config = with_user_config(
+            r#"
+[overrides.sensitive_paths]
+"~/.ssh/id_rsa.pub" = { reason = "Public key for git", acknowledged = "2025-01-15", access = "read" }
+"/not/sensitive" = { reason = "No effect", acknowledged = "2025-01-15" }
+
+[overrides.commands]
+pip = { reason = "Python development", acknowledged = "2025-01-15" }
+not-dangerous = { reason = "No effect", acknowledged = "2025-01-15" }
+"#,
+        )
+        .expect("Failed to apply user config");
+
+        let applied: Vec<String> = config
+            .applied_overrides()
+            .iter()
+            .map(|o| o.to_string())
+            .collect();
+        assert_eq!(
+            applied,
+            vec![
+                "command pip: Python development [acknowledged 2025-01-15]",
+                "sensitive path ~/.ssh/id_rsa.pub (read): Public key for git [acknowledged 2025-01-15]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply_lists_update() {
+        let mut config = embedded_config().expect("embedded config");
+        let embedded_paths = config.sensitive_paths.len();
+        let lists: security_lists::SecurityLists = toml::from_str(
+            r#"
+            [meta]
+            version = 1000
+            schema_version = "0.2.0"
+
+            [sensitive_paths]
+            ssh = ["~/.ssh", "~/.ssh-agent-keys"]
+            ai_tokens = ["~/.config/some-agent"]
+
+            [dangerous_commands]
+            cloud = ["gcloud"]
+
+            [command_rules]
+            rm = { deny_args = ["-r"], allow_paths = ["$WORKDIR"] }
+
+            [dangerous_syscalls]
+            namespaces = ["unshare", "setns"]
+            async_io = ["io_uring_setup"]
+
+            [system_read_paths]
+            "#,
+        )
+        .expect("update should parse");
+        apply_lists_update(
+            &mut config,
+            update::ListsUpdate {
+                lists,
+                signed_by: "Team".to_string(),
+            },
+        );
+
+        // Only what the update adds is listed under it; nothing is removed
+        assert_eq!(config.sensitive_paths.len(), embedded_paths + 2);
+        assert!(config.sensitive_paths.contains("~/.aws"));
+        assert!(config.sensitive_categories.contains(&(
+            "SSH keys and config (lists update)".to_string(),
+            vec!["~/.ssh-agent-keys".to_string()]
+        )));
+        assert!(config.sensitive_categories.contains(&(
+            "ai_tokens (lists update)".to_string(),
+            vec!["~/.config/some-agent".to_string()]
+        )));
+        assert!(config.dangerous_commands.contains("gcloud"));
+        assert!(config.dangerous_commands.contains("rm"));
+        assert_eq!(config.command_rules.len(), 1);
+        assert_eq!(config.command_rules[0].0, "rm");
+        assert_eq!(
+            config.command_rule_categories,
+            vec![(
+                "Command rules (lists update)".to_string(),
+                vec!["rm: deny args -r; paths under $WORKDIR".to_string()]
+            )]
+        );
+        assert!(config.dangerous_syscalls.contains("ptrace"));
+        assert!(config.dangerous_syscalls.contains("io_uring_setup"));
+        assert!(!config
+            .syscall_categories
+            .iter()
+            .any(|(category, _)| category == "Namespaces (lists update)"));
+        assert_eq!(config.security_lists_version, 1000);
+        assert_eq!(config.lists_update_signer.as_deref(), Some("Team"));
+    }
+
+    #[test]
+    fn test_system_policy_cannot_be_overridden() {
+        let mut config = embedded_config().expect("embedded config");
+        let policy: system::SystemPolicy = toml::from_str(
+            r#"
+[sensitive_paths]
+corp = ["/srv/corp-secrets"]
+
+[dangerous_commands]
+corp = ["kubectl"]
+"#,
+        )
+        .expect("Failed to parse policy");
+        apply_system_policy(&mut config, policy);
+
+        assert!(config.sensitive_paths.contains("/srv/corp-secrets"));
+        assert!(config
+            .sensitive_categories
+            .iter()
+            .any(|(name, _)| name == "corp (system policy)"));
+        assert!(is_dangerous_command("kubectl", &config));
+
+        let user_config: user::UserConfig = toml::from_str(
+            r#"
+[overrides.sensitive_paths]
+"/srv/corp-secrets/readme" = { reason = "docs", acknowledged = "2025-01-15" }
+"/srv" = { reason = "everything", acknowledged = "2025-01-15" }
+
+[overrides.commands]
+kubectl = { reason = "deploys", acknowledged = "2025-01-15" }
+pip = { reason = "Python development", acknowledged = "2025-01-15" }
+"#,
+        )
+        .expect("Failed to parse");
+        apply_user_config(&mut config, user_config).expect("apply");
+
+        // Overrides touching the policy are dropped, others still apply
+        assert!(config.allowed_sensitive.is_empty());
+        assert!(is_dangerous_command("kubectl", &config));
+        assert!(!is_dangerous_command("pip", &config));
+    }
+
+    #[test]
+    fn test_override_allows() {
+        let mut info = OverrideInfo::from_cli("test");
+        assert!(info.allows(FsAccess::ReadWrite));
+
+        info.access = Some("both".to_string());
+        assert!(info.allows(FsAccess::ReadWrite));
+
+        info.access = Some("read".to_string());
+        assert!(info.allows(FsAccess::Read));
+        assert!(!info.allows(FsAccess::Write));
+        assert!(!info.allows(FsAccess::ReadWrite));
+
+        info.access = Some("write".to_string());
+        assert!(!info.allows(FsAccess::Read));
+        assert!(info.allows(FsAccess::Write));
+    }
 }
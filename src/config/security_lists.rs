@@ -5,7 +5,7 @@
 #![allow(dead_code)]
 
 use serde::Deserialize;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 /// Root structure for security-lists.toml
 #[derive(Debug, Clone, Deserialize)]
@@ -13,6 +13,12 @@ pub struct SecurityLists {
     pub meta: SecurityListsMeta,
     pub sensitive_paths: SensitivePaths,
     pub dangerous_commands: DangerousCommands,
+    /// Argument rules for commands that may run, by command name
+    #[serde(default)]
+    pub command_rules: BTreeMap<String, CommandRule>,
+    /// Syscalls the seccomp filter denies (Linux)
+    #[serde(default)]
+    pub dangerous_syscalls: DangerousSyscalls,
     pub system_read_paths: SystemReadPaths,
 }
 
@@ -60,6 +66,9 @@ pub struct SensitivePaths {
     pub shell_configs: Vec<String>,
     #[serde(default)]
     pub history_files: Vec<String>,
+    /// Categories added after this binary was built (from a lists update)
+    #[serde(flatten)]
+    pub other: BTreeMap<String, Vec<String>>,
 }
 
 /// Dangerous commands organized by category
@@ -83,6 +92,82 @@ pub struct DangerousCommands {
     pub arbitrary_execution: Vec<String>,
     #[serde(default)]
     pub privilege_escalation: Vec<String>,
+    /// Categories added after this binary was built (from a lists update)
+    #[serde(flatten)]
+    pub other: BTreeMap<String, Vec<String>>,
+}
+
+/// Syscalls the seccomp filter denies, organized by category (Linux)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DangerousSyscalls {
+    #[serde(default)]
+    pub process_inspection: Vec<String>,
+    #[serde(default)]
+    pub kernel_keyring: Vec<String>,
+    #[serde(default)]
+    pub kernel_programs: Vec<String>,
+    #[serde(default)]
+    pub kernel_modules: Vec<String>,
+    #[serde(default)]
+    pub namespaces: Vec<String>,
+    #[serde(default)]
+    pub mounts: Vec<String>,
+    #[serde(default)]
+    pub system_administration: Vec<String>,
+    /// Categories added after this binary was built (from a lists update)
+    #[serde(flatten)]
+    pub other: BTreeMap<String, Vec<String>>,
+}
+
+/// Guard rails for a command that is allowed to run.
+///
+/// Checked against the command's arguments wherever the command blocklist
+/// is checked; see [`super::command_rules`].
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CommandRule {
+    /// Options the command may not be given (`-r` also matches `-fr`,
+    /// `--force` also matches `--force=...`)
+    #[serde(default)]
+    pub deny_args: Vec<String>,
+    /// Directories every path operand must be inside (empty = anywhere)
+    #[serde(default)]
+    pub allow_paths: Vec<String>,
+    /// Subcommands the command may not run, e.g. `push --force`
+    #[serde(default)]
+    pub deny_subcommands: Vec<String>,
+}
+
+impl CommandRule {
+    /// The same rule with `expand` applied to each allowed path
+    pub fn with_paths(&self, expand: impl Fn(&str) -> String) -> Self {
+        Self {
+            allow_paths: self.allow_paths.iter().map(|p| expand(p)).collect(),
+            ..self.clone()
+        }
+    }
+}
+
+impl std::fmt::Display for CommandRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+        if !self.deny_args.is_empty() {
+            parts.push(format!("deny args {}", self.deny_args.join(", ")));
+        }
+        if !self.deny_subcommands.is_empty() {
+            parts.push(format!(
+                "deny subcommands '{}'",
+                self.deny_subcommands.join("', '")
+            ));
+        }
+        if !self.allow_paths.is_empty() {
+            parts.push(format!("paths under {}", self.allow_paths.join(", ")));
+        }
+        if parts.is_empty() {
+            parts.push("no restrictions".to_string());
+        }
+        write!(f, "{}", parts.join("; "))
+    }
 }
 
 /// System read paths needed for executables
@@ -169,6 +254,7 @@ impl SecurityLists {
         paths.extend(self.sensitive_paths.secrets_dirs.iter().cloned());
         paths.extend(self.sensitive_paths.shell_configs.iter().cloned());
         paths.extend(self.sensitive_paths.history_files.iter().cloned());
+        paths.extend(self.sensitive_paths.other.values().flatten().cloned());
 
         paths
     }
@@ -186,10 +272,20 @@ impl SecurityLists {
         commands.extend(self.dangerous_commands.network_exfiltration.iter().cloned());
         commands.extend(self.dangerous_commands.arbitrary_execution.iter().cloned());
         commands.extend(self.dangerous_commands.privilege_escalation.iter().cloned());
+        commands.extend(self.dangerous_commands.other.values().flatten().cloned());
 
         commands
     }
 
+    /// Get all dangerous syscalls as a flat set
+    pub fn all_dangerous_syscalls(&self) -> HashSet<String> {
+        dangerous_syscalls_by_category(self)
+            .into_values()
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
     /// Get system read paths for the current platform
     pub fn system_paths_for_platform(&self) -> Vec<String> {
         let mut paths = self.system_read_paths.common.clone();
@@ -233,7 +329,7 @@ impl SecurityLists {
 }
 
 /// Get all sensitive paths organized by category (for display/audit)
-pub fn sensitive_paths_by_category(lists: &SecurityLists) -> HashMap<&'static str, &Vec<String>> {
+pub fn sensitive_paths_by_category(lists: &SecurityLists) -> HashMap<&str, &Vec<String>> {
     let mut categories = HashMap::new();
 
     categories.insert("SSH keys and config", &lists.sensitive_paths.ssh);
@@ -252,6 +348,49 @@ pub fn sensitive_paths_by_category(lists: &SecurityLists) -> HashMap<&'static st
     categories.insert("Secrets directories", &lists.sensitive_paths.secrets_dirs);
     categories.insert("Shell configurations", &lists.sensitive_paths.shell_configs);
     categories.insert("Command history", &lists.sensitive_paths.history_files);
+    for (name, paths) in &lists.sensitive_paths.other {
+        categories.insert(name.as_str(), paths);
+    }
+
+    categories
+}
+
+/// Get all dangerous commands organized by category (for display/audit)
+pub fn dangerous_commands_by_category(lists: &SecurityLists) -> HashMap<&str, &Vec<String>> {
+    let mut categories = HashMap::new();
+
+    let commands = &lists.dangerous_commands;
+    categories.insert("File destruction", &commands.file_destruction);
+    categories.insert("Disk destruction", &commands.disk_destruction);
+    categories.insert("Permission changes", &commands.permission_chaos);
+    categories.insert("System modification", &commands.system_modification);
+    categories.insert("Package managers", &commands.package_managers);
+    categories.insert("Dangerous file operations", &commands.dangerous_file_ops);
+    categories.insert("Network exfiltration", &commands.network_exfiltration);
+    categories.insert("Arbitrary execution", &commands.arbitrary_execution);
+    categories.insert("Privilege escalation", &commands.privilege_escalation);
+    for (name, cmds) in &commands.other {
+        categories.insert(name.as_str(), cmds);
+    }
+
+    categories
+}
+
+/// Get all dangerous syscalls organized by category (for display/audit)
+pub fn dangerous_syscalls_by_category(lists: &SecurityLists) -> HashMap<&str, &Vec<String>> {
+    let mut categories = HashMap::new();
+
+    let syscalls = &lists.dangerous_syscalls;
+    categories.insert("Process inspection", &syscalls.process_inspection);
+    categories.insert("Kernel keyring", &syscalls.kernel_keyring);
+    categories.insert("Kernel programs", &syscalls.kernel_programs);
+    categories.insert("Kernel modules", &syscalls.kernel_modules);
+    categories.insert("Namespaces", &syscalls.namespaces);
+    categories.insert("Mounts", &syscalls.mounts);
+    categories.insert("System administration", &syscalls.system_administration);
+    for (name, calls) in &syscalls.other {
+        categories.insert(name.as_str(), calls);
+    }
 
     categories
 }
@@ -283,6 +422,7 @@ mod tests {
                 secrets_dirs: vec![],
                 shell_configs: vec!["~/.bashrc".to_string()],
                 history_files: vec![],
+                other: BTreeMap::new(),
             },
             dangerous_commands: DangerousCommands {
                 file_destruction: vec!["rm".to_string()],
@@ -294,6 +434,13 @@ mod tests {
                 network_exfiltration: vec![],
                 arbitrary_execution: vec![],
                 privilege_escalation: vec![],
+                other: BTreeMap::new(),
+            },
+            command_rules: BTreeMap::new(),
+            dangerous_syscalls: DangerousSyscalls {
+                process_inspection: vec!["ptrace".to_string()],
+                namespaces: vec!["unshare".to_string(), "setns".to_string()],
+                ..Default::default()
             },
             system_read_paths: SystemReadPaths {
                 common: vec!["/bin".to_string(), "/usr/bin".to_string()],
@@ -325,6 +472,17 @@ mod tests {
         assert_eq!(commands.len(), 3);
     }
 
+    #[test]
+    fn test_all_dangerous_syscalls() {
+        let lists = sample_lists();
+        let syscalls = lists.all_dangerous_syscalls();
+
+        assert!(syscalls.contains("ptrace"));
+        assert!(syscalls.contains("unshare"));
+        assert!(syscalls.contains("setns"));
+        assert_eq!(syscalls.len(), 3);
+    }
+
     #[test]
     fn test_system_paths_for_platform() {
         let lists = sample_lists();
@@ -333,4 +491,59 @@ mod tests {
         assert!(paths.contains(&"/bin".to_string()));
         assert!(paths.contains(&"/usr/bin".to_string()));
     }
+
+    #[test]
+    fn test_unknown_categories_are_kept() {
+        let lists: SecurityLists = toml::from_str(
+            r#"
+            [meta]
+            version = 2
+            schema_version = "0.2.0"
+
+            [sensitive_paths]
+            ssh = ["~/.ssh"]
+            ai_tokens = ["~/.config/some-agent/token"]
+
+            [dangerous_commands]
+            cloud_cli = ["gcloud"]
+
+            [dangerous_syscalls]
+            async_io = ["io_uring_setup"]
+
+            [system_read_paths]
+            "#,
+        )
+        .expect("lists should parse");
+
+        assert!(lists
+            .all_sensitive_paths()
+            .contains("~/.config/some-agent/token"));
+        assert!(lists.all_dangerous_commands().contains("gcloud"));
+        assert!(lists.all_dangerous_syscalls().contains("io_uring_setup"));
+        assert_eq!(
+            sensitive_paths_by_category(&lists)["ai_tokens"],
+            &vec!["~/.config/some-agent/token".to_string()]
+        );
+        assert_eq!(
+            dangerous_commands_by_category(&lists)["cloud_cli"],
+            &vec!["gcloud".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_command_rule_display() {
+        let rule: CommandRule = toml::from_str(
+            r#"
+            deny_args = ["-r", "-f"]
+            deny_subcommands = ["push --force"]
+            allow_paths = ["$WORKDIR"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            rule.to_string(),
+            "deny args -r, -f; deny subcommands 'push --force'; paths under $WORKDIR"
+        );
+        assert!(toml::from_str::<CommandRule>("deny_arg = [\"-r\"]").is_err());
+    }
 }
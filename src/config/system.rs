@@ -0,0 +1,402 @@
+//! System policy loading
+//!
+//! Loads the admin-managed policy from /etc/nono/policy.toml. The policy must
+//! be signed (policy.toml.minisig) with one of the keys listed under
+//! `[trusted_keys]` in /etc/nono/trusted-keys.toml. It can only add
+//! restrictions: sensitive paths, dangerous commands and a network floor.
+
+use super::user::{TrustedKey, TrustedKeyInfo};
+use super::verify;
+use crate::capability::CapabilitySet;
+use crate::error::{NonoError, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory holding the system policy
+pub const SYSTEM_CONFIG_DIR: &str = "/etc/nono";
+
+/// System policy file name
+const POLICY_FILE: &str = "policy.toml";
+
+/// Detached minisign signature of the policy
+const SIGNATURE_FILE: &str = "policy.toml.minisig";
+
+/// Keys allowed to sign the policy
+const TRUSTED_KEYS_FILE: &str = "trusted-keys.toml";
+
+/// Root structure for the system policy
+///
+/// Unknown keys are rejected: a misspelt table would otherwise silently
+/// drop a restriction the admin meant to enforce.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SystemPolicy {
+    #[serde(default)]
+    pub meta: PolicyMeta,
+
+    /// Additional sensitive paths, by category. Users cannot override them.
+    #[serde(default)]
+    pub sensitive_paths: HashMap<String, Vec<String>>,
+
+    /// Additional dangerous commands, by category. Users cannot allow them.
+    #[serde(default)]
+    pub dangerous_commands: HashMap<String, Vec<String>>,
+
+    /// Network restrictions every sandbox gets
+    #[serde(default)]
+    pub network: PolicyNetwork,
+
+    /// Name of the trusted key that signed the policy
+    #[serde(skip)]
+    pub signed_by: String,
+}
+
+/// Metadata for the system policy
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyMeta {
+    #[serde(default)]
+    pub version: u64,
+}
+
+/// Mandatory network rules
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyNetwork {
+    /// Block all network access
+    #[serde(default)]
+    pub block: bool,
+
+    /// The only TCP ports the sandbox may connect to (empty = no restriction)
+    #[serde(default)]
+    pub connect_ports: Vec<u16>,
+
+    /// The only TCP ports the sandbox may bind (empty = no restriction)
+    #[serde(default)]
+    pub bind_ports: Vec<u16>,
+}
+
+/// Keys trusted to sign the system policy
+#[derive(Debug, Default, Deserialize)]
+struct SystemTrustedKeys {
+    #[serde(default)]
+    trusted_keys: HashMap<String, TrustedKeyInfo>,
+}
+
+/// Load the system policy from /etc/nono
+///
+/// Returns None if there is no policy.
+/// Returns Err if the policy is not owned by root, is writable by others,
+/// is malformed or does not carry a valid signature from a trusted key.
+pub fn load_system_policy() -> Result<Option<SystemPolicy>> {
+    let dir = Path::new(SYSTEM_CONFIG_DIR);
+    if !dir.join(POLICY_FILE).exists() {
+        return Ok(None);
+    }
+
+    for name in [".", POLICY_FILE, SIGNATURE_FILE, TRUSTED_KEYS_FILE] {
+        check_root_owned(&dir.join(name))?;
+    }
+
+    load_policy_from(dir).map(Some)
+}
+
+/// Load and verify the policy in `dir`
+pub fn load_policy_from(dir: &Path) -> Result<SystemPolicy> {
+    let policy_path = dir.join(POLICY_FILE);
+    let data = read(&policy_path)?;
+    let signature = read(&dir.join(SIGNATURE_FILE))?;
+    let keys_content = read(&dir.join(TRUSTED_KEYS_FILE))?;
+
+    let keys: SystemTrustedKeys = toml::from_str(&keys_content)
+        .map_err(|e| NonoError::ConfigParse(format!("Failed to parse trusted keys: {}", e)))?;
+
+    // Accept the first trusted key the signature verifies against
+    let mut names: Vec<&String> = keys.trusted_keys.keys().collect();
+    names.sort();
+    let signed_by = names
+        .into_iter()
+        .find(|name| {
+            keys.trusted_keys[*name]
+                .public_key
+                .as_deref()
+                .is_some_and(|key| {
+                    verify::verify_minisign(data.as_bytes(), &signature, key).is_ok()
+                })
+        })
+        .ok_or_else(|| NonoError::SignatureInvalid {
+            reason: format!(
+                "{} is not signed by any key in {}",
+                policy_path.display(),
+                dir.join(TRUSTED_KEYS_FILE).display()
+            ),
+        })?;
+
+    let mut policy: SystemPolicy = toml::from_str(&data)
+        .map_err(|e| NonoError::ConfigParse(format!("Failed to parse system policy: {}", e)))?;
+    policy.signed_by = keys.trusted_keys[signed_by].name.clone();
+    Ok(policy)
+}
+
+/// Keys under `[trusted_keys]` in /etc/nono/trusted-keys.toml that have a
+/// `public_key`, sorted by entry name
+///
+/// Returns an empty list if there is no such file, and Err if it or
+/// /etc/nono is not owned by root or is writable by others.
+pub fn trusted_keys() -> Result<Vec<TrustedKey>> {
+    let dir = Path::new(SYSTEM_CONFIG_DIR);
+    let path = dir.join(TRUSTED_KEYS_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    check_root_owned(dir)?;
+    check_root_owned(&path)?;
+
+    trusted_keys_from(dir)
+}
+
+/// Keys with a `public_key` in the trusted keys file in `dir`
+pub fn trusted_keys_from(dir: &Path) -> Result<Vec<TrustedKey>> {
+    let keys: SystemTrustedKeys = toml::from_str(&read(&dir.join(TRUSTED_KEYS_FILE))?)
+        .map_err(|e| NonoError::ConfigParse(format!("Failed to parse trusted keys: {}", e)))?;
+    let mut keys: Vec<_> = keys
+        .trusted_keys
+        .into_iter()
+        .filter_map(|(id, info)| info.public_key.map(|key| (id, info.name, key)))
+        .collect();
+    keys.sort();
+    Ok(keys.into_iter().map(|(_, name, key)| (name, key)).collect())
+}
+
+fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(|e| NonoError::ConfigRead {
+        path: path.to_path_buf(),
+        source: e,
+    })
+}
+
+/// The policy is a floor for every user on the machine, so only root may
+/// be able to change or remove it
+fn check_root_owned(path: &Path) -> Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = fs::metadata(path).map_err(|e| NonoError::ConfigRead {
+        path: path.to_path_buf(),
+        source: e,
+    })?;
+    if metadata.uid() != 0 || metadata.mode() & 0o022 != 0 {
+        return Err(NonoError::PolicyViolation(format!(
+            "{} must be owned by root and not writable by group or others",
+            path.display()
+        )));
+    }
+    Ok(())
+}
+
+impl SystemPolicy {
+    /// All sensitive paths the policy adds
+    pub fn all_sensitive_paths(&self) -> impl Iterator<Item = &String> {
+        self.sensitive_paths.values().flatten()
+    }
+
+    /// Apply the policy's floor to `caps`.
+    ///
+    /// Grants inside a policy sensitive path and ports outside the policy's
+    /// port lists are refused; the network block and the port lists are
+    /// imposed when the user did not ask for anything narrower.
+    pub fn enforce(&self, caps: &mut CapabilitySet) -> Result<()> {
+        for protected in self.all_sensitive_paths() {
+            let expanded = PathBuf::from(super::expand_home(protected));
+            let resolved = expanded.canonicalize().unwrap_or(expanded);
+            if let Some(cap) = caps
+                .fs
+                .iter()
+                .find(|cap| cap.resolved.starts_with(&resolved))
+            {
+                return Err(NonoError::PolicyViolation(format!(
+                    "{} is protected by the system policy ({})",
+                    cap.resolved.display(),
+                    protected
+                )));
+            }
+        }
+
+        if self.network.block {
+            caps.net_block = true;
+        }
+        if caps.net_block {
+            return Ok(());
+        }
+        restrict_ports(
+            "connect",
+            &mut caps.tcp_connect_ports,
+            &self.network.connect_ports,
+        )?;
+        restrict_ports("bind", &mut caps.tcp_bind_ports, &self.network.bind_ports)?;
+        Ok(())
+    }
+}
+
+/// Limit `ports` to the policy's `allowed` list (no limit if it is empty)
+fn restrict_ports(kind: &str, ports: &mut Vec<u16>, allowed: &[u16]) -> Result<()> {
+    if allowed.is_empty() {
+        return Ok(());
+    }
+    if let Some(port) = ports.iter().find(|port| !allowed.contains(port)) {
+        return Err(NonoError::PolicyViolation(format!(
+            "TCP {} port {} is not allowed by the system policy",
+            kind, port
+        )));
+    }
+    if ports.is_empty() {
+        *ports = allowed.to_vec();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capability::{FsAccess, FsCapability};
+
+    // Signed once with a throwaway minisign key; only the public half is kept
+    const POLICY: &str = "[sensitive_paths]\ncorp = [\"/srv/corp-secrets\"]\n\n[dangerous_commands]\ncorp = [\"kubectl\"]\n\n[network]\nconnect_ports = [443]\n";
+    const PUBLIC_KEY: &str = "RWS3CKL+VJd1G1O7IhPuYSaToZtQ5X0rynnY+WbT8+WmUW7YMIU6faIm";
+    const SIGNATURE: &str = "untrusted comment: signature from minisign secret key\nRUS3CKL+VJd1G7mtWdOe9TofWnyR5Ik4rnuYUNDiK9/075ai9RnLdXu8KeS27STPPseqEkFi27Rc+bKjJFbQoI1SjpYF1uEZugc=\ntrusted comment: timestamp:1760000000 file:policy.toml\noAR9CMsdQY1bFqPRCV94w5hz4sxx+GjiFyC/gZkZQq7gqqTQ6ZDQ5Px2DFAXTv07+Q1tFBdiWzipWXLfZDI+Aw==\n";
+
+    fn policy_dir(policy: &str, public_key: &str) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().expect("tempdir");
+        fs::write(dir.path().join(POLICY_FILE), policy).expect("write policy");
+        fs::write(dir.path().join(SIGNATURE_FILE), SIGNATURE).expect("write signature");
+        fs::write(
+            dir.path().join(TRUSTED_KEYS_FILE),
+            format!(
+                "[trusted_keys]\nplatform = {{ name = \"Platform team\", public_key = \"{}\" }}\n",
+                public_key
+            ),
+        )
+        .expect("write keys");
+        dir
+    }
+
+    #[test]
+    fn test_load_signed_policy() {
+        let dir = policy_dir(POLICY, PUBLIC_KEY);
+        let policy = load_policy_from(dir.path()).expect("policy should verify");
+
+        assert_eq!(policy.signed_by, "Platform team");
+        assert_eq!(policy.sensitive_paths["corp"], vec!["/srv/corp-secrets"]);
+        assert_eq!(policy.dangerous_commands["corp"], vec!["kubectl"]);
+        assert_eq!(policy.network.connect_ports, vec![443]);
+        assert!(!policy.network.block);
+    }
+
+    #[test]
+    fn test_trusted_keys_from() {
+        let dir = policy_dir(POLICY, PUBLIC_KEY);
+        fs::write(
+            dir.path().join(TRUSTED_KEYS_FILE),
+            format!(
+                "[trusted_keys]\nplatform = {{ name = \"Platform team\", public_key = \"{}\" }}\n\
+                 fingerprint_only = {{ name = \"No key\", fingerprint = \"abc\" }}\n",
+                PUBLIC_KEY
+            ),
+        )
+        .expect("write keys");
+
+        assert_eq!(
+            trusted_keys_from(dir.path()).expect("keys should parse"),
+            vec![("Platform team".to_string(), PUBLIC_KEY.to_string())]
+        );
+    }
+
+    #[test]
+    fn test_tampered_policy_rejected() {
+        let tampered = POLICY.replace("443", "22");
+        let dir = policy_dir(&tampered, PUBLIC_KEY);
+        assert!(matches!(
+            load_policy_from(dir.path()),
+            Err(NonoError::SignatureInvalid { .. })
+        ));
+    }
+
+    #[test]
+    fn test_untrusted_key_rejected() {
+        let dir = policy_dir(
+            POLICY,
+            "RWQD4t6yn0pzBbx3+zgXTKnURhackXBR+xmyP2CjdAKDzBjXHhtBCPDP",
+        );
+        assert!(matches!(
+            load_policy_from(dir.path()),
+            Err(NonoError::SignatureInvalid { .. })
+        ));
+    }
+
+    #[test]
+    fn test_unknown_policy_keys_rejected() {
+        let result: std::result::Result<SystemPolicy, _> =
+            toml::from_str("[sensitive_path]\ncorp = [\"/srv\"]\n");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_enforce_network_floor() {
+        let mut policy = SystemPolicy::default();
+        policy.network.connect_ports = vec![443, 8443];
+
+        // Imposed when the user asked for nothing narrower
+        let mut caps = CapabilitySet::new();
+        policy.enforce(&mut caps).expect("enforce");
+        assert_eq!(caps.tcp_connect_ports, vec![443, 8443]);
+        assert!(caps.has_port_rules());
+
+        // A subset is fine, anything else is refused
+        let mut caps = CapabilitySet::new();
+        caps.tcp_connect_ports = vec![443];
+        policy.enforce(&mut caps).expect("enforce");
+        assert_eq!(caps.tcp_connect_ports, vec![443]);
+
+        let mut caps = CapabilitySet::new();
+        caps.tcp_connect_ports = vec![22];
+        assert!(matches!(
+            policy.enforce(&mut caps),
+            Err(NonoError::PolicyViolation(_))
+        ));
+
+        policy.network.block = true;
+        let mut caps = CapabilitySet::new();
+        policy.enforce(&mut caps).expect("enforce");
+        assert!(caps.net_block);
+    }
+
+    #[test]
+    fn test_enforce_refuses_grants_inside_policy_paths() {
+        let mut policy = SystemPolicy::default();
+        policy
+            .sensitive_paths
+            .insert("corp".to_string(), vec!["/srv/corp-secrets".to_string()]);
+
+        let grant = |path: &str| FsCapability {
+            original: PathBuf::from(path),
+            resolved: PathBuf::from(path),
+            access: FsAccess::Read,
+            is_file: false,
+            ttl: None,
+            expires_at: None,
+        };
+
+        // A broader grant is allowed: the path is hidden inside it
+        let mut caps = CapabilitySet::new();
+        caps.fs.push(grant("/srv"));
+        policy.enforce(&mut caps).expect("enforce");
+
+        let mut caps = CapabilitySet::new();
+        caps.fs.push(grant("/srv/corp-secrets/db"));
+        assert!(matches!(
+            policy.enforce(&mut caps),
+            Err(NonoError::PolicyViolation(_))
+        ));
+    }
+}
@@ -0,0 +1,202 @@
+//! Security list updates
+//!
+//! `nono lists update --from FILE` installs a newer security-lists.toml
+//! (same format as `data/security-lists.toml`) and its minisign signature
+//! `FILE.minisig` into the user state directory. The update is verified
+//! again on every load and only ever adds to the embedded lists.
+
+use super::security_lists::SecurityLists;
+use super::user::TrustedKey;
+use super::{embedded, system, verify, version};
+use crate::error::{NonoError, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Installed update file name, in the user state directory
+const UPDATE_FILE: &str = "security-lists.toml";
+
+/// Signature of the installed update
+const UPDATE_SIGNATURE_FILE: &str = "security-lists.toml.minisig";
+
+/// A verified security lists update
+#[derive(Debug)]
+pub struct ListsUpdate {
+    pub lists: SecurityLists,
+    /// Name of the trusted key that signed it
+    pub signed_by: String,
+}
+
+/// Load the installed update, if there is one.
+///
+/// An update that does not verify is an error rather than being skipped, so
+/// tampering with it cannot go unnoticed.
+pub fn load_installed_update() -> Result<Option<ListsUpdate>> {
+    let dir = update_dir()?;
+    let path = dir.join(UPDATE_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let data = read(&path)?;
+    let signature = read(&dir.join(UPDATE_SIGNATURE_FILE))?;
+    verify_update(&path, &data, &signature, &trusted_keys()?).map(Some)
+}
+
+/// Verify `bundle` and its `.minisig` and install them.
+///
+/// The update must be newer than both the embedded lists and any version
+/// seen before.
+pub fn install_update(bundle: &Path) -> Result<ListsUpdate> {
+    let mut sig_path = bundle.as_os_str().to_owned();
+    sig_path.push(".minisig");
+    let sig_path = PathBuf::from(sig_path);
+
+    let data = read(bundle)?;
+    let signature = read(&sig_path)?;
+    let update = verify_update(bundle, &data, &signature, &trusted_keys()?)?;
+
+    let embedded_version = embedded::load_security_lists()?.meta.version;
+    let new_version = update.lists.meta.version;
+    if new_version == embedded_version {
+        return Err(NonoError::ListsUpdate(format!(
+            "version {} is already built into this binary",
+            new_version
+        )));
+    }
+    if new_version < embedded_version {
+        return Err(NonoError::VersionDowngrade {
+            config: version::SECURITY_LISTS.to_string(),
+            current: embedded_version,
+            attempted: new_version,
+        });
+    }
+
+    let mut tracker = version::VersionTracker::load()?;
+    tracker.check_and_update(&[(version::LISTS_UPDATE, new_version, "update")])?;
+
+    let dir = update_dir()?;
+    fs::create_dir_all(&dir).map_err(|e| NonoError::ConfigWrite {
+        path: dir.clone(),
+        source: e,
+    })?;
+    write_replace(&dir.join(UPDATE_SIGNATURE_FILE), &signature)?;
+    write_replace(&dir.join(UPDATE_FILE), &data)?;
+    tracker.save()?;
+
+    Ok(update)
+}
+
+/// Path of the installed update
+pub fn installed_update_path() -> Result<PathBuf> {
+    Ok(update_dir()?.join(UPDATE_FILE))
+}
+
+/// Keys trusted to sign updates: the keys an administrator lists in
+/// /etc/nono/trusted-keys.toml.
+///
+/// Keys from the user config are not trusted here: anything that can write
+/// the user config could then sign lists of its own. Neither is
+/// [`embedded::AUTHOR_PUBLIC_KEY`] while it is a placeholder.
+fn trusted_keys() -> Result<Vec<TrustedKey>> {
+    system::trusted_keys()
+}
+
+fn verify_update(
+    path: &Path,
+    data: &str,
+    signature: &str,
+    keys: &[TrustedKey],
+) -> Result<ListsUpdate> {
+    if keys.is_empty() {
+        return Err(NonoError::SignatureInvalid {
+            reason: format!(
+                "cannot verify security lists update {}: no keys are trusted to sign \
+                 updates (an administrator lists them in /etc/nono/trusted-keys.toml)",
+                path.display()
+            ),
+        });
+    }
+    let signed_by = keys
+        .iter()
+        .find(|(_, key)| verify::verify_minisign(data.as_bytes(), signature, key).is_ok())
+        .map(|(name, _)| name.clone())
+        .ok_or_else(|| NonoError::SignatureInvalid {
+            reason: format!(
+                "security lists update {} is not signed by a trusted key",
+                path.display()
+            ),
+        })?;
+
+    let lists: SecurityLists = toml::from_str(data).map_err(|e| {
+        NonoError::ListsUpdate(format!("failed to parse {}: {}", path.display(), e))
+    })?;
+
+    Ok(ListsUpdate { lists, signed_by })
+}
+
+fn update_dir() -> Result<PathBuf> {
+    super::user_state_dir().ok_or_else(|| {
+        NonoError::ConfigParse("Could not determine user state directory".to_string())
+    })
+}
+
+fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(|e| NonoError::ConfigRead {
+        path: path.to_path_buf(),
+        source: e,
+    })
+}
+
+/// Write `content` to `path` through a temporary file and a rename
+fn write_replace(path: &Path, content: &str) -> Result<()> {
+    let tmp_path = path.with_extension(format!("tmp.{}", std::process::id()));
+    fs::write(&tmp_path, content)
+        .and_then(|()| fs::rename(&tmp_path, path))
+        .map_err(|e| {
+            let _ = fs::remove_file(&tmp_path);
+            NonoError::ConfigWrite {
+                path: path.to_path_buf(),
+                source: e,
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Signed once with a throwaway minisign key; only the public half is kept
+    const UPDATE: &str = "[meta]\nversion = 1000\nschema_version = \"0.2.0\"\n\n[sensitive_paths]\nai_tokens = [\"~/.config/some-agent\"]\n\n[dangerous_commands]\n\n[system_read_paths]\n";
+    const PUBLIC_KEY: &str = "RWT4CsHkm88ll+jOiMlqEcYZqIuaToqaZey7JmsQ/SZSHZ/YQ7YueNzY";
+    const SIGNATURE: &str = "untrusted comment: signature from minisign secret key\nRUT4CsHkm88llwttM21QrZZHyHA3rhqytbzrYRQ4nGdOjgfwQgCUZTCv4CAMAHZG3ifMtUDH15LOGHkIMKHnk2vyQIG9Hn6/sAQ=\ntrusted comment: timestamp:1760000000 file:security-lists.toml\na/9DEGvI/TcCdgvvo8s52ZdVZd/Hao65HZC2hwHGhx6Uc4jBSTqPwinJOByHe5OwlH7cX+pa7TqQOdQ11QdlAQ==\n";
+
+    fn keys() -> Vec<TrustedKey> {
+        vec![("Team".to_string(), PUBLIC_KEY.to_string())]
+    }
+
+    #[test]
+    fn test_verify_update() {
+        let update = verify_update(Path::new("u.toml"), UPDATE, SIGNATURE, &keys())
+            .expect("update should verify");
+
+        assert_eq!(update.signed_by, "Team");
+        assert_eq!(update.lists.meta.version, 1000);
+        assert!(update
+            .lists
+            .all_sensitive_paths()
+            .contains("~/.config/some-agent"));
+    }
+
+    #[test]
+    fn test_verify_update_rejects_tampering() {
+        let tampered = UPDATE.replace("some-agent", "other-agent");
+        assert!(matches!(
+            verify_update(Path::new("u.toml"), &tampered, SIGNATURE, &keys()),
+            Err(NonoError::SignatureInvalid { .. })
+        ));
+        assert!(matches!(
+            verify_update(Path::new("u.toml"), UPDATE, SIGNATURE, &[]),
+            Err(NonoError::SignatureInvalid { .. })
+        ));
+    }
+}
@@ -13,6 +13,9 @@ use std::path::PathBuf;
 /// User configuration file name
 const USER_CONFIG_FILE: &str = "config.toml";
 
+/// A key trusted for signatures: (name, minisign public key)
+pub type TrustedKey = (String, String);
+
 /// Root structure for user config
 #[derive(Debug, Default, Deserialize)]
 pub struct UserConfig {
@@ -88,6 +91,9 @@ pub struct TrustedKeyInfo {
     /// Key fingerprint for verification
     #[serde(default)]
     pub fingerprint: Option<String>,
+    /// Minisign public key (base64, starts with RW)
+    #[serde(default)]
+    pub public_key: Option<String>,
 }
 
 /// Load user configuration from ~/.config/nono/config.toml
@@ -112,6 +118,41 @@ pub fn load_user_config() -> Result<Option<UserConfig>> {
     Ok(Some(config))
 }
 
+/// Keys trusted to sign profiles, from `[trusted_keys]` in the user config.
+///
+/// A key is given inline as `public_key`, or in
+/// `~/.config/nono/trusted-keys/<name>.pub` (a minisign public key file).
+pub fn trusted_keys() -> Result<Vec<TrustedKey>> {
+    let Some(config) = load_user_config()? else {
+        return Ok(Vec::new());
+    };
+
+    let mut keys = Vec::new();
+    let mut names: Vec<_> = config.trusted_keys.into_iter().collect();
+    names.sort_by(|a, b| a.0.cmp(&b.0));
+    for (name, info) in names {
+        let key = match info.public_key {
+            Some(key) => key,
+            None => {
+                let path = user_trusted_keys_dir()?.join(format!("{}.pub", name));
+                let content = fs::read_to_string(&path).map_err(|e| NonoError::ConfigRead {
+                    path: path.clone(),
+                    source: e,
+                })?;
+                // Skip minisign's "untrusted comment:" line
+                content
+                    .lines()
+                    .find(|line| !line.is_empty() && !line.starts_with("untrusted comment:"))
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string()
+            }
+        };
+        keys.push((info.name, key));
+    }
+    Ok(keys)
+}
+
 /// Get the path to user config file
 pub fn user_config_path() -> Result<PathBuf> {
     let config_dir = super::user_config_dir().ok_or_else(|| {
@@ -1,9 +1,11 @@
 //! Version tracking for downgrade protection
 //!
 //! Prevents attackers from replacing current security lists with older
-//! (but still validly signed) versions.
-
-#![allow(dead_code)]
+//! (but still validly signed) versions. Every load of the embedded
+//! security lists, the installed lists update and the system policy is
+//! checked against and recorded in `versions.json` in the user state
+//! directory. The user config is not tracked: it is not signed, so its
+//! version proves nothing.
 
 use crate::error::{NonoError, Result};
 use chrono::{DateTime, Utc};
@@ -15,6 +17,15 @@ use std::path::PathBuf;
 /// Version state file name
 const VERSION_STATE_FILE: &str = "versions.json";
 
+/// Tracked name of the security lists compiled into the binary
+pub const SECURITY_LISTS: &str = "security_lists";
+
+/// Tracked name of the installed security lists update
+pub const LISTS_UPDATE: &str = "security_lists_update";
+
+/// Tracked name of the system policy in /etc/nono
+pub const SYSTEM_POLICY: &str = "system_policy";
+
 /// Version tracking for a single config source
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VersionState {
@@ -68,7 +79,16 @@ impl VersionTracker {
             NonoError::ConfigParse(format!("Failed to serialize version state: {}", e))
         })?;
 
-        fs::write(&path, content).map_err(|e| NonoError::ConfigWrite { path, source: e })?;
+        // Write then rename, so concurrent runs never see a partial file
+        let tmp_path = path.with_extension(format!("json.{}", std::process::id()));
+        fs::write(&tmp_path, content).map_err(|e| NonoError::ConfigWrite {
+            path: tmp_path.clone(),
+            source: e,
+        })?;
+        fs::rename(&tmp_path, &path).map_err(|e| {
+            let _ = fs::remove_file(&tmp_path);
+            NonoError::ConfigWrite { path, source: e }
+        })?;
 
         Ok(())
     }
@@ -104,8 +124,26 @@ impl VersionTracker {
         );
     }
 
+    /// Check all of `loaded` for downgrades, then update them together
+    pub fn check_and_update(&mut self, loaded: &[(&str, u64, &str)]) -> Result<()> {
+        for (name, version, _) in loaded {
+            self.check_version(name, *version)?;
+        }
+        for (name, version, source) in loaded {
+            self.update_version(name, *version, source);
+        }
+        Ok(())
+    }
+
+    /// Tracked configs sorted by name
+    pub fn sorted(&self) -> Vec<(&String, &VersionState)> {
+        let mut configs: Vec<_> = self.configs.iter().collect();
+        configs.sort_by(|a, b| a.0.cmp(b.0));
+        configs
+    }
+
     /// Get the state file path
-    fn state_file_path() -> Result<PathBuf> {
+    pub fn state_file_path() -> Result<PathBuf> {
         let state_dir = super::user_state_dir().ok_or_else(|| {
             NonoError::ConfigParse("Could not determine user state directory".to_string())
         })?;
@@ -114,20 +152,43 @@ impl VersionTracker {
     }
 }
 
-/// Check version and update tracker atomically
-pub fn check_and_update_version(name: &str, version: u64, source: &str) -> Result<()> {
-    let mut tracker = VersionTracker::load()?;
-
-    // Check for downgrade
-    tracker.check_version(name, version)?;
-
-    // Update and save
-    tracker.update_version(name, version, source);
-    tracker.save()?;
+/// Check each `(name, version, source)` against the tracked versions and
+/// record them.
+///
+/// Nothing is recorded if any of them is a downgrade. Failing to save the
+/// state is only a warning: the versions are still checked on the next run.
+/// A state file nono may not read (inside a sandbox, where the supervisor
+/// has already recorded the versions) is skipped.
+pub fn check_and_update_versions(loaded: &[(&str, u64, &str)]) -> Result<()> {
+    let mut tracker = match VersionTracker::load() {
+        Err(e) if super::is_out_of_reach(&e) => {
+            tracing::debug!("Not checking security list versions: {}", e);
+            return Ok(());
+        }
+        result => result?,
+    };
+    tracker.check_and_update(loaded)?;
+    if let Err(e) = tracker.save() {
+        tracing::warn!("Failed to record security list versions: {}", e);
+    }
 
     Ok(())
 }
 
+/// Check each `(name, version, source)` against the tracked versions
+/// without recording them, if the state can be read at all
+pub fn check_versions(loaded: &[(&str, u64, &str)]) -> Result<()> {
+    match VersionTracker::load() {
+        Ok(tracker) => loaded
+            .iter()
+            .try_for_each(|(name, version, _)| tracker.check_version(name, *version)),
+        Err(e) => {
+            tracing::debug!("Not checking security list versions: {}", e);
+            Ok(())
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -186,4 +247,63 @@ mod tests {
         assert_eq!(tracker.configs.get("test").unwrap().version, 5);
         assert_eq!(tracker.configs.get("test").unwrap().source, "system");
     }
+
+    #[test]
+    fn test_check_and_update_is_all_or_nothing() {
+        let mut tracker = VersionTracker::default();
+        tracker.update_version(SYSTEM_POLICY, 3, "system");
+
+        let result = tracker.check_and_update(&[
+            (SECURITY_LISTS, 2, "embedded"),
+            (SYSTEM_POLICY, 2, "system"),
+        ]);
+        assert!(matches!(result, Err(NonoError::VersionDowngrade { .. })));
+        assert!(!tracker.configs.contains_key(SECURITY_LISTS));
+        assert_eq!(tracker.configs[SYSTEM_POLICY].version, 3);
+
+        tracker
+            .check_and_update(&[
+                (SECURITY_LISTS, 2, "embedded"),
+                (SYSTEM_POLICY, 4, "system"),
+            ])
+            .expect("upgrade should be accepted");
+        assert_eq!(tracker.configs[SECURITY_LISTS].version, 2);
+        assert_eq!(tracker.configs[SYSTEM_POLICY].version, 4);
+        assert_eq!(tracker.sorted()[0].0, SECURITY_LISTS);
+    }
+
+    #[test]
+    fn test_lists_update_tracked_apart() {
+        let mut tracker = VersionTracker::default();
+        tracker
+            .check_and_update(&[
+                (SECURITY_LISTS, 1, "embedded"),
+                (LISTS_UPDATE, 1000, "update"),
+            ])
+            .expect("first load");
+
+        // Removing the update falls back to the embedded lists
+        tracker
+            .check_and_update(&[(SECURITY_LISTS, 1, "embedded")])
+            .expect("embedded lists without the update");
+        // An older update is still a downgrade
+        assert!(matches!(
+            tracker.check_and_update(&[
+                (SECURITY_LISTS, 1, "embedded"),
+                (LISTS_UPDATE, 900, "update")
+            ]),
+            Err(NonoError::VersionDowngrade { .. })
+        ));
+    }
+
+    #[test]
+    fn test_tracker_roundtrip() {
+        let mut tracker = VersionTracker::default();
+        tracker.update_version(SYSTEM_POLICY, 7, "system");
+
+        let json = serde_json::to_string(&tracker).expect("serialize");
+        let loaded: VersionTracker = serde_json::from_str(&json).expect("deserialize");
+        assert_eq!(loaded.configs[SYSTEM_POLICY].version, 7);
+        assert_eq!(loaded.configs[SYSTEM_POLICY].source, "system");
+    }
 }
@@ -13,7 +13,8 @@
 //! - **Actionable**: Provides specific flags to grant additional access
 //! - **Library code**: No process management, no CLI assumptions
 
-use crate::capability::{CapabilitySet, FsAccess};
+use crate::capability::{format_duration, format_ports, format_size, CapabilitySet, FsAccess};
+use nix::libc;
 
 /// Formats diagnostic information about sandbox policy.
 ///
@@ -45,10 +46,17 @@ impl<'a> DiagnosticFormatter<'a> {
         ));
         lines.push("[nono]".to_string());
 
+        // Resource limit the command may have run into
+        if let Some(hint) = self.limit_hint(exit_code) {
+            lines.push(hint);
+            lines.push("[nono]".to_string());
+        }
+
         // Policy summary
         lines.push("[nono] Sandbox policy:".to_string());
         self.format_allowed_paths(&mut lines);
         self.format_network_status(&mut lines);
+        self.format_limits(&mut lines);
 
         // Help section
         lines.push("[nono]".to_string());
@@ -61,6 +69,14 @@ impl<'a> DiagnosticFormatter<'a> {
             lines.push(
                 "[nono]   --allow-net        network access (remove --net-block)".to_string(),
             );
+        } else if self.caps.has_port_rules() {
+            if self.caps.has_host_allowlist() {
+                lines.push(
+                    "[nono]   --allow-host <host> connections to a host (via proxy)".to_string(),
+                );
+            }
+            lines.push("[nono]   --allow-port <port> TCP connections to a port".to_string());
+            lines.push("[nono]   --allow-bind <port> listening on a TCP port".to_string());
         }
 
         lines.join("\n")
@@ -78,6 +94,9 @@ impl<'a> DiagnosticFormatter<'a> {
                     FsAccess::Read => "read",
                     FsAccess::Write => "write",
                     FsAccess::ReadWrite => "read+write",
+                    FsAccess::ReadNoExec => "read, no exec",
+                    FsAccess::Execute if cfg!(target_os = "linux") => "exec, files readable",
+                    FsAccess::Execute => "exec only",
                 };
                 let kind = cap.kind_label();
                 lines.push(format!(
@@ -94,11 +113,71 @@ impl<'a> DiagnosticFormatter<'a> {
     fn format_network_status(&self, lines: &mut Vec<String>) {
         if self.caps.net_block {
             lines.push("[nono]   Network: blocked".to_string());
+        } else if self.caps.has_port_rules() {
+            if self.caps.has_host_allowlist() {
+                lines.push(format!(
+                    "[nono]   Network: hosts {} (via proxy)",
+                    self.caps.allow_hosts.join(", ")
+                ));
+            }
+            lines.push(format!(
+                "[nono]   Network: TCP connect to port(s) {}; bind to port(s) {}",
+                format_ports(&self.caps.tcp_connect_ports),
+                format_ports(&self.caps.tcp_bind_ports)
+            ));
         } else {
             lines.push("[nono]   Network: allowed".to_string());
         }
     }
 
+    /// Format the resource limits, if any are set.
+    fn format_limits(&self, lines: &mut Vec<String>) {
+        let limits = self.caps.limits.describe();
+        if !limits.is_empty() {
+            lines.push(format!("[nono]   Limits: {}", limits.join(", ")));
+        }
+    }
+
+    /// Explain a death by a signal that a resource limit sends.
+    ///
+    /// The signal is taken from a shell-style exit code (128 + signal), so
+    /// it also works for the exit status of a PID namespace's reaper.
+    fn limit_hint(&self, exit_code: i32) -> Option<String> {
+        let limits = &self.caps.limits;
+        let signal = exit_code.checked_sub(128)?;
+        if signal == libc::SIGXCPU {
+            let cpu_time = limits.cpu_time?;
+            Some(format!(
+                "[nono] The command was killed by SIGXCPU: it used up its CPU time limit of {} (--max-cpu-time).",
+                format_duration(cpu_time)
+            ))
+        } else if signal == libc::SIGXFSZ {
+            let file_size = limits.file_size?;
+            Some(format!(
+                "[nono] The command was killed by SIGXFSZ: it wrote past the file size limit of {} (--max-file-size).",
+                format_size(file_size)
+            ))
+        } else if signal == libc::SIGKILL && (limits.memory.is_some() || limits.cpu_time.is_some())
+        {
+            let mut reached = Vec::new();
+            if let Some(memory) = limits.memory {
+                reached.push(format!("memory {} (--max-memory)", format_size(memory)));
+            }
+            if let Some(cpu_time) = limits.cpu_time {
+                reached.push(format!(
+                    "CPU time {} (--max-cpu-time)",
+                    format_duration(cpu_time)
+                ));
+            }
+            Some(format!(
+                "[nono] The command was killed by SIGKILL, which may be due to its resource limits: {}.",
+                reached.join(", ")
+            ))
+        } else {
+            None
+        }
+    }
+
     /// Format a concise single-line summary of the policy.
     ///
     /// Useful for logging or brief status messages.
@@ -108,6 +187,8 @@ impl<'a> DiagnosticFormatter<'a> {
         let path_count = self.caps.fs.len();
         let network_status = if self.caps.net_block {
             "blocked"
+        } else if self.caps.has_port_rules() {
+            "port-filtered"
         } else {
             "allowed"
         };
@@ -132,6 +213,8 @@ mod tests {
             resolved: PathBuf::from("/test/project"),
             access: FsAccess::ReadWrite,
             is_file: false,
+            ttl: None,
+            expires_at: None,
         });
         caps.net_block = true;
         caps
@@ -221,6 +304,30 @@ mod tests {
         assert!(output.contains("--allow-net"));
     }
 
+    #[test]
+    fn test_format_footer_shows_port_rules() {
+        let mut caps = make_test_caps();
+        caps.net_block = false;
+        caps.tcp_connect_ports = vec![443];
+        let formatter = DiagnosticFormatter::new(&caps);
+        let output = formatter.format_footer(1);
+
+        assert!(output.contains("TCP connect to port(s) 443; bind to port(s) none"));
+        assert!(output.contains("--allow-port"));
+    }
+
+    #[test]
+    fn test_format_footer_shows_allowed_hosts() {
+        let mut caps = make_test_caps();
+        caps.net_block = false;
+        caps.allow_hosts = vec!["api.anthropic.com".to_string()];
+        let formatter = DiagnosticFormatter::new(&caps);
+        let output = formatter.format_footer(1);
+
+        assert!(output.contains("Network: hosts api.anthropic.com (via proxy)"));
+        assert!(output.contains("--allow-host"));
+    }
+
     #[test]
     fn test_format_footer_no_network_help_when_allowed() {
         let mut caps = make_test_caps();
@@ -259,12 +366,16 @@ mod tests {
             resolved: PathBuf::from("/test/file.txt"),
             access: FsAccess::Read,
             is_file: true,
+            ttl: None,
+            expires_at: None,
         });
         caps.fs.push(FsCapability {
             original: PathBuf::from("/test/dir"),
             resolved: PathBuf::from("/test/dir"),
             access: FsAccess::Write,
             is_file: false,
+            ttl: None,
+            expires_at: None,
         });
 
         let formatter = DiagnosticFormatter::new(&caps);
@@ -273,4 +384,46 @@ mod tests {
         assert!(output.contains("file.txt (read, file)"));
         assert!(output.contains("dir (write, dir)"));
     }
+
+    #[test]
+    fn test_format_footer_shows_limits() {
+        let mut caps = make_test_caps();
+        caps.limits.memory = Some(2 << 30);
+        caps.limits.processes = Some(256);
+        let formatter = DiagnosticFormatter::new(&caps);
+        let output = formatter.format_footer(1);
+
+        assert!(output.contains("Limits: memory 2G, processes 256"));
+        assert!(!output.contains("killed by"));
+    }
+
+    #[test]
+    fn test_format_footer_explains_limit_signals() {
+        let mut caps = make_test_caps();
+        caps.limits.cpu_time = Some(std::time::Duration::from_secs(600));
+        caps.limits.file_size = Some(100 << 20);
+        let formatter = DiagnosticFormatter::new(&caps);
+
+        let output = formatter.format_footer(128 + libc::SIGXCPU);
+        assert!(output.contains("killed by SIGXCPU"));
+        assert!(output.contains("CPU time limit of 10m (--max-cpu-time)"));
+
+        let output = formatter.format_footer(128 + libc::SIGXFSZ);
+        assert!(output.contains("file size limit of 100M (--max-file-size)"));
+
+        let output = formatter.format_footer(128 + libc::SIGKILL);
+        assert!(output.contains("killed by SIGKILL"));
+        assert!(output.contains("CPU time 10m (--max-cpu-time)"));
+        assert!(!output.contains("--max-memory"));
+
+        // Without the matching limit, the signal is not blamed on one
+        caps.limits = Default::default();
+        let formatter = DiagnosticFormatter::new(&caps);
+        assert!(!formatter
+            .format_footer(128 + libc::SIGXCPU)
+            .contains("killed by"));
+        assert!(!formatter
+            .format_footer(128 + libc::SIGKILL)
+            .contains("killed by"));
+    }
 }
@@ -0,0 +1,1093 @@
+//! Ephemeral copy-on-write workspace (`--ephemeral`).
+//!
+//! Before the command starts, the supervisor moves itself into a private
+//! user and mount namespace and mounts an overlayfs over every directory the
+//! command may write to. The real directory becomes the read-only lower
+//! layer; all writes land in an upper layer in a staging directory. The
+//! sandboxed child inherits these mounts, and its Landlock rules are built
+//! against the overlay, so there is no path to the real files.
+//!
+//! When the command exits, the supervisor scans the upper layers, shows what
+//! changed and lets the operator commit the changes to the real directories,
+//! discard them, or export them as a patch. The real directories stay
+//! reachable through a descriptor for `/` opened before the namespace was
+//! entered: paths resolved through `/proc/self/fd/<fd>` walk the original
+//! mount tree, not the overlays.
+
+use crate::capability::CapabilitySet;
+use crate::error::{NonoError, Result};
+use colored::Colorize;
+use std::fs::{self, File};
+use std::io::{BufRead, IsTerminal, Write};
+use std::os::unix::fs::{FileTypeExt, MetadataExt, OpenOptionsExt, PermissionsExt};
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+/// Lines of unchanged context around each hunk in generated diffs
+const DIFF_CONTEXT: usize = 3;
+
+/// Largest line-table (old lines x new lines) diffed line by line; beyond
+/// this the changed region is emitted as a single replacement
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Default file name offered when exporting a patch
+const DEFAULT_PATCH_NAME: &str = "nono-ephemeral.patch";
+
+/// What happened to a path in the ephemeral workspace
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Deleted,
+}
+
+impl ChangeKind {
+    fn marker(self) -> &'static str {
+        match self {
+            ChangeKind::Added => "A",
+            ChangeKind::Modified => "M",
+            ChangeKind::Deleted => "D",
+        }
+    }
+}
+
+/// A single changed path, relative to its layer's directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    layer: usize,
+    path: PathBuf,
+    kind: ChangeKind,
+    is_dir: bool,
+}
+
+/// One overlay: a granted directory and the layers that capture its writes
+struct Layer {
+    target: PathBuf,
+    upper: PathBuf,
+    work: PathBuf,
+}
+
+/// Operator's choice once the command has exited
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Commit,
+    Discard,
+    Export,
+    View,
+}
+
+impl Action {
+    fn parse(answer: &str) -> Option<Self> {
+        match answer.trim().to_ascii_lowercase().as_str() {
+            "c" | "commit" => Some(Action::Commit),
+            "d" | "discard" => Some(Action::Discard),
+            "e" | "export" => Some(Action::Export),
+            "v" | "view" => Some(Action::View),
+            _ => None,
+        }
+    }
+}
+
+/// Staged overlays for one `--ephemeral` session
+pub struct EphemeralSession {
+    staging: PathBuf,
+    layers: Vec<Layer>,
+    /// `/` as seen before the mount namespace was entered
+    host_root: File,
+    /// Writable single-file grants that an overlay cannot cover
+    uncovered_files: Vec<PathBuf>,
+}
+
+impl EphemeralSession {
+    /// Create the staging directory and one upper/work pair per writable
+    /// directory grant.
+    pub fn create(caps: &CapabilitySet) -> Result<Self> {
+        if !cfg!(target_os = "linux") {
+            return Err(NonoError::Ephemeral(
+                "--ephemeral requires Linux (overlayfs in a user namespace)".to_string(),
+            ));
+        }
+
+        let mut targets: Vec<PathBuf> = caps
+            .fs
+            .iter()
+            .filter(|cap| !cap.is_file && cap.access.writes())
+            .map(|cap| cap.resolved.clone())
+            .collect();
+        targets.sort();
+        targets.dedup();
+        // A nested grant is already covered by its ancestor's overlay
+        let targets: Vec<PathBuf> = targets
+            .iter()
+            .filter(|t| !targets.iter().any(|o| o != *t && t.starts_with(o)))
+            .cloned()
+            .collect();
+
+        if targets.is_empty() {
+            return Err(NonoError::Ephemeral(
+                "no writable directories granted; nothing to isolate".to_string(),
+            ));
+        }
+        for target in &targets {
+            // overlayfs mount options use ',' and ':' as separators
+            let s = target.to_string_lossy();
+            if s.contains(',') || s.contains(':') || s.contains('\\') {
+                return Err(NonoError::Ephemeral(format!(
+                    "cannot overlay {}: path contains ',', ':' or '\\'",
+                    target.display()
+                )));
+            }
+        }
+
+        let uncovered_files = caps
+            .fs
+            .iter()
+            .filter(|cap| cap.is_file && cap.access.writes())
+            .filter(|cap| !targets.iter().any(|t| cap.resolved.starts_with(t)))
+            .map(|cap| cap.resolved.clone())
+            .collect();
+
+        let staging = staging_dir(&targets)?;
+        let mut layers = Vec::with_capacity(targets.len());
+        for (i, target) in targets.into_iter().enumerate() {
+            let upper = staging.join(i.to_string()).join("upper");
+            let work = staging.join(i.to_string()).join("work");
+            for dir in [&upper, &work] {
+                fs::create_dir_all(dir).map_err(|e| {
+                    NonoError::Ephemeral(format!("failed to create {}: {}", dir.display(), e))
+                })?;
+            }
+            layers.push(Layer {
+                target,
+                upper,
+                work,
+            });
+        }
+
+        let host_root = File::open("/")
+            .map_err(|e| NonoError::Ephemeral(format!("failed to open /: {}", e)))?;
+
+        Ok(Self {
+            staging,
+            layers,
+            host_root,
+            uncovered_files,
+        })
+    }
+
+    /// Directories whose writes are captured
+    pub fn targets(&self) -> impl Iterator<Item = &Path> {
+        self.layers.iter().map(|l| l.target.as_path())
+    }
+
+    /// Writable files that are written through directly
+    pub fn uncovered_files(&self) -> &[PathBuf] {
+        &self.uncovered_files
+    }
+
+    /// Enter a private user and mount namespace and mount the overlays.
+    ///
+    /// Must be called while the process is still single-threaded (a
+    /// requirement of `unshare(CLONE_NEWUSER)`) and before the sandbox
+    /// policy is prepared, so Landlock rules refer to the overlays.
+    #[cfg(target_os = "linux")]
+    pub fn enter(&self) -> Result<()> {
+        use crate::sandbox::namespace;
+
+        namespace::enter()?;
+
+        for layer in &self.layers {
+            let options = format!(
+                "lowerdir={},upperdir={},workdir={},userxattr",
+                layer.target.display(),
+                layer.upper.display(),
+                layer.work.display()
+            );
+            namespace::mount(
+                Some(Path::new("overlay")),
+                &layer.target,
+                Some("overlay"),
+                0,
+                Some(&options),
+            )
+            .map_err(|e| {
+                NonoError::Ephemeral(format!(
+                    "failed to mount overlay on {}: {}",
+                    layer.target.display(),
+                    e
+                ))
+            })?;
+            debug!("Mounted ephemeral overlay on {}", layer.target.display());
+        }
+
+        // Our working directory still points at the real directory; look it
+        // up again so it (and the child's) resolves through the overlay
+        namespace::refresh_cwd();
+
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn enter(&self) -> Result<()> {
+        Err(NonoError::Ephemeral(
+            "--ephemeral requires Linux (overlayfs in a user namespace)".to_string(),
+        ))
+    }
+
+    /// The real (host) location of an absolute path, bypassing the overlays
+    pub fn host_path(&self, path: &Path) -> PathBuf {
+        let mut host = PathBuf::from(format!("/proc/self/fd/{}", self.host_root.as_raw_fd()));
+        host.push(path.strip_prefix("/").unwrap_or(path));
+        host
+    }
+
+    fn layer_host_path(&self, change: &Change) -> PathBuf {
+        self.host_path(&self.layers[change.layer].target.join(&change.path))
+    }
+
+    fn display_path(&self, change: &Change) -> PathBuf {
+        self.layers[change.layer].target.join(&change.path)
+    }
+
+    /// Scan the upper layers for changes against the real directories
+    pub fn changes(&self) -> Result<Vec<Change>> {
+        let mut changes = Vec::new();
+        for (i, layer) in self.layers.iter().enumerate() {
+            let lower = self.host_path(&layer.target);
+            scan(i, &layer.upper, &lower, Path::new(""), false, &mut changes).map_err(|e| {
+                NonoError::Ephemeral(format!(
+                    "failed to scan changes for {}: {}",
+                    layer.target.display(),
+                    e
+                ))
+            })?;
+        }
+        Ok(changes)
+    }
+
+    /// Review the changes and commit, discard or export them.
+    ///
+    /// Without a terminal to ask on, changes are exported to a patch in the
+    /// temp directory and nothing touches the real directories.
+    pub fn finish(self, silent: bool) -> Result<()> {
+        let changes = self.changes()?;
+        if changes.is_empty() {
+            if !silent {
+                eprintln!(
+                    "{}",
+                    "Ephemeral workspace: no changes.".truecolor(150, 150, 150)
+                );
+            }
+            self.cleanup();
+            return Ok(());
+        }
+
+        let stdin = std::io::stdin();
+        if silent || !stdin.is_terminal() {
+            // SECURITY: the command may share the temp directory, so the
+            // name must not be guessable and the file must be new: a planted
+            // symlink would have the supervisor overwrite its target
+            let temp_dir = std::env::temp_dir();
+            let (file, host) = tempfile::Builder::new()
+                .prefix("nono-ephemeral-")
+                .suffix(".patch")
+                .tempfile_in(self.host_path(&temp_dir))
+                .and_then(|file| file.keep().map_err(|e| e.error))
+                .map_err(|e| {
+                    NonoError::Ephemeral(format!(
+                        "failed to create a patch file in {}: {}",
+                        temp_dir.display(),
+                        e
+                    ))
+                })?;
+            let dest = temp_dir.join(host.file_name().unwrap_or_default());
+            let skipped = self.export(&changes, file, &dest)?;
+            eprintln!(
+                "nono: ephemeral changes exported to {} (not applied)",
+                dest.display()
+            );
+            if skipped.is_empty() {
+                self.cleanup();
+            } else {
+                // The patch can't carry these, so keep the upper layers
+                eprintln!(
+                    "nono: {} binary or special file(s) not in the patch are kept in {}",
+                    skipped.len(),
+                    self.staging.display()
+                );
+            }
+            return Ok(());
+        }
+
+        self.print_summary(&changes);
+        loop {
+            eprint!(
+                "  {} ",
+                "[c]ommit, [d]iscard, [e]xport patch, [v]iew diff:".white()
+            );
+            std::io::stderr().flush().ok();
+
+            let mut input = String::new();
+            let read = stdin
+                .lock()
+                .read_line(&mut input)
+                .map_err(NonoError::CommandExecution)?;
+            if read == 0 {
+                // EOF: never apply or drop changes without an answer
+                eprintln!();
+                eprintln!(
+                    "nono: no answer; ephemeral changes kept in {}",
+                    self.staging.display()
+                );
+                return Ok(());
+            }
+
+            match Action::parse(&input) {
+                Some(Action::Commit) => {
+                    self.commit(&changes)?;
+                    eprintln!(
+                        "{}",
+                        format!("Committed {} change(s).", changes.len()).green()
+                    );
+                    self.cleanup();
+                    return Ok(());
+                }
+                Some(Action::Discard) => {
+                    eprintln!(
+                        "{}",
+                        "Discarded ephemeral changes.".truecolor(150, 150, 150)
+                    );
+                    self.cleanup();
+                    return Ok(());
+                }
+                Some(Action::Export) => {
+                    eprint!(
+                        "  {} ",
+                        format!("Patch file [{}]:", DEFAULT_PATCH_NAME).white()
+                    );
+                    std::io::stderr().flush().ok();
+                    let mut name = String::new();
+                    stdin
+                        .lock()
+                        .read_line(&mut name)
+                        .map_err(NonoError::CommandExecution)?;
+                    let name = match name.trim() {
+                        "" => DEFAULT_PATCH_NAME,
+                        name => name,
+                    };
+                    let dest = std::env::current_dir()
+                        .map_err(NonoError::CommandExecution)?
+                        .join(name);
+                    // The operator picked the name; still never follow a
+                    // symlink the command may have left there
+                    let file = fs::OpenOptions::new()
+                        .write(true)
+                        .create(true)
+                        .truncate(true)
+                        .custom_flags(nix::libc::O_NOFOLLOW)
+                        .open(self.host_path(&dest))
+                        .map_err(|e| {
+                            NonoError::Ephemeral(format!(
+                                "failed to open {}: {}",
+                                dest.display(),
+                                e
+                            ))
+                        })?;
+                    let skipped = self.export(&changes, file, &dest)?;
+                    eprintln!(
+                        "{}",
+                        format!("Exported patch to {}", dest.display()).green()
+                    );
+                    for path in &skipped {
+                        eprintln!(
+                            "  Not in patch (binary or special file): {}",
+                            path.display()
+                        );
+                    }
+                    self.cleanup();
+                    return Ok(());
+                }
+                Some(Action::View) => {
+                    let (patch, _) = self.render_patch(&changes)?;
+                    print_colored_diff(&patch);
+                }
+                None => {}
+            }
+        }
+    }
+
+    fn print_summary(&self, changes: &[Change]) {
+        eprintln!();
+        eprintln!("{}", "Ephemeral workspace changes:".white().bold());
+        for change in changes {
+            let mut path = self.display_path(change).display().to_string();
+            if change.is_dir {
+                path.push('/');
+            }
+            let marker = match change.kind {
+                ChangeKind::Added => change.kind.marker().green(),
+                ChangeKind::Modified => change.kind.marker().yellow(),
+                ChangeKind::Deleted => change.kind.marker().red(),
+            };
+            eprintln!("  {} {}", marker, path);
+        }
+    }
+
+    /// Apply the upper layers to the real directories
+    fn commit(&self, changes: &[Change]) -> Result<()> {
+        for change in changes {
+            let upper = self.layers[change.layer].upper.join(&change.path);
+            let host = self.layer_host_path(change);
+            commit_change(change, &upper, &host).map_err(|e| {
+                NonoError::Ephemeral(format!(
+                    "failed to commit {}: {}",
+                    self.display_path(change).display(),
+                    e
+                ))
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Build a unified diff of all text changes.
+    ///
+    /// Paths are relative to each layer's directory (`a/<path>`, `b/<path>`);
+    /// with several layers, each section is preceded by a comment naming the
+    /// directory to apply it in. Returns the patch and the paths it can't
+    /// represent (binary files, symlinks, special files).
+    fn render_patch(&self, changes: &[Change]) -> Result<(String, Vec<PathBuf>)> {
+        let mut patch = String::new();
+        let mut skipped = Vec::new();
+        let mut current_layer = None;
+
+        for change in changes {
+            if self.layers.len() > 1 && current_layer != Some(change.layer) {
+                current_layer = Some(change.layer);
+                patch.push_str(&format!(
+                    "# nono: apply in {}\n",
+                    self.layers[change.layer].target.display()
+                ));
+            }
+            let upper_root = &self.layers[change.layer].upper;
+            let host_root = self.host_path(&self.layers[change.layer].target);
+
+            // Deleted directories expand to their files
+            let files = if change.kind == ChangeKind::Deleted && change.is_dir {
+                let mut files = Vec::new();
+                list_files(&host_root, &change.path, &mut files).map_err(|e| {
+                    NonoError::Ephemeral(format!("failed to list deleted directory: {}", e))
+                })?;
+                files
+            } else if change.is_dir {
+                continue;
+            } else {
+                vec![change.path.clone()]
+            };
+
+            for rel in files {
+                let old = match change.kind {
+                    ChangeKind::Added => Some(None),
+                    _ => read_text(&host_root.join(&rel)).map(Some),
+                };
+                let new = match change.kind {
+                    ChangeKind::Deleted => Some(None),
+                    _ => read_text(&upper_root.join(&rel)).map(Some),
+                };
+                match (old, new) {
+                    (Some(old), Some(new)) => {
+                        let rel = rel.display();
+                        let old_label = old
+                            .as_ref()
+                            .map_or("/dev/null".to_string(), |_| format!("a/{}", rel));
+                        let new_label = new
+                            .as_ref()
+                            .map_or("/dev/null".to_string(), |_| format!("b/{}", rel));
+                        patch.push_str(&unified_diff(
+                            &old_label,
+                            &new_label,
+                            old.as_deref().unwrap_or(""),
+                            new.as_deref().unwrap_or(""),
+                        ));
+                    }
+                    _ => skipped.push(self.layers[change.layer].target.join(&rel)),
+                }
+            }
+        }
+        Ok((patch, skipped))
+    }
+
+    /// Write the patch to `file`, opened at `dest` on the host filesystem
+    fn export(&self, changes: &[Change], mut file: File, dest: &Path) -> Result<Vec<PathBuf>> {
+        let (patch, skipped) = self.render_patch(changes)?;
+        file.write_all(patch.as_bytes()).map_err(|e| {
+            NonoError::Ephemeral(format!("failed to write {}: {}", dest.display(), e))
+        })?;
+        Ok(skipped)
+    }
+
+    /// Remove the staging directory and everything in it
+    fn cleanup(self) {
+        // overlayfs leaves its work/work directory with mode 000
+        for layer in &self.layers {
+            let inner = layer.work.join("work");
+            if inner.exists() {
+                let _ = fs::set_permissions(&inner, fs::Permissions::from_mode(0o700));
+            }
+        }
+        if let Err(e) = fs::remove_dir_all(&self.staging) {
+            warn!(
+                "Failed to remove ephemeral staging directory {}: {}",
+                self.staging.display(),
+                e
+            );
+        }
+    }
+}
+
+/// Pick and create a staging directory outside every overlaid directory
+fn staging_dir(targets: &[PathBuf]) -> Result<PathBuf> {
+    let name = format!("nono-ephemeral-{}", std::process::id());
+    let candidates = [
+        Some(std::env::temp_dir()),
+        dirs::state_dir().map(|d| d.join("nono")),
+    ];
+    for base in candidates.into_iter().flatten() {
+        let base = base.canonicalize().unwrap_or(base);
+        if targets.iter().any(|t| base.starts_with(t)) {
+            continue;
+        }
+        let dir = base.join(&name);
+        fs::create_dir_all(&base)
+            .and_then(|_| fs::create_dir(&dir))
+            .and_then(|_| fs::set_permissions(&dir, fs::Permissions::from_mode(0o700)))
+            .map_err(|e| {
+                NonoError::Ephemeral(format!("failed to create {}: {}", dir.display(), e))
+            })?;
+        return Ok(dir);
+    }
+    Err(NonoError::Ephemeral(
+        "no staging location outside the writable directories (tried $TMPDIR and ~/.local/state)"
+            .to_string(),
+    ))
+}
+
+/// Whether an upper-layer directory hides the lower directory's contents
+fn is_opaque(path: &Path) -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        use nix::libc;
+        use std::os::unix::ffi::OsStrExt;
+
+        let Ok(c_path) = std::ffi::CString::new(path.as_os_str().as_bytes()) else {
+            return false;
+        };
+        let mut value = [0u8; 1];
+        // SAFETY: path and name are NUL-terminated; value is a valid buffer of its length
+        let len = unsafe {
+            libc::lgetxattr(
+                c_path.as_ptr(),
+                c"user.overlay.opaque".as_ptr(),
+                value.as_mut_ptr() as *mut libc::c_void,
+                value.len(),
+            )
+        };
+        len == 1 && value[0] == b'y'
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = path;
+        false
+    }
+}
+
+/// Walk one upper-layer directory, recording how it differs from `lower`.
+///
+/// `hidden` is set below opaque directories, whose lower contents are
+/// replaced rather than merged: lower entries missing from the upper layer
+/// were deleted.
+fn scan(
+    layer: usize,
+    upper: &Path,
+    lower: &Path,
+    rel: &Path,
+    hidden: bool,
+    changes: &mut Vec<Change>,
+) -> std::io::Result<()> {
+    let mut entries: Vec<_> = fs::read_dir(upper)?.collect::<std::io::Result<_>>()?;
+    entries.sort_by_key(|e| e.file_name());
+
+    for entry in &entries {
+        let name = entry.file_name();
+        let upper_path = entry.path();
+        let lower_path = lower.join(&name);
+        let rel_path = rel.join(&name);
+        let meta = fs::symlink_metadata(&upper_path)?;
+        let lower_meta = fs::symlink_metadata(&lower_path).ok();
+        let change = |kind, is_dir| Change {
+            layer,
+            path: rel_path.clone(),
+            kind,
+            is_dir,
+        };
+
+        if meta.file_type().is_char_device() && meta.rdev() == 0 {
+            // Whiteout: the lower entry was deleted
+            if let Some(lower_meta) = lower_meta {
+                changes.push(change(ChangeKind::Deleted, lower_meta.is_dir()));
+            }
+        } else if meta.is_dir() {
+            let lower_is_dir = lower_meta.as_ref().is_some_and(|m| m.is_dir());
+            if !lower_is_dir {
+                let kind = if lower_meta.is_some() {
+                    ChangeKind::Modified
+                } else {
+                    ChangeKind::Added
+                };
+                changes.push(change(kind, true));
+            } else if meta.permissions().mode() & 0o7777
+                != lower_meta
+                    .as_ref()
+                    .map_or(0, |m| m.permissions().mode() & 0o7777)
+            {
+                changes.push(change(ChangeKind::Modified, true));
+            }
+            let opaque = hidden || is_opaque(&upper_path);
+            scan(layer, &upper_path, &lower_path, &rel_path, opaque, changes)?;
+        } else {
+            match lower_meta {
+                Some(lower_meta)
+                    if !hidden && same_entry(&upper_path, &meta, &lower_path, &lower_meta) => {}
+                Some(_) => changes.push(change(ChangeKind::Modified, false)),
+                None => changes.push(change(ChangeKind::Added, false)),
+            }
+        }
+    }
+
+    if hidden {
+        if let Ok(lower_entries) = fs::read_dir(lower) {
+            let mut deleted: Vec<_> = lower_entries
+                .filter_map(|e| e.ok())
+                .filter(|e| !entries.iter().any(|u| u.file_name() == e.file_name()))
+                .collect();
+            deleted.sort_by_key(|e| e.file_name());
+            for entry in deleted {
+                changes.push(Change {
+                    layer,
+                    path: rel.join(entry.file_name()),
+                    kind: ChangeKind::Deleted,
+                    is_dir: entry.file_type().is_ok_and(|t| t.is_dir()),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Whether a copied-up file is unchanged (opened for writing but not modified)
+fn same_entry(
+    upper: &Path,
+    upper_meta: &fs::Metadata,
+    lower: &Path,
+    lower_meta: &fs::Metadata,
+) -> bool {
+    if upper_meta.file_type() != lower_meta.file_type()
+        || upper_meta.permissions().mode() != lower_meta.permissions().mode()
+    {
+        return false;
+    }
+    if upper_meta.file_type().is_symlink() {
+        return fs::read_link(upper).ok() == fs::read_link(lower).ok();
+    }
+    if !upper_meta.is_file() || upper_meta.len() != lower_meta.len() {
+        return false;
+    }
+    match (fs::read(upper), fs::read(lower)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Apply a single change from the upper layer to the host path
+fn commit_change(change: &Change, upper: &Path, host: &Path) -> std::io::Result<()> {
+    let existing = fs::symlink_metadata(host).ok();
+    let remove_existing = || match &existing {
+        Some(m) if m.is_dir() => fs::remove_dir_all(host),
+        Some(_) => fs::remove_file(host),
+        None => Ok(()),
+    };
+
+    if change.kind == ChangeKind::Deleted {
+        return remove_existing();
+    }
+
+    let meta = fs::symlink_metadata(upper)?;
+    if meta.is_dir() {
+        if !existing.as_ref().is_some_and(|m| m.is_dir()) {
+            remove_existing()?;
+            fs::create_dir(host)?;
+        }
+        fs::set_permissions(host, meta.permissions())
+    } else if meta.file_type().is_symlink() {
+        remove_existing()?;
+        std::os::unix::fs::symlink(fs::read_link(upper)?, host)
+    } else if meta.is_file() {
+        // Never write through a symlink or over a directory
+        if existing.as_ref().is_some_and(|m| !m.is_file()) {
+            remove_existing()?;
+        }
+        fs::copy(upper, host).map(|_| ())
+    } else {
+        Err(std::io::Error::other("special files are not committed"))
+    }
+}
+
+/// Collect all non-directory entries below `root/rel`, relative to `root`
+fn list_files(root: &Path, rel: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    let mut entries: Vec<_> = fs::read_dir(root.join(rel))?.collect::<std::io::Result<_>>()?;
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        let path = rel.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            list_files(root, &path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Read a regular file as text; `None` for binaries, symlinks and special files
+fn read_text(path: &Path) -> Option<String> {
+    let meta = fs::symlink_metadata(path).ok()?;
+    if !meta.is_file() {
+        return None;
+    }
+    let text = String::from_utf8(fs::read(path).ok()?).ok()?;
+    (!text.contains('\0')).then_some(text)
+}
+
+fn print_colored_diff(patch: &str) {
+    for line in patch.lines() {
+        if line.starts_with("+++") || line.starts_with("---") || line.starts_with('#') {
+            eprintln!("{}", line.bold());
+        } else if line.starts_with("@@") {
+            eprintln!("{}", line.cyan());
+        } else if line.starts_with('+') {
+            eprintln!("{}", line.green());
+        } else if line.starts_with('-') {
+            eprintln!("{}", line.red());
+        } else {
+            eprintln!("{}", line);
+        }
+    }
+}
+
+/// One line of a line-level edit script
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit<'a> {
+    Equal(&'a str),
+    Delete(&'a str),
+    Insert(&'a str),
+}
+
+/// Line diff via longest common subsequence, after trimming the common
+/// prefix and suffix. Oversized inputs fall back to replacing the whole
+/// changed region.
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Edit<'a>> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+
+    let mut edits: Vec<Edit<'a>> = old[..prefix].iter().map(|l| Edit::Equal(l)).collect();
+
+    if a.len().saturating_mul(b.len()) > MAX_DIFF_CELLS {
+        edits.extend(a.iter().map(|l| Edit::Delete(l)));
+        edits.extend(b.iter().map(|l| Edit::Insert(l)));
+    } else {
+        // lcs[i][j] = LCS length of a[i..] and b[j..]
+        let width = b.len() + 1;
+        let mut lcs = vec![0u32; (a.len() + 1) * width];
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                lcs[i * width + j] = if a[i] == b[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() && j < b.len() {
+            if a[i] == b[j] {
+                edits.push(Edit::Equal(a[i]));
+                i += 1;
+                j += 1;
+            } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+                edits.push(Edit::Delete(a[i]));
+                i += 1;
+            } else {
+                edits.push(Edit::Insert(b[j]));
+                j += 1;
+            }
+        }
+        edits.extend(a[i..].iter().map(|l| Edit::Delete(l)));
+        edits.extend(b[j..].iter().map(|l| Edit::Insert(l)));
+    }
+
+    edits.extend(old[old.len() - suffix..].iter().map(|l| Edit::Equal(l)));
+    edits
+}
+
+/// Render a unified diff between two texts (empty if they are equal)
+fn unified_diff(old_label: &str, new_label: &str, old: &str, new: &str) -> String {
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    let edits = diff_lines(&old_lines, &new_lines);
+
+    let changed: Vec<usize> = edits
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| !matches!(e, Edit::Equal(_)))
+        .map(|(i, _)| i)
+        .collect();
+    if changed.is_empty() {
+        return String::new();
+    }
+
+    // Line numbers consumed before each edit
+    let mut old_before = Vec::with_capacity(edits.len() + 1);
+    let mut new_before = Vec::with_capacity(edits.len() + 1);
+    let (mut o, mut n) = (0, 0);
+    for edit in &edits {
+        old_before.push(o);
+        new_before.push(n);
+        match edit {
+            Edit::Equal(_) => {
+                o += 1;
+                n += 1;
+            }
+            Edit::Delete(_) => o += 1,
+            Edit::Insert(_) => n += 1,
+        }
+    }
+    old_before.push(o);
+    new_before.push(n);
+
+    let mut out = format!("--- {}\n+++ {}\n", old_label, new_label);
+    let mut k = 0;
+    while k < changed.len() {
+        let mut last = k;
+        while last + 1 < changed.len() && changed[last + 1] - changed[last] <= 2 * DIFF_CONTEXT + 1
+        {
+            last += 1;
+        }
+        let start = changed[k].saturating_sub(DIFF_CONTEXT);
+        let end = (changed[last] + DIFF_CONTEXT + 1).min(edits.len());
+
+        let old_count = old_before[end] - old_before[start];
+        let new_count = new_before[end] - new_before[start];
+        // An empty range is numbered by the line before it
+        let old_start = old_before[start] + usize::from(old_count > 0);
+        let new_start = new_before[start] + usize::from(new_count > 0);
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start, old_count, new_start, new_count
+        ));
+
+        for edit in &edits[start..end] {
+            let (prefix, line) = match edit {
+                Edit::Equal(l) => (' ', l),
+                Edit::Delete(l) => ('-', l),
+                Edit::Insert(l) => ('+', l),
+            };
+            out.push(prefix);
+            out.push_str(line);
+            if !line.ends_with('\n') {
+                out.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+        k = last + 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn session(target: &Path, upper: &Path) -> EphemeralSession {
+        EphemeralSession {
+            staging: upper.parent().unwrap().to_path_buf(),
+            layers: vec![Layer {
+                target: target.to_path_buf(),
+                upper: upper.to_path_buf(),
+                work: upper.with_file_name("work"),
+            }],
+            host_root: File::open("/").unwrap(),
+            uncovered_files: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_action_parse() {
+        assert_eq!(Action::parse("c\n"), Some(Action::Commit));
+        assert_eq!(Action::parse("Discard"), Some(Action::Discard));
+        assert_eq!(Action::parse(" e "), Some(Action::Export));
+        assert_eq!(Action::parse("v"), Some(Action::View));
+        assert_eq!(Action::parse(""), None);
+        assert_eq!(Action::parse("yes"), None);
+    }
+
+    #[test]
+    fn test_unified_diff_single_change() {
+        let diff = unified_diff("a/f", "b/f", "1\n2\n3\n4\n5\n", "1\n2\nthree\n4\n5\n");
+        assert_eq!(
+            diff,
+            "--- a/f\n+++ b/f\n@@ -1,5 +1,5 @@\n 1\n 2\n-3\n+three\n 4\n 5\n"
+        );
+    }
+
+    #[test]
+    fn test_unified_diff_separate_hunks() {
+        let old: String = (1..=20).map(|i| format!("{}\n", i)).collect();
+        let new: String = (1..=20)
+            .map(|i| match i {
+                2 => "two\n".to_string(),
+                19 => "nineteen\n".to_string(),
+                i => format!("{}\n", i),
+            })
+            .collect();
+        let diff = unified_diff("a/f", "b/f", &old, &new);
+        assert!(diff.contains("@@ -1,5 +1,5 @@\n"));
+        assert!(diff.contains("@@ -16,5 +16,5 @@\n"));
+    }
+
+    #[test]
+    fn test_unified_diff_new_and_deleted_file() {
+        assert_eq!(
+            unified_diff("/dev/null", "b/f", "", "x\n"),
+            "--- /dev/null\n+++ b/f\n@@ -0,0 +1,1 @@\n+x\n"
+        );
+        assert_eq!(
+            unified_diff("a/f", "/dev/null", "x\ny\n", ""),
+            "--- a/f\n+++ /dev/null\n@@ -1,2 +0,0 @@\n-x\n-y\n"
+        );
+    }
+
+    #[test]
+    fn test_unified_diff_no_trailing_newline() {
+        let diff = unified_diff("a/f", "b/f", "x", "y");
+        assert!(
+            diff.ends_with("-x\n\\ No newline at end of file\n+y\n\\ No newline at end of file\n")
+        );
+    }
+
+    #[test]
+    fn test_unified_diff_equal() {
+        assert_eq!(unified_diff("a/f", "b/f", "same\n", "same\n"), "");
+    }
+
+    #[test]
+    fn test_scan_and_commit() {
+        let dir = tempdir().unwrap();
+        let target = dir.path().join("target");
+        let upper = dir.path().join("staging").join("upper");
+        fs::create_dir_all(&target).unwrap();
+        fs::create_dir_all(upper.join("sub")).unwrap();
+        fs::create_dir_all(target.join("sub")).unwrap();
+
+        fs::write(target.join("same.txt"), "same\n").unwrap();
+        fs::write(upper.join("same.txt"), "same\n").unwrap();
+        fs::write(target.join("edit.txt"), "old\n").unwrap();
+        fs::write(upper.join("edit.txt"), "new\n").unwrap();
+        fs::write(upper.join("sub").join("added.txt"), "added\n").unwrap();
+        fs::create_dir(upper.join("newdir")).unwrap();
+        fs::write(upper.join("newdir").join("f"), "f\n").unwrap();
+
+        let session = session(&target, &upper);
+        let changes = session.changes().unwrap();
+        let summary: Vec<(String, ChangeKind)> = changes
+            .iter()
+            .map(|c| (c.path.display().to_string(), c.kind))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("edit.txt".to_string(), ChangeKind::Modified),
+                ("newdir".to_string(), ChangeKind::Added),
+                ("newdir/f".to_string(), ChangeKind::Added),
+                ("sub/added.txt".to_string(), ChangeKind::Added),
+            ]
+        );
+
+        let (patch, skipped) = session.render_patch(&changes).unwrap();
+        assert!(skipped.is_empty());
+        assert!(patch.contains("--- a/edit.txt\n+++ b/edit.txt\n"));
+        assert!(patch.contains("--- /dev/null\n+++ b/newdir/f\n"));
+
+        session.commit(&changes).unwrap();
+        assert_eq!(
+            fs::read_to_string(target.join("edit.txt")).unwrap(),
+            "new\n"
+        );
+        assert_eq!(fs::read_to_string(target.join("newdir/f")).unwrap(), "f\n");
+        assert_eq!(
+            fs::read_to_string(target.join("sub/added.txt")).unwrap(),
+            "added\n"
+        );
+        assert!(session.changes().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_commit_deletion() {
+        let dir = tempdir().unwrap();
+        let target = dir.path().join("target");
+        let upper = dir.path().join("upper");
+        fs::create_dir_all(target.join("gone")).unwrap();
+        fs::write(target.join("gone").join("f"), "f\n").unwrap();
+
+        let change = Change {
+            layer: 0,
+            path: PathBuf::from("gone"),
+            kind: ChangeKind::Deleted,
+            is_dir: true,
+        };
+        let session = session(&target, &upper);
+        let (patch, _) = session.render_patch(std::slice::from_ref(&change)).unwrap();
+        assert!(patch.contains("--- a/gone/f\n+++ /dev/null\n"));
+
+        session.commit(&[change]).unwrap();
+        assert!(!target.join("gone").exists());
+    }
+
+    #[test]
+    fn test_commit_replaces_symlink_without_following() {
+        let dir = tempdir().unwrap();
+        let target = dir.path().join("target");
+        let upper = dir.path().join("upper");
+        let outside = dir.path().join("outside");
+        fs::create_dir_all(&target).unwrap();
+        fs::create_dir_all(&upper).unwrap();
+        fs::write(&outside, "untouched\n").unwrap();
+        std::os::unix::fs::symlink(&outside, target.join("link")).unwrap();
+        fs::write(upper.join("link"), "regular\n").unwrap();
+
+        let session = session(&target, &upper);
+        let changes = session.changes().unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, ChangeKind::Modified);
+        session.commit(&changes).unwrap();
+
+        assert_eq!(fs::read_to_string(&outside).unwrap(), "untouched\n");
+        assert!(fs::symlink_metadata(target.join("link")).unwrap().is_file());
+    }
+}
@@ -48,6 +48,12 @@ pub enum NonoError {
     #[error("Unsigned profile requires --trust-unsigned flag: {0}")]
     UnsignedProfile(String),
 
+    #[error("Failed to sign profile: {0}")]
+    ProfileSign(String),
+
+    #[error("Failed to update security lists: {0}")]
+    ListsUpdate(String),
+
     #[error("Failed to read profile {path}: {source}")]
     ProfileRead {
         path: std::path::PathBuf,
@@ -69,6 +75,18 @@ pub enum NonoError {
     #[error("Command '{command}' is blocked: {reason}")]
     BlockedCommand { command: String, reason: String },
 
+    #[error("Blocked by system policy: {0}")]
+    PolicyViolation(String),
+
+    #[error("Failed to open audit log {path}: {source}")]
+    AuditLog {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Ephemeral workspace error: {0}")]
+    Ephemeral(String),
+
     // Config errors
     #[error("Failed to read config {path}: {source}")]
     ConfigRead {
@@ -89,7 +107,6 @@ pub enum NonoError {
     #[error("Signature verification failed: {reason}")]
     SignatureInvalid { reason: String },
 
-    #[allow(dead_code)]
     #[error("Config version downgrade detected for '{config}': current={current}, attempted={attempted}")]
     VersionDowngrade {
         config: String,
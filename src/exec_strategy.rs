@@ -5,14 +5,19 @@
 //!
 //! # Async-Signal-Safety
 //!
-//! The Monitor strategy uses `fork()` to create a child process. After fork in a
-//! multi-threaded program, the child can only safely call async-signal-safe functions
-//! until `exec()`. This module carefully prepares all data in the parent (where
-//! allocation is safe) and uses only raw libc calls in the child.
-
-use crate::capability::CapabilitySet;
+//! The Monitor and Supervised strategies use `fork()` to create a child process.
+//! After fork in a multi-threaded program, the child can only safely call
+//! async-signal-safe functions until `exec()`. This module carefully prepares all
+//! data in the parent (where allocation is safe) and uses only raw libc calls in
+//! the child.
+
+use crate::audit::{AuditEvent, AuditLog};
+use crate::capability::{CapabilitySet, ResourceLimits};
 use crate::diagnostic::DiagnosticFormatter;
 use crate::error::{NonoError, Result};
+use crate::notify::NotifySupervisor;
+use crate::proxy::FilteringProxy;
+use crate::sandbox;
 use nix::libc;
 use nix::sys::signal::{self, Signal};
 use nix::sys::wait::{waitpid, WaitPidFlag, WaitStatus};
@@ -99,8 +104,13 @@ pub enum ExecStrategy {
     /// - Larger attack surface (requires hardening)
     /// - Diagnostic footer on non-zero exit
     /// - Undo support (parent can write snapshots)
-    /// - Future: IPC for capability expansion
-    #[allow(dead_code)]
+    /// - Works for interactive commands (child keeps the TTY)
+    /// - Interactive permission prompts for denied operations (Linux)
+    /// - Expiry of time-limited capabilities (Linux)
+    /// - Command blocklist enforced on every exec of the command's children (Linux)
+    /// - Ephemeral workspace review after the command exits (Linux)
+    /// - Isolated root holding only the granted paths (Linux)
+    /// - PID namespace of its own, with nono as its init (Linux)
     Supervised,
 }
 
@@ -122,6 +132,122 @@ pub struct ExecConfig<'a> {
     pub no_diagnostics: bool,
     /// Threading context for fork safety validation.
     pub threading: ThreadingContext,
+    /// Whether the child keeps the terminal instead of having its output
+    /// intercepted. Only honored by the Supervised strategy.
+    pub interactive: bool,
+    /// Filtering proxy to start in the supervisor after fork.
+    /// Only honored by the Supervised strategy.
+    pub proxy: Option<&'a FilteringProxy>,
+    /// Audit log to record injected diagnostics in
+    pub audit: Option<&'a AuditLog>,
+    /// Supervisor the child's intercepted syscalls are sent to, for
+    /// `--prompt`, time-limited capabilities and the command blocklist.
+    /// Only honored by the Supervised strategy (Linux).
+    pub notify: Option<&'a NotifySupervisor>,
+    /// Root the child moves into before the sandbox is applied, for
+    /// `--isolate`. Only honored by the Supervised strategy (Linux).
+    pub isolated_root: Option<&'a sandbox::IsolatedRoot>,
+}
+
+/// Pre-built execve arguments.
+///
+/// Everything the child needs for `execve()` is allocated here, in the parent,
+/// so the child never has to allocate after fork. The pointer arrays borrow
+/// from the owned CStrings, which must therefore outlive them.
+struct PreparedExec {
+    program: CString,
+    _argv: Vec<CString>,
+    _env: Vec<CString>,
+    argv_ptrs: Vec<*const libc::c_char>,
+    envp_ptrs: Vec<*const libc::c_char>,
+}
+
+impl PreparedExec {
+    fn new(config: &ExecConfig<'_>) -> Result<Self> {
+        let cmd_args = &config.command[1..];
+
+        // Use pre-resolved program path (resolved before sandbox was applied)
+        // This ensures the program can be found even if its directory is not
+        // in the sandbox's allowed paths.
+        let program_path = config.resolved_program;
+
+        // Convert program path to CString for execve
+        let program = CString::new(program_path.to_string_lossy().as_bytes())
+            .map_err(|_| NonoError::SandboxInit("Program path contains null byte".to_string()))?;
+
+        // Build argv: [program, args..., NULL]
+        let mut argv: Vec<CString> = Vec::with_capacity(1 + cmd_args.len());
+        argv.push(program.clone());
+        for arg in cmd_args {
+            argv.push(CString::new(arg.as_bytes()).map_err(|_| {
+                NonoError::SandboxInit(format!("Argument contains null byte: {}", arg))
+            })?);
+        }
+
+        // Build environment: inherit current env + add our vars
+        let mut env: Vec<CString> = Vec::new();
+
+        // Copy current environment, skipping vars we'll override
+        for (key, value) in std::env::vars_os() {
+            if let (Some(k), Some(v)) = (key.to_str(), value.to_str()) {
+                let should_skip =
+                    config.env_vars.iter().any(|(ek, _)| *ek == k) || k == "NONO_CAP_FILE";
+                if !should_skip {
+                    if let Ok(cstr) = CString::new(format!("{}={}", k, v)) {
+                        env.push(cstr);
+                    }
+                }
+            }
+        }
+
+        // Add NONO_CAP_FILE
+        if let Some(cap_file_str) = config.cap_file.to_str() {
+            if let Ok(cstr) = CString::new(format!("NONO_CAP_FILE={}", cap_file_str)) {
+                env.push(cstr);
+            }
+        }
+
+        // Add user-specified environment variables (secrets, etc.)
+        for (key, value) in &config.env_vars {
+            if let Ok(cstr) = CString::new(format!("{}={}", key, value)) {
+                env.push(cstr);
+            }
+        }
+
+        // Create null-terminated pointer arrays for execve
+        let argv_ptrs: Vec<*const libc::c_char> = argv
+            .iter()
+            .map(|s| s.as_ptr())
+            .chain(std::iter::once(std::ptr::null()))
+            .collect();
+
+        let envp_ptrs: Vec<*const libc::c_char> = env
+            .iter()
+            .map(|s| s.as_ptr())
+            .chain(std::iter::once(std::ptr::null()))
+            .collect();
+
+        Ok(Self {
+            program,
+            _argv: argv,
+            _env: env,
+            argv_ptrs,
+            envp_ptrs,
+        })
+    }
+
+    /// Replace the current process image. Only returns on failure.
+    ///
+    /// Async-signal-safe: uses only pre-built data.
+    fn exec(&self) {
+        unsafe {
+            libc::execve(
+                self.program.as_ptr(),
+                self.argv_ptrs.as_ptr(),
+                self.envp_ptrs.as_ptr(),
+            );
+        }
+    }
 }
 
 /// Execute a command using the Direct strategy (exec, nono disappears).
@@ -144,6 +270,10 @@ pub fn execute_direct(config: &ExecConfig<'_>) -> Result<()> {
         cmd.env(key, value);
     }
 
+    // nono becomes the command, so its own limits are the command's
+    set_resource_limits(&config.caps.limits)
+        .map_err(|e| NonoError::SandboxInit(format!("Failed to set resource limits: {}", e)))?;
+
     let err = cmd.exec();
 
     // exec() only returns if there's an error
@@ -207,115 +337,10 @@ pub fn execute_monitor(config: &ExecConfig<'_>) -> Result<i32> {
 
     info!("Executing (monitor): {} {:?}", program, cmd_args);
 
-    // Use pre-resolved program path (resolved before sandbox was applied)
-    // This ensures the program can be found even if its directory is not
-    // in the sandbox's allowed paths.
-    let program_path = config.resolved_program;
-
-    // Convert program path to CString for execve
-    let program_c = CString::new(program_path.to_string_lossy().as_bytes())
-        .map_err(|_| NonoError::SandboxInit("Program path contains null byte".to_string()))?;
-
-    // Build argv: [program, args..., NULL]
-    let mut argv_c: Vec<CString> = Vec::with_capacity(1 + cmd_args.len());
-    argv_c.push(program_c.clone());
-    for arg in cmd_args {
-        argv_c.push(CString::new(arg.as_bytes()).map_err(|_| {
-            NonoError::SandboxInit(format!("Argument contains null byte: {}", arg))
-        })?);
-    }
-
-    // Build environment: inherit current env + add our vars
-    let mut env_c: Vec<CString> = Vec::new();
-
-    // Copy current environment, skipping vars we'll override
-    for (key, value) in std::env::vars_os() {
-        if let (Some(k), Some(v)) = (key.to_str(), value.to_str()) {
-            let should_skip =
-                config.env_vars.iter().any(|(ek, _)| *ek == k) || k == "NONO_CAP_FILE";
-            if !should_skip {
-                if let Ok(cstr) = CString::new(format!("{}={}", k, v)) {
-                    env_c.push(cstr);
-                }
-            }
-        }
-    }
-
-    // Add NONO_CAP_FILE
-    if let Some(cap_file_str) = config.cap_file.to_str() {
-        if let Ok(cstr) = CString::new(format!("NONO_CAP_FILE={}", cap_file_str)) {
-            env_c.push(cstr);
-        }
-    }
-
-    // Add user-specified environment variables (secrets, etc.)
-    for (key, value) in &config.env_vars {
-        if let Ok(cstr) = CString::new(format!("{}={}", key, value)) {
-            env_c.push(cstr);
-        }
-    }
-
-    // Create null-terminated pointer arrays for execve
-    let argv_ptrs: Vec<*const libc::c_char> = argv_c
-        .iter()
-        .map(|s| s.as_ptr())
-        .chain(std::iter::once(std::ptr::null()))
-        .collect();
-
-    let envp_ptrs: Vec<*const libc::c_char> = env_c
-        .iter()
-        .map(|s| s.as_ptr())
-        .chain(std::iter::once(std::ptr::null()))
-        .collect();
-
-    // Platform-specific ptrace hardening
-    #[cfg(target_os = "linux")]
-    {
-        use nix::sys::prctl;
-        if let Err(e) = prctl::set_dumpable(false) {
-            warn!("Failed to set PR_SET_DUMPABLE(0): {}", e);
-        }
-    }
-
-    #[cfg(target_os = "macos")]
-    {
-        const PT_DENY_ATTACH: libc::c_int = 31;
-        let result =
-            unsafe { libc::ptrace(PT_DENY_ATTACH, 0, std::ptr::null_mut::<libc::c_char>(), 0) };
-        if result != 0 {
-            warn!(
-                "Failed to set PT_DENY_ATTACH: {} (errno: {})",
-                result,
-                std::io::Error::last_os_error()
-            );
-        }
-    }
+    let exec = PreparedExec::new(config)?;
 
-    // Validate threading context before fork
-    let thread_count = get_thread_count();
-    match (config.threading, thread_count) {
-        (_, 1) => {}
-        (ThreadingContext::KeyringExpected, n) if n <= MAX_KEYRING_THREADS => {
-            debug!(
-                "Proceeding with fork despite {} threads (keyring backend threads expected)",
-                n
-            );
-        }
-        (ThreadingContext::Strict, n) => {
-            return Err(NonoError::SandboxInit(format!(
-                "Cannot fork: process has {} threads (expected 1). \
-                 This is a bug - fork() requires single-threaded execution.",
-                n
-            )));
-        }
-        (ThreadingContext::KeyringExpected, n) => {
-            return Err(NonoError::SandboxInit(format!(
-                "Cannot fork: process has {} threads (max {} with keyring). \
-                 Unexpected threading detected.",
-                n, MAX_KEYRING_THREADS
-            )));
-        }
-    }
+    harden_parent();
+    check_fork_threading(config.threading)?;
 
     // Create pipes for stdout and stderr interception
     let (stdout_read, stdout_write): (OwnedFd, OwnedFd) = nix::unistd::pipe()
@@ -372,11 +397,13 @@ pub fn execute_monitor(config: &ExecConfig<'_>) -> Result<i32> {
                 }
             }
 
-            // Execute using pre-prepared CStrings (no allocation)
-            unsafe {
-                libc::execve(program_c.as_ptr(), argv_ptrs.as_ptr(), envp_ptrs.as_ptr());
+            if set_resource_limits(&config.caps.limits).is_err() {
+                child_abort(b"nono: failed to set resource limits\n");
             }
 
+            // Execute using pre-prepared CStrings (no allocation)
+            exec.exec();
+
             // execve only returns on error - exit without cleanup
             unsafe { libc::_exit(127) }
         }
@@ -407,6 +434,393 @@ pub fn execute_monitor(config: &ExecConfig<'_>) -> Result<i32> {
     }
 }
 
+/// Execute a command using the Supervised strategy (fork first, sandbox only child).
+///
+/// The sandbox policy is compiled in the parent but enforced only in the child,
+/// immediately after fork. The parent stays outside the sandbox for the whole
+/// lifetime of the command, so it can wait for the child, report diagnostics
+/// and clean up afterwards with full access to the system.
+///
+/// # Security Properties
+///
+/// - Only the child is sandboxed; the parent keeps its original privileges
+/// - The parent is hardened against the child the same way as in Monitor mode:
+///   - Linux: PR_SET_DUMPABLE(0), and Landlock denies ptrace of processes
+///     outside the child's domain
+///   - macOS: PT_DENY_ATTACH
+/// - The child closes every inherited FD beyond stdio before exec, so it holds
+///   no handle on anything the parent opened
+/// - If the sandbox cannot be enforced in the child, it exits with 126 without
+///   running the command
+///
+/// # Output Handling
+///
+/// When `config.interactive` is false, output is intercepted exactly as in
+/// Monitor mode. When true, the child inherits the terminal and the parent only
+/// waits; terminal-generated signals (SIGINT, SIGQUIT) already reach the child's
+/// process group, so the parent ignores them instead of forwarding.
+///
+/// # Concurrency Limitations
+///
+/// Same as [`execute_monitor`]: not reentrant, requires single-threaded execution.
+pub fn execute_supervised(config: &ExecConfig<'_>) -> Result<i32> {
+    let program = &config.command[0];
+    let cmd_args = &config.command[1..];
+
+    info!("Executing (supervised): {} {:?}", program, cmd_args);
+
+    let exec = PreparedExec::new(config)?;
+
+    // Compile the sandbox policy while allocation is still safe
+    let pending = match config.isolated_root {
+        Some(root) => sandbox::prepare_isolated(config.caps, root)?,
+        None => sandbox::prepare(config.caps)?,
+    };
+
+    harden_parent();
+    check_fork_threading(config.threading)?;
+
+    // Create pipes for output interception unless the child keeps the TTY
+    let pipes: Option<[OwnedFd; 4]> = if config.interactive {
+        None
+    } else {
+        let (stdout_read, stdout_write) = nix::unistd::pipe()
+            .map_err(|e| NonoError::SandboxInit(format!("pipe() for stdout failed: {}", e)))?;
+        let (stderr_read, stderr_write) = nix::unistd::pipe()
+            .map_err(|e| NonoError::SandboxInit(format!("pipe() for stderr failed: {}", e)))?;
+        Some([stdout_read, stdout_write, stderr_read, stderr_write])
+    };
+
+    // Extract raw FDs before fork (-1 when not intercepting)
+    let raw = |i: usize| pipes.as_ref().map_or(-1, |p| p[i].as_raw_fd());
+    let (stdout_read_fd, stdout_write_fd) = (raw(0), raw(1));
+    let (stderr_read_fd, stderr_write_fd) = (raw(2), raw(3));
+
+    // Seccomp notify filter, and the socket its listener FD is handed back
+    // over (the listener only exists once the child installs it)
+    #[cfg(target_os = "linux")]
+    let notify = match config.notify {
+        Some(supervisor) => {
+            let (parent_sock, child_sock) = std::os::unix::net::UnixStream::pair()
+                .map_err(|e| NonoError::SandboxInit(format!("socketpair() failed: {}", e)))?;
+            Some((supervisor.filter(), parent_sock, child_sock))
+        }
+        None => None,
+    };
+    #[cfg(target_os = "linux")]
+    let notify_sock_fd = notify.as_ref().map_or(-1, |(_, _, c)| c.as_raw_fd());
+    #[cfg(not(target_os = "linux"))]
+    let notify_sock_fd = -1;
+
+    // Wrap in ManuallyDrop to prevent Drop from running in child
+    let pipes = ManuallyDrop::new(pipes);
+
+    // Compute max FD in parent (get_max_fd may allocate on Linux)
+    let max_fd = get_max_fd();
+
+    // SAFETY: fork() is safe here because we validated threading context
+    // and child will only use async-signal-safe functions until exec()
+    let fork_result = unsafe { fork_child(config.caps.pid_namespace) };
+
+    match fork_result {
+        Ok(ForkResult::Child) => {
+            // CHILD: No allocations allowed from here until exec()
+            if !config.interactive {
+                unsafe {
+                    libc::close(stdout_read_fd);
+                    libc::close(stderr_read_fd);
+                }
+            }
+
+            // Switch to the isolated root while mounting is still allowed
+            if let Some(root) = config.isolated_root {
+                if !root.enter_in_child() {
+                    child_abort(b"nono: failed to enter isolated root\n");
+                }
+            }
+
+            // A PID namespace needs a procfs of its own, which the sandbox
+            // must be told about
+            #[cfg(target_os = "linux")]
+            if config.caps.pid_namespace
+                && !(crate::sandbox::namespace::mount_proc_in_child(config.isolated_root.is_none())
+                    && pending.allow_proc_in_child())
+            {
+                child_abort(b"nono: failed to mount a private /proc\n");
+            }
+
+            // Mounting is done: give up the capabilities the private user
+            // namespace granted, so root cannot see through the masks
+            #[cfg(target_os = "linux")]
+            if !crate::sandbox::namespace::drop_capabilities_in_child() {
+                child_abort(b"nono: failed to drop capabilities\n");
+            }
+
+            // Enforce the sandbox before anything else runs in the child
+            if !pending.apply_in_child() {
+                child_abort(b"nono: failed to apply sandbox in child process\n");
+            }
+
+            // Close inherited FDs from keyring/other sources
+            close_inherited_fds(max_fd, &[stdout_write_fd, stderr_write_fd, notify_sock_fd]);
+
+            if !config.interactive {
+                unsafe {
+                    if stdout_write_fd != libc::STDOUT_FILENO {
+                        libc::dup2(stdout_write_fd, libc::STDOUT_FILENO);
+                        libc::close(stdout_write_fd);
+                    }
+                    if stderr_write_fd != libc::STDERR_FILENO {
+                        libc::dup2(stderr_write_fd, libc::STDERR_FILENO);
+                        libc::close(stderr_write_fd);
+                    }
+                }
+            }
+
+            // As PID 1, stay behind to reap the namespace; the command runs
+            // in a child under the same sandbox
+            #[cfg(target_os = "linux")]
+            if config.caps.pid_namespace
+                && !crate::sandbox::namespace::fork_reaper_in_child(&[notify_sock_fd])
+            {
+                child_abort(b"nono: failed to start the command in its PID namespace\n");
+            }
+
+            // Install the notify filter last, so only the command's own
+            // syscalls are sent to the supervisor
+            #[cfg(target_os = "linux")]
+            if let Some((filter, _, _)) = &notify {
+                let listener = filter.install_in_child();
+                if listener < 0
+                    || !crate::sandbox::seccomp::send_fd_in_child(notify_sock_fd, listener)
+                {
+                    child_abort(b"nono: failed to install seccomp notify filter\n");
+                }
+                unsafe {
+                    libc::close(listener);
+                    libc::close(notify_sock_fd);
+                }
+            }
+
+            // Limit resources only now, so they apply to the command but
+            // not to the PID namespace's reaper
+            if set_resource_limits(&config.caps.limits).is_err() {
+                child_abort(b"nono: failed to set resource limits\n");
+            }
+
+            exec.exec();
+
+            // execve only returns on error - exit without cleanup
+            unsafe { libc::_exit(127) }
+        }
+        Ok(ForkResult::Parent { child }) => {
+            // PARENT: release the ruleset handle, the child owns enforcement now
+            drop(pending);
+
+            // Take over the child's seccomp listener and start answering it
+            #[cfg(target_os = "linux")]
+            if let (Some(supervisor), Some((_, parent_sock, child_sock))) = (config.notify, notify)
+            {
+                use std::os::fd::AsFd;
+                drop(child_sock);
+                let started = match crate::sandbox::seccomp::recv_fd(parent_sock.as_fd()) {
+                    Ok(Some(listener)) => supervisor.start(listener),
+                    // The child failed before installing the filter and reports why itself
+                    Ok(None) => Ok(()),
+                    Err(e) => Err(NonoError::SandboxInit(format!(
+                        "Failed to receive seccomp listener: {}",
+                        e
+                    ))),
+                };
+                if let Err(e) = started {
+                    let _ = signal::kill(child, Signal::SIGKILL);
+                    let _ = wait_for_child(child);
+                    return Err(e);
+                }
+            }
+
+            // Start supervisor services now that it is safe to spawn threads
+            if let Some(proxy) = config.proxy {
+                if let Err(e) = proxy.start() {
+                    let _ = signal::kill(child, Signal::SIGKILL);
+                    let _ = wait_for_child(child);
+                    return Err(e);
+                }
+            }
+
+            match ManuallyDrop::into_inner(pipes) {
+                Some([stdout_read, stdout_write, stderr_read, stderr_write]) => {
+                    drop(stdout_write);
+                    drop(stderr_write);
+                    let stdout_file = std::fs::File::from(stdout_read);
+                    let stderr_file = std::fs::File::from(stderr_read);
+                    execute_parent_monitor(child, config, stdout_file, stderr_file)
+                }
+                None => execute_parent_interactive(child),
+            }
+        }
+        Err(e) => {
+            drop(ManuallyDrop::into_inner(pipes));
+            Err(NonoError::SandboxInit(format!("fork() failed: {}", e)))
+        }
+    }
+}
+
+/// Parent process in Supervised mode when the child owns the terminal.
+fn execute_parent_interactive(child: Pid) -> Result<i32> {
+    debug!("Supervisor waiting for interactive child pid {}", child);
+
+    setup_signal_forwarding(child);
+
+    // The terminal delivers SIGINT/SIGQUIT to the whole foreground process
+    // group, so forwarding them would deliver each one twice.
+    unsafe {
+        for sig in &[Signal::SIGINT, Signal::SIGQUIT] {
+            if let Err(e) = signal::signal(*sig, signal::SigHandler::SigIgn) {
+                debug!("Failed to ignore {:?}: {}", sig, e);
+            }
+        }
+    }
+
+    let status = wait_for_child(child)?;
+    Ok(exit_code_from_status(status))
+}
+
+/// Apply platform-specific ptrace hardening to the parent before fork.
+fn harden_parent() {
+    #[cfg(target_os = "linux")]
+    {
+        use nix::sys::prctl;
+        if let Err(e) = prctl::set_dumpable(false) {
+            warn!("Failed to set PR_SET_DUMPABLE(0): {}", e);
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        const PT_DENY_ATTACH: libc::c_int = 31;
+        let result =
+            unsafe { libc::ptrace(PT_DENY_ATTACH, 0, std::ptr::null_mut::<libc::c_char>(), 0) };
+        if result != 0 {
+            warn!(
+                "Failed to set PT_DENY_ATTACH: {} (errno: {})",
+                result,
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+}
+
+/// Fork the command's process, as the init of a new PID namespace when
+/// `pid_namespace` is set.
+///
+/// # Safety
+///
+/// Same as [`fork`]: the child may only use async-signal-safe functions.
+unsafe fn fork_child(pid_namespace: bool) -> nix::Result<ForkResult> {
+    #[cfg(target_os = "linux")]
+    if pid_namespace {
+        return crate::sandbox::namespace::fork_into_pid_namespace();
+    }
+
+    let _ = pid_namespace;
+    fork()
+}
+
+/// Lower the resource limits of the calling process to `limits` (see
+/// `--max-memory` and the other `--max-*` flags).
+///
+/// Limits above the current hard limit are capped at it. The CPU time hard
+/// limit is one second above the soft one, so the command is sent SIGXCPU
+/// before the kernel resorts to SIGKILL.
+///
+/// Async-signal-safe: only calls getrlimit() and setrlimit().
+fn set_resource_limits(limits: &ResourceLimits) -> nix::Result<()> {
+    use nix::sys::resource::{getrlimit, setrlimit, Resource};
+
+    let entries = [
+        (Resource::RLIMIT_AS, limits.memory, 0),
+        (Resource::RLIMIT_NPROC, limits.processes, 0),
+        (
+            Resource::RLIMIT_CPU,
+            limits.cpu_time.map(|t| t.as_secs()),
+            1,
+        ),
+        (Resource::RLIMIT_FSIZE, limits.file_size, 0),
+        (Resource::RLIMIT_NOFILE, limits.open_files, 0),
+    ];
+    for (resource, limit, grace) in entries {
+        let Some(limit) = limit else {
+            continue;
+        };
+        let (_, max) = getrlimit(resource)?;
+        setrlimit(
+            resource,
+            limit.min(max),
+            limit.saturating_add(grace).min(max),
+        )?;
+    }
+    Ok(())
+}
+
+/// Report why a forked child cannot go on and exit with status 126.
+///
+/// Async-signal-safe: only calls write() and _exit().
+fn child_abort(msg: &[u8]) -> ! {
+    unsafe {
+        libc::write(
+            libc::STDERR_FILENO,
+            msg.as_ptr() as *const libc::c_void,
+            msg.len(),
+        );
+        libc::_exit(126)
+    }
+}
+
+/// Validate that the current thread count allows a safe fork.
+fn check_fork_threading(threading: ThreadingContext) -> Result<()> {
+    let thread_count = get_thread_count();
+    match (threading, thread_count) {
+        (_, 1) => Ok(()),
+        (ThreadingContext::KeyringExpected, n) if n <= MAX_KEYRING_THREADS => {
+            debug!(
+                "Proceeding with fork despite {} threads (keyring backend threads expected)",
+                n
+            );
+            Ok(())
+        }
+        (ThreadingContext::Strict, n) => Err(NonoError::SandboxInit(format!(
+            "Cannot fork: process has {} threads (expected 1). \
+             This is a bug - fork() requires single-threaded execution.",
+            n
+        ))),
+        (ThreadingContext::KeyringExpected, n) => Err(NonoError::SandboxInit(format!(
+            "Cannot fork: process has {} threads (max {} with keyring). \
+             Unexpected threading detected.",
+            n, MAX_KEYRING_THREADS
+        ))),
+    }
+}
+
+/// Map a wait status to a shell-style exit code.
+fn exit_code_from_status(status: WaitStatus) -> i32 {
+    match status {
+        WaitStatus::Exited(_, code) => {
+            debug!("Child exited with code {}", code);
+            code
+        }
+        WaitStatus::Signaled(_, signal, _) => {
+            debug!("Child killed by signal {:?}", signal);
+            // Exit code convention: 128 + signal number
+            128 + signal as i32
+        }
+        other => {
+            warn!("Unexpected wait status: {:?}", other);
+            1
+        }
+    }
+}
+
 /// Close inherited file descriptors, keeping stdin/stdout/stderr and specified FDs.
 ///
 /// `max_fd` must be computed in the parent before fork (get_max_fd may allocate).
@@ -476,6 +890,8 @@ fn execute_parent_monitor(
     let no_diagnostics = config.no_diagnostics;
     let diag_flag_stdout = Arc::clone(&diagnostic_injected);
     let diag_flag_stderr = Arc::clone(&diagnostic_injected);
+    let audit_stdout = config.audit.cloned();
+    let audit_stderr = config.audit.cloned();
 
     let stdout_handle = std::thread::spawn(move || {
         process_output(
@@ -484,6 +900,7 @@ fn execute_parent_monitor(
             no_diagnostics,
             false,
             diag_flag_stdout,
+            audit_stdout.as_ref(),
         );
     });
 
@@ -494,6 +911,7 @@ fn execute_parent_monitor(
             no_diagnostics,
             true,
             diag_flag_stderr,
+            audit_stderr.as_ref(),
         );
     });
 
@@ -509,21 +927,7 @@ fn execute_parent_monitor(
     }
 
     // Determine exit code
-    let exit_code = match status {
-        WaitStatus::Exited(_, code) => {
-            debug!("Child exited with code {}", code);
-            code
-        }
-        WaitStatus::Signaled(_, signal, _) => {
-            debug!("Child killed by signal {:?}", signal);
-            // Exit code convention: 128 + signal number
-            128 + signal as i32
-        }
-        other => {
-            warn!("Unexpected wait status: {:?}", other);
-            1
-        }
-    };
+    let exit_code = exit_code_from_status(status);
 
     // Print diagnostic footer on non-zero exit if not already injected
     if exit_code != 0
@@ -535,6 +939,13 @@ fn execute_parent_monitor(
         let formatter = DiagnosticFormatter::new(config.caps);
         let footer = formatter.format_footer(exit_code);
         eprintln!("\n{}", footer);
+        if let Some(audit) = config.audit {
+            audit.record(&AuditEvent::Diagnostic {
+                stream: "exit",
+                trigger: &format!("exit code {}", exit_code),
+                message: &footer,
+            });
+        }
     }
 
     Ok(exit_code)
@@ -551,6 +962,7 @@ fn process_output(
     no_diagnostics: bool,
     is_stderr: bool,
     diagnostic_injected: Arc<AtomicBool>,
+    audit: Option<&AuditLog>,
 ) {
     let reader = BufReader::new(pipe);
     let mut stdout = std::io::stdout();
@@ -604,6 +1016,14 @@ fn process_output(
                 }
                 let _ = stdout.flush();
 
+                if let Some(audit) = audit {
+                    audit.record(&AuditEvent::Diagnostic {
+                        stream: stream_name,
+                        trigger: &line,
+                        message: &footer,
+                    });
+                }
+
                 // Reset the flag after debounce period in a background thread
                 let flag = Arc::clone(&diagnostic_injected);
                 std::thread::spawn(move || {
@@ -767,4 +1187,15 @@ mod tests {
         assert_ne!(ExecStrategy::Monitor, ExecStrategy::Supervised);
         assert_ne!(ExecStrategy::Direct, ExecStrategy::Supervised);
     }
+
+    #[test]
+    fn test_exit_code_from_status() {
+        let pid = Pid::from_raw(1);
+        assert_eq!(exit_code_from_status(WaitStatus::Exited(pid, 0)), 0);
+        assert_eq!(exit_code_from_status(WaitStatus::Exited(pid, 42)), 42);
+        assert_eq!(
+            exit_code_from_status(WaitStatus::Signaled(pid, Signal::SIGKILL, false)),
+            128 + 9
+        );
+    }
 }
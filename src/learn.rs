@@ -442,9 +442,9 @@ fn process_accesses(
     // Get profile paths if available
     let profile_paths: HashSet<String> = if let Some(prof) = profile {
         let mut paths = HashSet::new();
-        paths.extend(prof.filesystem.allow.iter().cloned());
-        paths.extend(prof.filesystem.read.iter().cloned());
-        paths.extend(prof.filesystem.write.iter().cloned());
+        paths.extend(prof.filesystem.allow.iter().map(|e| e.path.clone()));
+        paths.extend(prof.filesystem.read.iter().map(|e| e.path.clone()));
+        paths.extend(prof.filesystem.write.iter().map(|e| e.path.clone()));
         paths
     } else {
         HashSet::new()
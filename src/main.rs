@@ -1,14 +1,19 @@
+mod audit;
 mod capability;
 mod cli;
 mod config;
 mod diagnostic;
+mod ephemeral;
 mod error;
 mod exec_strategy;
 mod hooks;
 mod keystore;
 mod learn;
+mod notify;
 mod output;
 mod profile;
+mod prompt;
+mod proxy;
 mod query;
 mod sandbox;
 mod sandbox_state;
@@ -16,7 +21,10 @@ mod setup;
 
 use capability::{CapabilitySet, FsAccess, FsCapability};
 use clap::Parser;
-use cli::{Cli, Commands, LearnArgs, SandboxArgs, SetupArgs, ShellArgs, WhyArgs, WhyOp};
+use cli::{
+    Cli, Commands, LearnArgs, ListsArgs, ListsCommands, ProfileArgs, ProfileCommands, SandboxArgs,
+    SetupArgs, ShellArgs, WhyArgs, WhyOp,
+};
 use colored::Colorize;
 use error::{NonoError, Result};
 use profile::WorkdirAccess;
@@ -66,6 +74,54 @@ fn run() -> Result<()> {
             // Setup prints its own banner
             run_setup(args)
         }
+        Commands::Profile(args) => run_profile(args, cli.silent),
+        Commands::Lists(args) => run_lists(args, cli.silent),
+    }
+}
+
+/// Show or update the security lists
+fn run_lists(args: ListsArgs, silent: bool) -> Result<()> {
+    match args.command {
+        ListsCommands::Show(show) => {
+            config::init_effective_config(false)?;
+            output::print_security_lists(config::effective_config(), show.category.as_deref());
+            Ok(())
+        }
+        ListsCommands::Update(update) => {
+            let installed = config::update::install_update(&update.from)?;
+            if !silent {
+                eprintln!(
+                    "{} security lists v{} (signed by {}) to {}",
+                    "Installed".green(),
+                    installed.lists.meta.version,
+                    installed.signed_by,
+                    config::update::installed_update_path()?.display()
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Manage user profiles
+fn run_profile(args: ProfileArgs, silent: bool) -> Result<()> {
+    match args.command {
+        ProfileCommands::Sign(sign) => {
+            let path = profile::resolve_user_profile(&sign.profile)?;
+            let signature = profile::signing::sign_profile(&path, sign.key.as_deref(), sign.embed)?;
+            if !silent {
+                eprintln!(
+                    "{} {} (signature in {})",
+                    "Signed".green(),
+                    path.display(),
+                    signature.display()
+                );
+                eprintln!(
+                    "Add the public key under [trusted_keys] in the user config to trust it."
+                );
+            }
+            Ok(())
+        }
     }
 }
 
@@ -124,11 +180,13 @@ fn run_learn(args: LearnArgs, silent: bool) -> Result<()> {
 
 /// Check why a path or network operation would be allowed or denied
 fn run_why(args: WhyArgs) -> Result<()> {
-    use query::{print_result, query_network, query_path, QueryResult};
+    use query::{print_result, query_network, query_path, sandbox_status, QueryResult};
     use sandbox_state::load_sandbox_state;
 
+    config::init_effective_config(false)?;
+
     // Build capability set from args or load from sandbox state
-    let caps = if args.self_query {
+    let mut caps = if args.self_query {
         // Inside sandbox - load from state file
         match load_sandbox_state() {
             Some(state) => state.to_caps(),
@@ -164,17 +222,11 @@ fn run_why(args: WhyArgs) -> Result<()> {
             allow_file: args.allow_file.clone(),
             read_file: args.read_file.clone(),
             write_file: args.write_file.clone(),
+            deny: args.deny.clone(),
             net_block: args.net_block,
-            allow_command: vec![],
-            block_command: vec![],
-            secrets: None,
-            profile: None,
-            allow_cwd: false,
             workdir: args.workdir.clone(),
             trust_unsigned: args.trust_unsigned,
-            config: None,
-            verbose: 0,
-            dry_run: false,
+            ..Default::default()
         };
 
         CapabilitySet::from_profile(&prof, &workdir, &sandbox_args)?
@@ -187,21 +239,17 @@ fn run_why(args: WhyArgs) -> Result<()> {
             allow_file: args.allow_file.clone(),
             read_file: args.read_file.clone(),
             write_file: args.write_file.clone(),
+            deny: args.deny.clone(),
             net_block: args.net_block,
-            allow_command: vec![],
-            block_command: vec![],
-            secrets: None,
-            profile: None,
-            allow_cwd: false,
             workdir: args.workdir.clone(),
-            trust_unsigned: false,
-            config: None,
-            verbose: 0,
-            dry_run: false,
+            ..Default::default()
         };
 
         CapabilitySet::from_args(&sandbox_args)?
     };
+    if !args.self_query {
+        config::enforce_system_policy(&mut caps)?;
+    }
 
     // Execute the query
     let result = if let Some(ref path) = args.path {
@@ -209,11 +257,14 @@ fn run_why(args: WhyArgs) -> Result<()> {
             Some(WhyOp::Read) => FsAccess::Read,
             Some(WhyOp::Write) => FsAccess::Write,
             Some(WhyOp::ReadWrite) => FsAccess::ReadWrite,
+            Some(WhyOp::Exec) => FsAccess::Execute,
             None => FsAccess::Read, // Default to read
         };
         query_path(path, op, &caps)?
     } else if let Some(ref host) = args.host {
         query_network(host, args.port, &caps)
+    } else if args.self_query {
+        sandbox_status(&caps)
     } else {
         return Err(NonoError::ConfigParse(
             "--path or --host is required".to_string(),
@@ -257,7 +308,7 @@ fn run_sandbox(args: SandboxArgs, command: Vec<String>, silent: bool) -> Result<
                 prepared.secrets.len()
             );
         }
-        output::print_dry_run(&program, &cmd_args, silent);
+        output::print_dry_run(&program, &cmd_args, &prepared.caps, silent);
         return Ok(());
     }
 
@@ -268,6 +319,7 @@ fn run_sandbox(args: SandboxArgs, command: Vec<String>, silent: bool) -> Result<
         &prepared.caps,
         prepared.secrets,
         prepared.interactive,
+        &args,
         silent,
     )
 }
@@ -293,7 +345,7 @@ fn run_shell(args: ShellArgs, silent: bool) -> Result<()> {
                 prepared.secrets.len()
             );
         }
-        output::print_dry_run(shell_path.as_os_str(), &[], silent);
+        output::print_dry_run(shell_path.as_os_str(), &[], &prepared.caps, silent);
         return Ok(());
     }
 
@@ -314,6 +366,7 @@ fn run_shell(args: ShellArgs, silent: bool) -> Result<()> {
         &prepared.caps,
         prepared.secrets,
         true, // Force interactive for shell
+        &args.sandbox,
         silent,
     )
 }
@@ -324,26 +377,66 @@ fn execute_sandboxed(
     caps: &CapabilitySet,
     loaded_secrets: Vec<keystore::LoadedSecret>,
     interactive: bool,
+    args: &SandboxArgs,
     silent: bool,
 ) -> Result<()> {
+    // Convert OsString command to String for exec_strategy
+    // (lossy conversion is acceptable - non-UTF8 commands are rare)
+    let command: Vec<String> = std::iter::once(program.to_string_lossy().into_owned())
+        .chain(cmd_args.iter().map(|s| s.to_string_lossy().into_owned()))
+        .collect();
+
+    // Time-limited capabilities start counting down now
+    let mut caps = caps.clone();
+    caps.arm_expiry(std::time::SystemTime::now());
+    let caps = &caps;
+
+    // The command must not be able to rewrite or delete its own record
+    if let Some(path) = args.audit_log.as_deref() {
+        let resolved = resolve_new_file(path);
+        if sandbox::is_writable(caps, &resolved) {
+            return Err(NonoError::AuditLog {
+                path: path.to_path_buf(),
+                source: std::io::Error::other(
+                    "the sandboxed command could write to it; \
+                     choose a path outside the granted directories",
+                ),
+            });
+        }
+    }
+
+    // Open the audit log first so blocked commands are recorded too
+    let audit = args
+        .audit_log
+        .as_deref()
+        .map(audit::AuditLog::open)
+        .transpose()?;
+    if let Some(ref audit) = audit {
+        audit.record(&audit::AuditEvent::SessionStart {
+            command: &command,
+            profile: args.profile.as_deref(),
+            security_lists_version: config::get_security_lists_version(),
+            capabilities: sandbox_state::SandboxState::from_caps(caps),
+        });
+    }
+
     // Check if command is blocked using config module
     if let Some(blocked) =
         config::check_blocked_command(&program, &caps.allowed_commands, &caps.blocked_commands)
     {
+        let reason = config::blocked_command_reason(&blocked, &caps.blocked_commands);
+        if let Some(ref audit) = audit {
+            audit.record(&audit::AuditEvent::CommandBlocked {
+                command: &blocked,
+                reason,
+            });
+        }
         return Err(NonoError::BlockedCommand {
             command: blocked,
-            reason: "This command is blocked by default due to destructive potential. \
-                     Use --allow-command to override if you understand the risks."
-                .to_string(),
+            reason: reason.to_string(),
         });
     }
 
-    // Convert OsString command to String for exec_strategy
-    // (lossy conversion is acceptable - non-UTF8 commands are rare)
-    let command: Vec<String> = std::iter::once(program.to_string_lossy().into_owned())
-        .chain(cmd_args.iter().map(|s| s.to_string_lossy().into_owned()))
-        .collect();
-
     // Dry run mode - just show what would happen
     if command.is_empty() {
         return Err(NonoError::NoCommand);
@@ -354,31 +447,200 @@ fn execute_sandboxed(
     // is not in the sandbox's allowed paths.
     let resolved_program = exec_strategy::resolve_program(&command[0])?;
 
+    // The program may be a link to a blocked command under another name, and
+    // its arguments are held to the command rules. (The supervisor lets this
+    // first exec through, so it is checked here.)
+    let target = resolved_program
+        .canonicalize()
+        .unwrap_or_else(|_| resolved_program.clone());
+    let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("/"));
+    let refusal = match config::check_blocked_command(
+        &target,
+        &caps.allowed_commands,
+        &caps.blocked_commands,
+    ) {
+        Some(blocked) => Some((
+            blocked.clone(),
+            config::blocked_command_reason(&blocked, &caps.blocked_commands).to_string(),
+        )),
+        None => [program.as_os_str(), target.as_os_str()]
+            .into_iter()
+            .find_map(|name| {
+                config::command_rules::check_command_args(
+                    name,
+                    &cmd_args,
+                    &cwd,
+                    &caps.command_rules,
+                )
+                .map(|reason| (name.to_string_lossy().into_owned(), reason))
+            }),
+    };
+    if let Some((blocked, reason)) = refusal {
+        if let Some(ref audit) = audit {
+            audit.record(&audit::AuditEvent::CommandBlocked {
+                command: &blocked,
+                reason: &reason,
+            });
+        }
+        return Err(NonoError::BlockedCommand {
+            command: blocked,
+            reason,
+        });
+    }
+
     // Write capability state file BEFORE applying sandbox.
     // This file goes to /tmp which may not be in the sandbox's allowed paths.
     let cap_file = write_capability_state_file(caps, silent);
-    let cap_file_path = cap_file.unwrap_or_else(|| std::path::PathBuf::from("/dev/null"));
-
-    // Apply the sandbox
-    output::print_applying_sandbox(silent);
-    sandbox::apply(caps)?;
-    output::print_sandbox_active(silent);
 
-    // Build environment variables for the command
-    let env_vars: Vec<(&str, &str)> = loaded_secrets
-        .iter()
-        .map(|s| (s.env_var.as_str(), s.value.as_str()))
-        .collect();
+    // Bind the filtering proxy for host allowlists now, so its port can be
+    // added to the sandbox's TCP rules. It starts serving in the supervisor.
+    let proxy = if caps.has_host_allowlist() {
+        Some(proxy::FilteringProxy::bind(
+            &caps.allow_hosts,
+            &proxy::upstream_ports(
+                &caps.tcp_connect_ports,
+                config::effective_config().policy_connect_ports(),
+            ),
+        )?)
+    } else {
+        None
+    };
+    let proxy_url = proxy.as_ref().map(|p| p.url()).transpose()?;
+    let mut caps = caps.clone();
+    if let Some(ref p) = proxy {
+        caps.proxy_port = Some(p.port()?);
+    }
+    let caps = &caps;
+    // Open the terminal for permission prompts before anything is sandboxed
+    let prompter = if args.prompt {
+        Some(prompt::Prompter::new(audit.as_ref())?)
+    } else {
+        None
+    };
+    // Prompts, capability expiry and the command blocklist (for every exec in
+    // the sandboxed process tree, not just this one) are decided on the
+    // child's syscalls
+    let notify = if prompter.is_some() || caps.has_ttl() || notify::enforces_blocklist(caps) {
+        Some(notify::NotifySupervisor::new(
+            caps,
+            prompter,
+            audit.as_ref(),
+        )?)
+    } else {
+        None
+    };
+    // Stage the copy-on-write overlays; they are mounted by the supervisor
+    // just before it forks the command
+    let ephemeral = if args.ephemeral {
+        let session = ephemeral::EphemeralSession::create(caps)?;
+        if !silent {
+            for target in session.targets() {
+                eprintln!("  Ephemeral: writes to {} are captured", target.display());
+            }
+        }
+        for path in session.uncovered_files() {
+            eprintln!(
+                "  Warning: --ephemeral does not cover single files; writes to {} go through",
+                path.display()
+            );
+        }
+        Some(session)
+    } else {
+        None
+    };
+    // Paths to mask inside the granted directories (denied or sensitive)
+    let hidden = sandbox::hidden_paths(caps);
+    if !silent {
+        for entry in hidden
+            .iter()
+            .filter(|entry| !caps.deny.contains(&entry.path))
+        {
+            let revealed: Vec<String> = entry
+                .reveal
+                .iter()
+                .filter(|r| r.path != entry.path)
+                .map(|r| r.path.display().to_string())
+                .collect();
+            if entry.reveal.len() > revealed.len() {
+                eprintln!(
+                    "  Sensitive path {} is read-only (user config override)",
+                    entry.path.display()
+                );
+            } else if revealed.is_empty() {
+                eprintln!("  Hiding sensitive path {}", entry.path.display());
+            } else {
+                eprintln!(
+                    "  Hiding sensitive path {} (except {})",
+                    entry.path.display(),
+                    revealed.join(", ")
+                );
+            }
+        }
+    }
+    let cap_file_path = cap_file
+        .clone()
+        .unwrap_or_else(|| std::path::PathBuf::from("/dev/null"));
 
     // Determine execution strategy
+    // Supervised: unsandboxed parent, sandbox applied only in the child.
+    //   Required when the parent has to run services outside the sandbox
+    //   (filtering proxy, permission prompts, capability expiry, the command
+    //   blocklist on Linux), record the session (audit log), or review the
+    //   ephemeral workspace after the command exits. On Linux, denied and
+    //   sensitive paths inside a grant are masked in the supervisor's mount
+    //   namespace, which Direct and Monitor modes do not create; the same
+    //   goes for the --isolate root and the --isolate-pids namespace.
     // Interactive mode (shell, TUI apps): use Direct exec for TTY preservation
     // Non-interactive: use Monitor mode for diagnostic output on failure
-    let strategy = if interactive {
+    let strategy = if args.supervised
+        || proxy.is_some()
+        || audit.is_some()
+        || notify.is_some()
+        || ephemeral.is_some()
+        || !hidden.is_empty()
+        || args.isolate
+        || caps.pid_namespace
+    {
+        exec_strategy::ExecStrategy::Supervised
+    } else if interactive {
         exec_strategy::ExecStrategy::Direct
     } else {
         exec_strategy::ExecStrategy::Monitor
     };
 
+    // Apply the sandbox (Supervised applies it in the child after fork)
+    output::print_applying_sandbox(silent);
+    if strategy == exec_strategy::ExecStrategy::Supervised {
+        output::print_supervisor_active(silent);
+    } else {
+        sandbox::apply(caps)?;
+        output::print_sandbox_active(silent);
+    }
+
+    // Build environment variables for the command
+    let mut env_vars: Vec<(&str, &str)> = loaded_secrets
+        .iter()
+        .map(|s| (s.env_var.as_str(), s.value.as_str()))
+        .collect();
+
+    if let Some(ref audit) = audit {
+        if !loaded_secrets.is_empty() {
+            audit.record(&audit::AuditEvent::SecretsInjected {
+                env_vars: loaded_secrets.iter().map(|s| s.env_var.as_str()).collect(),
+            });
+        }
+    }
+
+    // Point the command at the filtering proxy
+    if let Some(ref url) = proxy_url {
+        for key in proxy::PROXY_ENV_VARS {
+            env_vars.push((key, url.as_str()));
+        }
+        for key in proxy::NO_PROXY_ENV_VARS {
+            env_vars.push((key, ""));
+        }
+    }
+
     // Determine threading context for fork safety
     // If secrets were loaded, keyring may have spawned threads
     let threading = if !loaded_secrets.is_empty() {
@@ -401,6 +663,11 @@ fn execute_sandboxed(
         cap_file: &cap_file_path,
         no_diagnostics: silent,
         threading,
+        interactive,
+        proxy: proxy.as_ref(),
+        audit: audit.as_ref(),
+        notify: notify.as_ref(),
+        isolated_root: None,
     };
 
     // Execute based on strategy
@@ -418,10 +685,54 @@ fn execute_sandboxed(
             std::process::exit(exit_code);
         }
         exec_strategy::ExecStrategy::Supervised => {
-            // Not yet implemented
-            Err(NonoError::SandboxInit(
-                "Supervised mode not yet implemented".to_string(),
-            ))
+            // Supervised mode: unsandboxed parent outlives the sandboxed child
+            if let Some(ref session) = ephemeral {
+                session.enter()?;
+            }
+            sandbox::hide_paths(&hidden)?;
+            if caps.pid_namespace {
+                sandbox::prepare_pid_namespace()?;
+                if !silent {
+                    eprintln!("  PID namespace: other processes are not visible to the command");
+                }
+            }
+            // Built last, so the root picks up the overlays and masks
+            let isolated_root = if args.isolate {
+                let root = sandbox::isolate(caps, &cap_file.iter().cloned().collect::<Vec<_>>())?;
+                if !silent {
+                    eprintln!("  Isolated: only granted and system paths exist for the command");
+                }
+                Some(root)
+            } else {
+                None
+            };
+            let config = exec_strategy::ExecConfig {
+                isolated_root: isolated_root.as_ref(),
+                ..config
+            };
+            let exit_code = exec_strategy::execute_supervised(&config)?;
+            drop(isolated_root);
+            if let Some(ref audit) = audit {
+                audit.record(&audit::AuditEvent::SessionEnd { exit_code });
+            }
+            // The parent is not sandboxed, so it can clean up its own state file
+            // (on the host, not in an ephemeral overlay of the temp directory)
+            if let Some(path) = cap_file {
+                let path = match ephemeral {
+                    Some(ref session) => session.host_path(&path),
+                    None => path,
+                };
+                if let Err(e) = std::fs::remove_file(&path) {
+                    tracing::debug!("Failed to remove state file {}: {}", path.display(), e);
+                }
+            }
+            if let Some(session) = ephemeral {
+                if let Err(e) = session.finish(silent) {
+                    eprintln!("nono: {}", e);
+                }
+            }
+            // Note: loaded_secrets will be dropped here, zeroizing the secret values
+            std::process::exit(exit_code);
         }
     }
 }
@@ -447,6 +758,8 @@ fn prepare_sandbox(args: &SandboxArgs, silent: bool) -> Result<PreparedSandbox>
     // Clean up stale state files from previous nono runs
     // This prevents disk space exhaustion and information disclosure
     sandbox_state::cleanup_stale_state_files();
+    // Merge ~/.config/nono/config.toml into the security lists
+    config::init_effective_config(true)?;
     // Load profile once if specified (used for both capabilities and secrets)
     let loaded_profile = if let Some(ref profile_name) = args.profile {
         let prof = profile::load_profile(profile_name, args.trust_unsigned)?;
@@ -561,6 +874,10 @@ fn prepare_sandbox(args: &SandboxArgs, silent: bool) -> Result<PreparedSandbox>
         }
     }
 
+    // Impose the admin policy's floor from /etc/nono
+    config::enforce_system_policy(&mut caps)?;
+    config::apply_command_rules(&mut caps, &workdir);
+
     // Check if any capabilities are specified (must have fs or network)
     // Network is allowed by default, so only error if no fs AND network is blocked
     if !caps.has_fs() && caps.net_block {
@@ -595,6 +912,9 @@ fn prepare_sandbox(args: &SandboxArgs, silent: bool) -> Result<PreparedSandbox>
 
     // Print capability summary
     output::print_capabilities(&caps, silent);
+    if args.verbose > 0 {
+        output::print_enforcement_report(&sandbox::enforcement_report(&caps), silent);
+    }
 
     // Check platform support
     if !sandbox::is_supported() {
@@ -610,6 +930,24 @@ fn prepare_sandbox(args: &SandboxArgs, silent: bool) -> Result<PreparedSandbox>
     })
 }
 
+/// Canonical form of `path`, which may not exist yet: its directory is
+/// resolved and the file name kept
+fn resolve_new_file(path: &std::path::Path) -> std::path::PathBuf {
+    if let Ok(resolved) = path.canonicalize() {
+        return resolved;
+    }
+    let absolute = std::env::current_dir()
+        .map(|cwd| cwd.join(path))
+        .unwrap_or_else(|_| path.to_path_buf());
+    match (absolute.parent(), absolute.file_name()) {
+        (Some(dir), Some(name)) => dir
+            .canonicalize()
+            .map(|dir| dir.join(name))
+            .unwrap_or(absolute.clone()),
+        _ => absolute,
+    }
+}
+
 fn write_capability_state_file(caps: &CapabilitySet, silent: bool) -> Option<std::path::PathBuf> {
     // Write sandbox state for `nono why --self`.
     // This allows sandboxed processes to query their own capabilities.
@@ -720,12 +1058,12 @@ mod tests {
     #[test]
     fn test_check_sensitive_path() {
         // Verify sensitive path checking works
-        assert!(config::check_sensitive_path("~/.ssh").is_some());
-        assert!(config::check_sensitive_path("~/.aws").is_some());
-        assert!(config::check_sensitive_path("~/.bashrc").is_some());
+        assert!(config::check_sensitive_path("~/.ssh", FsAccess::Read).is_some());
+        assert!(config::check_sensitive_path("~/.aws", FsAccess::Read).is_some());
+        assert!(config::check_sensitive_path("~/.bashrc", FsAccess::Read).is_some());
 
         // Non-sensitive paths should return None
-        assert!(config::check_sensitive_path("/tmp").is_none());
-        assert!(config::check_sensitive_path("~/Documents").is_none());
+        assert!(config::check_sensitive_path("/tmp", FsAccess::Read).is_none());
+        assert!(config::check_sensitive_path("~/Documents", FsAccess::Read).is_none());
     }
 }
@@ -0,0 +1,1420 @@
+//! Seccomp notify supervisor for the supervised child (Linux only).
+//!
+//! Some decisions cannot be left to the Landlock ruleset the child was
+//! started with, so the child stops on selected syscalls (seccomp user
+//! notification) and the supervisor answers them:
+//!
+//! - `--prompt`: operations the sandbox denies are put to the operator
+//!   (see [`crate::prompt`]) instead of failing silently.
+//! - Time-limited capabilities (`--allow DIR:5m`): Landlock rules cannot be
+//!   removed from a running process, so once a capability expires the
+//!   supervisor refuses what only that capability allowed.
+//! - The command blocklist: `nono run` only checks the program it starts, so
+//!   every `execve` in the sandboxed process tree is checked against the
+//!   blocklist too, and a blocked command fails with `EACCES`. So are the
+//!   arguments of commands with command rules (see
+//!   [`crate::config::command_rules`]); an argument too long to read
+//!   (over `PATH_MAX`) makes such an exec fail with `E2BIG`.
+//!
+//! File opens and TCP connects are performed on a *probe* thread of the
+//! supervisor that carries the Landlock policy still in effect, and the
+//! result (e.g. the opened file descriptor) is handed to the child. Because
+//! the supervisor performs the operation itself, using the path or address
+//! it read, the child cannot swap the target after the decision.
+//!
+//! Operations the child must perform itself (`execve`, `mkdir`, `unlink`,
+//! `rename`, ...) are checked by path and then let through. A multithreaded
+//! command could rewrite the path between the check and the syscall, so for
+//! these the expiry of a capability is best-effort. File descriptors the
+//! command opened before a capability expired stay usable. io_uring, which
+//! opens files without a syscall the filter could stop, is unavailable
+//! (`ENOSYS`) to a command with time-limited capabilities.
+//!
+//! The blocklist matches command *names*, like the check on the top-level
+//! program: the file name of the executed path, of the file it resolves to,
+//! and `argv[0]`. A copy of a blocked binary under another name still runs,
+//! and so does a script whose interpreter is blocked (the kernel starts the
+//! interpreter without another `execve`).
+
+use crate::audit::AuditLog;
+use crate::capability::{CapabilitySet, FsAccess};
+use crate::config;
+use crate::error::{NonoError, Result};
+use crate::prompt::Prompter;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+struct Inner {
+    caps: CapabilitySet,
+    /// System paths the sandbox always allows reading, whatever expires
+    system_read: Vec<PathBuf>,
+    prompter: Option<Prompter>,
+    audit: Option<AuditLog>,
+    /// Whether execs are checked against the command blocklist and rules
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    block_exec: bool,
+    /// Set once the child has asked to exec the command
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    command_started: AtomicBool,
+    /// `(pid, command)` pairs already reported as blocked
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    blocked_reported: Mutex<HashSet<(u32, String)>>,
+}
+
+/// Whether the supervisor has to enforce the command blocklist or command
+/// rules on `caps`.
+///
+/// Only possible on Linux; on macOS the Seatbelt profile denies executing
+/// blocked commands instead, and command rules only apply to the top-level
+/// program.
+pub fn enforces_blocklist(caps: &CapabilitySet) -> bool {
+    cfg!(target_os = "linux")
+        && (!config::blocked_commands(&caps.allowed_commands, &caps.blocked_commands).is_empty()
+            || !caps.command_rules.is_empty())
+}
+
+/// Supervisor side of `--prompt`, time-limited capabilities and the command
+/// blocklist.
+///
+/// Created before fork, started after fork with the child's seccomp listener.
+pub struct NotifySupervisor {
+    inner: Arc<Inner>,
+}
+
+impl NotifySupervisor {
+    /// `caps` must already have their expiry armed
+    pub fn new(
+        caps: &CapabilitySet,
+        prompter: Option<Prompter>,
+        audit: Option<&AuditLog>,
+    ) -> Result<Self> {
+        if !cfg!(target_os = "linux") && caps.has_ttl() {
+            return Err(NonoError::SandboxInit(
+                "time-limited capabilities are only supported on Linux \
+                 (requires seccomp user notification)"
+                    .to_string(),
+            ));
+        }
+
+        let system_read = config::get_system_read_paths()
+            .iter()
+            .filter_map(|path| Path::new(path).canonicalize().ok())
+            .collect();
+
+        Ok(Self {
+            inner: Arc::new(Inner {
+                caps: caps.clone(),
+                system_read,
+                prompter,
+                audit: audit.cloned(),
+                block_exec: enforces_blocklist(caps),
+                command_started: AtomicBool::new(false),
+                blocked_reported: Mutex::new(HashSet::new()),
+            }),
+        })
+    }
+}
+
+impl Inner {
+    /// Whether `access` to `path` is refused because capabilities expired
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    fn access_expired(&self, path: &Path, access: FsAccess, now: SystemTime) -> bool {
+        self.caps.access_expired(path, access, now)
+            && (access.writes() || !self.system_read.iter().any(|p| path.starts_with(p)))
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::{Inner, NotifySupervisor};
+    use crate::audit::AuditEvent;
+    use crate::capability::{CapabilitySet, FsAccess};
+    use crate::config;
+    use crate::error::{NonoError, Result};
+    use crate::sandbox;
+    use crate::sandbox::seccomp::{self, Notification, NotifyListener, SeccompFilter};
+    use nix::libc;
+    use std::ffi::{CString, OsStr};
+    use std::os::fd::{AsFd, AsRawFd, FromRawFd, OwnedFd};
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::FileExt;
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::Ordering;
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::thread;
+    use std::time::SystemTime;
+    use tracing::{debug, info, warn};
+
+    /// Longest path read from the child's memory (PATH_MAX)
+    const MAX_PATH_LEN: usize = 4096;
+
+    /// Most arguments read from an exec held to command rules
+    const MAX_ARGS: usize = 4096;
+
+    /// Largest socket address read from the child's memory
+    const MAX_SOCKADDR_LEN: usize = 128;
+
+    /// A syscall that acts on paths but has no result the supervisor could
+    /// produce for the child, checked against expired capabilities
+    struct PathSyscall {
+        nr: libc::c_long,
+        /// Label shown in prompts
+        operation: &'static str,
+        /// `(dirfd argument, path argument)` pairs; `None` means `AT_FDCWD`
+        paths: &'static [(Option<usize>, usize)],
+        /// Access the operation needs on each path
+        access: FsAccess,
+        /// Whether the syscall follows a final symlink (otherwise the entry
+        /// itself is created, removed or renamed)
+        follow: bool,
+    }
+
+    const fn path_syscall(
+        nr: libc::c_long,
+        operation: &'static str,
+        paths: &'static [(Option<usize>, usize)],
+        access: FsAccess,
+        follow: bool,
+    ) -> PathSyscall {
+        PathSyscall {
+            nr,
+            operation,
+            paths,
+            access,
+            follow,
+        }
+    }
+
+    /// Syscalls that execute a program, checked against the command blocklist
+    const EXEC_SYSCALLS: [libc::c_long; 2] = [libc::SYS_execve, libc::SYS_execveat];
+
+    /// Path syscalls intercepted when capabilities can expire
+    const PATH_SYSCALLS: &[PathSyscall] = &[
+        path_syscall(
+            libc::SYS_execve,
+            "execute",
+            &[(None, 0)],
+            FsAccess::Execute,
+            true,
+        ),
+        path_syscall(
+            libc::SYS_execveat,
+            "execute",
+            &[(Some(0), 1)],
+            FsAccess::Execute,
+            true,
+        ),
+        path_syscall(
+            libc::SYS_truncate,
+            "truncate",
+            &[(None, 0)],
+            FsAccess::Write,
+            true,
+        ),
+        path_syscall(
+            libc::SYS_mkdirat,
+            "mkdir",
+            &[(Some(0), 1)],
+            FsAccess::Write,
+            false,
+        ),
+        path_syscall(
+            libc::SYS_mknodat,
+            "mknod",
+            &[(Some(0), 1)],
+            FsAccess::Write,
+            false,
+        ),
+        path_syscall(
+            libc::SYS_unlinkat,
+            "unlink",
+            &[(Some(0), 1)],
+            FsAccess::Write,
+            false,
+        ),
+        path_syscall(
+            libc::SYS_symlinkat,
+            "symlink",
+            &[(Some(1), 2)],
+            FsAccess::Write,
+            false,
+        ),
+        path_syscall(
+            libc::SYS_linkat,
+            "link",
+            &[(Some(2), 3)],
+            FsAccess::Write,
+            false,
+        ),
+        path_syscall(
+            libc::SYS_renameat,
+            "rename",
+            &[(Some(0), 1), (Some(2), 3)],
+            FsAccess::Write,
+            false,
+        ),
+        path_syscall(
+            libc::SYS_renameat2,
+            "rename",
+            &[(Some(0), 1), (Some(2), 3)],
+            FsAccess::Write,
+            false,
+        ),
+        #[cfg(target_arch = "x86_64")]
+        path_syscall(
+            libc::SYS_mkdir,
+            "mkdir",
+            &[(None, 0)],
+            FsAccess::Write,
+            false,
+        ),
+        #[cfg(target_arch = "x86_64")]
+        path_syscall(
+            libc::SYS_mknod,
+            "mknod",
+            &[(None, 0)],
+            FsAccess::Write,
+            false,
+        ),
+        #[cfg(target_arch = "x86_64")]
+        path_syscall(
+            libc::SYS_unlink,
+            "unlink",
+            &[(None, 0)],
+            FsAccess::Write,
+            false,
+        ),
+        #[cfg(target_arch = "x86_64")]
+        path_syscall(
+            libc::SYS_rmdir,
+            "rmdir",
+            &[(None, 0)],
+            FsAccess::Write,
+            false,
+        ),
+        #[cfg(target_arch = "x86_64")]
+        path_syscall(
+            libc::SYS_symlink,
+            "symlink",
+            &[(None, 1)],
+            FsAccess::Write,
+            false,
+        ),
+        #[cfg(target_arch = "x86_64")]
+        path_syscall(libc::SYS_link, "link", &[(None, 1)], FsAccess::Write, false),
+        #[cfg(target_arch = "x86_64")]
+        path_syscall(
+            libc::SYS_rename,
+            "rename",
+            &[(None, 0), (None, 1)],
+            FsAccess::Write,
+            false,
+        ),
+    ];
+
+    impl NotifySupervisor {
+        /// Seccomp filter the child must install for its operations to reach us
+        pub fn filter(&self) -> SeccompFilter {
+            let caps = &self.inner.caps;
+            let mut syscalls = Vec::new();
+            // The blocklist alone only needs execs
+            if self.inner.prompter.is_some() || caps.has_ttl() {
+                syscalls.extend(seccomp::open_syscalls());
+            }
+            // Connects can only be denied when TCP filtering is active
+            if self.inner.prompter.is_some() && (caps.net_block || caps.has_port_rules()) {
+                syscalls.push(libc::SYS_connect);
+            }
+            if caps.has_ttl() {
+                syscalls.extend(PATH_SYSCALLS.iter().map(|s| s.nr));
+            } else if self.inner.block_exec {
+                syscalls.extend(EXEC_SYSCALLS);
+            }
+            if caps.has_ttl() || self.inner.block_exec {
+                // Expiry and the blocklist are enforced here and nowhere
+                // else, so they must not be bypassable through another
+                // syscall ABI. Nor, for expiry, through io_uring, whose
+                // opens never reach the filter.
+                let unavailable = if caps.has_ttl() {
+                    seccomp::io_uring_syscalls()
+                } else {
+                    Vec::new()
+                };
+                return SeccompFilter::notify_enforcing(&syscalls, &unavailable);
+            }
+            SeccompFilter::notify(&syscalls)
+        }
+
+        /// Start answering the child's notifications on background threads.
+        ///
+        /// Must be called in the supervisor after fork: it spawns threads.
+        pub fn start(&self, listener: OwnedFd) -> Result<()> {
+            let listener = Arc::new(NotifyListener::new(listener));
+            let inner = Arc::clone(&self.inner);
+            // Only prompts and expiry need operations performed for the child
+            let probes = if inner.prompter.is_some() || inner.caps.has_ttl() {
+                Some(Arc::new(Probes::spawn(&inner.caps, SystemTime::now())?))
+            } else {
+                None
+            };
+
+            if inner.caps.has_ttl() {
+                spawn_expiry_reporter(&inner)?;
+            }
+
+            thread::Builder::new()
+                .name("nono-notify".to_string())
+                .spawn(move || loop {
+                    let notif = match listener.receive() {
+                        Ok(notif) => notif,
+                        Err(e) => {
+                            debug!("Seccomp listener closed: {}", e);
+                            break;
+                        }
+                    };
+
+                    // One thread per request: opens may block (FIFOs) and
+                    // prompts wait on the operator
+                    let (l, i, p) = (Arc::clone(&listener), Arc::clone(&inner), probes.clone());
+                    if let Err(e) =
+                        thread::Builder::new().spawn(move || handle(&l, &i, p.as_deref(), notif))
+                    {
+                        warn!("Failed to spawn notification handler: {}", e);
+                        let _ = listener.respond_error(notif.id, libc::EAGAIN);
+                    }
+                })
+                .map_err(|e| {
+                    NonoError::SandboxInit(format!("Failed to start notify supervisor: {}", e))
+                })?;
+
+            Ok(())
+        }
+    }
+
+    /// Log each capability as it expires (and record it in the audit log)
+    fn spawn_expiry_reporter(inner: &Arc<Inner>) -> Result<()> {
+        let mut expiries: Vec<_> = inner
+            .caps
+            .fs
+            .iter()
+            .filter_map(|cap| cap.expires_at.map(|at| (at, cap.clone())))
+            .collect();
+        expiries.sort_by_key(|(at, _)| *at);
+        let audit = inner.audit.clone();
+
+        thread::Builder::new()
+            .name("nono-expiry".to_string())
+            .spawn(move || {
+                for (at, cap) in expiries {
+                    if let Ok(wait) = at.duration_since(SystemTime::now()) {
+                        thread::sleep(wait);
+                    }
+                    info!("Capability expired: {}", cap);
+                    if let Some(ref audit) = audit {
+                        audit.record(&AuditEvent::CapabilityExpired {
+                            path: &cap.resolved,
+                            access: &cap.access.to_string(),
+                        });
+                    }
+                }
+            })
+            .map_err(|e| {
+                NonoError::SandboxInit(format!("Failed to start expiry reporter: {}", e))
+            })?;
+        Ok(())
+    }
+
+    /// An intercepted operation, ready to be performed by the supervisor
+    enum Request {
+        Open {
+            /// Directory relative paths resolve against (None for absolute paths)
+            base: Option<OwnedFd>,
+            path: CString,
+            flags: libc::c_int,
+            mode: libc::c_uint,
+            /// Absolute path shown to the operator
+            display: String,
+        },
+        Connect {
+            /// The child's socket, duplicated into the supervisor
+            socket: OwnedFd,
+            addr: Vec<u8>,
+            display: String,
+        },
+        /// An operation only the kernel can perform for the caller (exec,
+        /// directory changes, opens of `/proc/self/...`), checked by path
+        InPlace {
+            operation: &'static str,
+            display: String,
+            /// Resolved paths the operation touches and the access it needs
+            checks: Vec<(PathBuf, FsAccess)>,
+        },
+    }
+
+    impl Request {
+        /// Operation label shown in prompts ("read", "write", "connect", ...)
+        fn operation(&self) -> &'static str {
+            match self {
+                Request::Open { flags, .. } => open_access_label(*flags),
+                Request::Connect { .. } => "connect",
+                Request::InPlace { operation, .. } => operation,
+            }
+        }
+
+        fn target(&self) -> &str {
+            match self {
+                Request::Open { display, .. }
+                | Request::Connect { display, .. }
+                | Request::InPlace { display, .. } => display,
+            }
+        }
+
+        /// Perform the operation on the calling thread.
+        ///
+        /// Returns the opened FD for opens, `None` for connects, or an errno.
+        fn perform(&self) -> std::result::Result<Option<OwnedFd>, i32> {
+            match self {
+                Request::Open {
+                    base,
+                    path,
+                    flags,
+                    mode,
+                    ..
+                } => {
+                    let dirfd = base.as_ref().map_or(libc::AT_FDCWD, |b| b.as_raw_fd());
+                    // Our copy is close-on-exec; the child's copy gets the flags it asked for
+                    let fd = unsafe {
+                        libc::openat(
+                            dirfd,
+                            path.as_ptr(),
+                            flags | libc::O_CLOEXEC | libc::O_NOCTTY,
+                            *mode,
+                        )
+                    };
+                    if fd < 0 {
+                        return Err(last_errno());
+                    }
+                    Ok(Some(unsafe { OwnedFd::from_raw_fd(fd) }))
+                }
+                Request::Connect { socket, addr, .. } => {
+                    let ret = unsafe {
+                        libc::connect(
+                            socket.as_raw_fd(),
+                            addr.as_ptr() as *const libc::sockaddr,
+                            addr.len() as libc::socklen_t,
+                        )
+                    };
+                    if ret < 0 {
+                        return Err(last_errno());
+                    }
+                    Ok(None)
+                }
+                Request::InPlace { .. } => Err(libc::ENOSYS),
+            }
+        }
+
+        /// Whether ordinary file permissions (not the sandbox) deny the open.
+        ///
+        /// No point asking the operator about an operation nono cannot grant.
+        fn denied_by_permissions(&self) -> bool {
+            match self {
+                Request::Open {
+                    base, path, flags, ..
+                } => {
+                    let dirfd = base.as_ref().map_or(libc::AT_FDCWD, |b| b.as_raw_fd());
+                    let mode = match flags & libc::O_ACCMODE {
+                        libc::O_WRONLY => libc::W_OK,
+                        libc::O_RDWR => libc::R_OK | libc::W_OK,
+                        _ => libc::R_OK,
+                    };
+                    let ret =
+                        unsafe { libc::faccessat(dirfd, path.as_ptr(), mode, libc::AT_EACCESS) };
+                    ret < 0 && last_errno() == libc::EACCES
+                }
+                Request::Connect { .. } | Request::InPlace { .. } => false,
+            }
+        }
+    }
+
+    /// Describe what an open with `flags` does to the file
+    fn open_access_label(flags: libc::c_int) -> &'static str {
+        let writes = flags & (libc::O_CREAT | libc::O_TRUNC) != 0;
+        match flags & libc::O_ACCMODE {
+            libc::O_RDWR => "read+write",
+            libc::O_WRONLY => "write",
+            _ if writes => "read+write",
+            _ => "read",
+        }
+    }
+
+    /// Filesystem access an open with `flags` needs
+    fn open_access(flags: libc::c_int) -> FsAccess {
+        match open_access_label(flags) {
+            "read" => FsAccess::Read,
+            "write" => FsAccess::Write,
+            _ => FsAccess::ReadWrite,
+        }
+    }
+
+    fn last_errno() -> i32 {
+        std::io::Error::last_os_error()
+            .raw_os_error()
+            .unwrap_or(libc::EIO)
+    }
+
+    /// Handle one notification end to end
+    fn handle(
+        listener: &NotifyListener,
+        inner: &Inner,
+        probes: Option<&Probes>,
+        notif: Notification,
+    ) {
+        // The first exec is nono starting the command it already checked,
+        // from a child that is still not dumpable (its memory may be
+        // unreadable here); nothing else in the tree runs before it
+        let first_exec = EXEC_SYSCALLS.contains(&notif.nr)
+            && !inner.command_started.swap(true, Ordering::SeqCst);
+        if inner.block_exec && EXEC_SYSCALLS.contains(&notif.nr) && !first_exec {
+            match blocked_exec(&notif, &inner.caps) {
+                Ok(None) => {}
+                Ok(Some(_)) | Err(_) if !listener.is_valid(notif.id) => return,
+                Ok(Some((command, path, reason))) => {
+                    // Shells try each PATH entry in turn: report once per process
+                    let first_report = match inner.blocked_reported.lock() {
+                        Ok(mut reported) => reported.insert((notif.pid, command.clone())),
+                        Err(_) => true,
+                    };
+                    if first_report {
+                        eprintln!(
+                            "[nono] Blocked execution of '{}' ({}): {}",
+                            command, path, reason
+                        );
+                    }
+                    if let Some(ref audit) = inner.audit {
+                        audit.record(&AuditEvent::CommandBlocked {
+                            command: &command,
+                            reason: &reason,
+                        });
+                    }
+                    let _ = listener.respond_error(notif.id, libc::EACCES);
+                    return;
+                }
+                Err(errno) => {
+                    let _ = listener.respond_error(notif.id, errno);
+                    return;
+                }
+            }
+        }
+
+        // Until a capability expires, only --prompt has anything to decide
+        let now = SystemTime::now();
+        let probes = match probes {
+            Some(probes) if inner.prompter.is_some() || expired_count(&inner.caps, now) > 0 => {
+                probes
+            }
+            _ => {
+                let _ = listener.respond_continue(notif.id);
+                return;
+            }
+        };
+
+        let request = match parse_request(&notif, &inner.caps) {
+            Ok(Some(request)) => request,
+            Ok(None) => {
+                let _ = listener.respond_continue(notif.id);
+                return;
+            }
+            Err(errno) => {
+                let _ = listener.respond_error(notif.id, errno);
+                return;
+            }
+        };
+
+        // Everything above read the caller's memory; if it died meanwhile,
+        // its PID may have been reused and what we read means nothing
+        if !listener.is_valid(notif.id) {
+            return;
+        }
+
+        let ask = |request: &Request| {
+            inner.prompter.as_ref().is_some_and(|prompter| {
+                prompter.decide(request.operation(), request.target(), notif.pid)
+            })
+        };
+
+        if let Request::InPlace { ref checks, .. } = request {
+            let expired = checks
+                .iter()
+                .any(|(path, access)| inner.access_expired(path, *access, now));
+            let sent = if !expired || ask(&request) {
+                listener.respond_continue(notif.id)
+            } else {
+                listener.respond_error(notif.id, libc::EACCES)
+            };
+            if sent.is_err() {
+                let _ = listener.respond_error(notif.id, libc::EACCES);
+            }
+            return;
+        }
+
+        let request = Arc::new(request);
+        let result = match probes.perform(&inner.caps, Arc::clone(&request)) {
+            Err(libc::EACCES) if !request.denied_by_permissions() => {
+                if ask(&request) {
+                    request.perform()
+                } else {
+                    Err(libc::EACCES)
+                }
+            }
+            other => other,
+        };
+
+        let sent = match result {
+            Ok(Some(fd)) => {
+                let cloexec =
+                    matches!(*request, Request::Open { flags, .. } if flags & libc::O_CLOEXEC != 0);
+                listener.respond_fd(notif.id, fd.as_fd(), cloexec)
+            }
+            Ok(None) => listener.respond_value(notif.id, 0),
+            Err(errno) => listener.respond_error(notif.id, errno),
+        };
+        if let Err(e) = sent {
+            // Never leave the caller hanging
+            let errno = e.raw_os_error().unwrap_or(libc::EIO);
+            let _ = listener.respond_error(notif.id, errno);
+        }
+    }
+
+    /// Number of capabilities expired at `now` (only ever grows)
+    fn expired_count(caps: &CapabilitySet, now: SystemTime) -> usize {
+        caps.fs.iter().filter(|cap| cap.is_expired_at(now)).count()
+    }
+
+    /// Turn a notification into a request.
+    ///
+    /// `Ok(None)` means the kernel should just run the syscall: the sandbox
+    /// cannot deny it.
+    fn parse_request(
+        notif: &Notification,
+        caps: &CapabilitySet,
+    ) -> std::result::Result<Option<Request>, i32> {
+        let nr = notif.nr;
+        let args = notif.args;
+
+        if nr == libc::SYS_openat {
+            return parse_open(
+                notif.pid,
+                args[0] as i32,
+                args[1],
+                args[2] as i32,
+                args[3] as u32,
+            );
+        }
+        #[cfg(target_arch = "x86_64")]
+        {
+            if nr == libc::SYS_open {
+                return parse_open(
+                    notif.pid,
+                    libc::AT_FDCWD,
+                    args[0],
+                    args[1] as i32,
+                    args[2] as u32,
+                );
+            }
+            if nr == libc::SYS_creat {
+                let flags = libc::O_CREAT | libc::O_WRONLY | libc::O_TRUNC;
+                return parse_open(notif.pid, libc::AT_FDCWD, args[0], flags, args[1] as u32);
+            }
+        }
+        if nr == libc::SYS_openat2 {
+            // C libraries fall back to openat, which we can handle
+            return Err(libc::ENOSYS);
+        }
+        if nr == libc::SYS_connect {
+            return parse_connect(notif.pid, caps, args[0] as i32, args[1], args[2] as usize);
+        }
+        if let Some(syscall) = PATH_SYSCALLS.iter().find(|s| s.nr == nr) {
+            return parse_path_syscall(notif, syscall).map(Some);
+        }
+
+        Ok(None)
+    }
+
+    fn parse_open(
+        pid: u32,
+        dirfd: i32,
+        path_addr: u64,
+        flags: i32,
+        mode: u32,
+    ) -> std::result::Result<Option<Request>, i32> {
+        let path = read_child_cstring(pid, path_addr).map_err(|_| libc::EFAULT)?;
+        let path_bytes = path.as_bytes();
+
+        let (base, display) = if path_bytes.starts_with(b"/") {
+            (None, PathBuf::from(OsStr::from_bytes(path_bytes)))
+        } else {
+            let base_link = base_link(pid, dirfd);
+            let base_dir = std::fs::read_link(&base_link).map_err(|_| libc::EBADF)?;
+            let base = open_path_dir(&base_link)?;
+            (Some(base), base_dir.join(OsStr::from_bytes(path_bytes)))
+        };
+
+        // Another process cannot open these faithfully: the kernel has to,
+        // after checking where they lead
+        if refers_to_caller(&display) {
+            let checks = caller_view(&display, pid)
+                .and_then(|path| path.canonicalize().ok())
+                .map(|path| vec![(path, open_access(flags))])
+                .unwrap_or_default();
+            return Ok(Some(Request::InPlace {
+                operation: open_access_label(flags),
+                display: display.display().to_string(),
+                checks,
+            }));
+        }
+
+        Ok(Some(Request::Open {
+            base,
+            path,
+            flags,
+            mode,
+            display: display.display().to_string(),
+        }))
+    }
+
+    /// Read and resolve the paths of an exec or directory-changing syscall
+    fn parse_path_syscall(
+        notif: &Notification,
+        syscall: &PathSyscall,
+    ) -> std::result::Result<Request, i32> {
+        let mut checks = Vec::new();
+        let mut display = Vec::new();
+
+        for &(dirfd_arg, path_arg) in syscall.paths {
+            let dirfd = dirfd_arg.map_or(libc::AT_FDCWD, |i| notif.args[i] as i32);
+            let path =
+                read_child_cstring(notif.pid, notif.args[path_arg]).map_err(|_| libc::EFAULT)?;
+            let path = Path::new(OsStr::from_bytes(path.as_bytes()));
+
+            let absolute = if path.is_absolute() {
+                path.to_path_buf()
+            } else {
+                let base =
+                    std::fs::read_link(base_link(notif.pid, dirfd)).map_err(|_| libc::EBADF)?;
+                if path.as_os_str().is_empty() {
+                    // execveat(fd, "", AT_EMPTY_PATH)
+                    base
+                } else {
+                    base.join(path)
+                }
+            };
+            display.push(absolute.display().to_string());
+
+            // Paths that do not resolve fail in the kernel as well
+            if let Some(resolved) = resolve_target(&absolute, syscall.follow) {
+                checks.push((resolved, syscall.access));
+            }
+        }
+
+        Ok(Request::InPlace {
+            operation: syscall.operation,
+            display: display.join(" -> "),
+            checks,
+        })
+    }
+
+    /// Check an exec against the command blocklist and the command rules.
+    ///
+    /// Returns the refused command name, the executed path and the reason,
+    /// if refused.
+    fn blocked_exec(
+        notif: &Notification,
+        caps: &CapabilitySet,
+    ) -> std::result::Result<Option<(String, String, String)>, i32> {
+        let syscall = PATH_SYSCALLS
+            .iter()
+            .find(|s| s.nr == notif.nr)
+            .ok_or(libc::ENOSYS)?;
+        let Request::InPlace {
+            display, checks, ..
+        } = parse_path_syscall(notif, syscall)?
+        else {
+            return Err(libc::EINVAL);
+        };
+
+        let mut names = vec![PathBuf::from(&display)];
+        names.extend(checks.into_iter().map(|(path, _)| path));
+        // Multi-call binaries (busybox, coreutils) dispatch on argv[0]
+        let argv = if notif.nr == libc::SYS_execveat {
+            notif.args[2]
+        } else {
+            notif.args[1]
+        };
+        let arg0 = read_child_argv(notif.pid, argv, 1).map_err(|_| libc::EFAULT)?;
+        names.extend(
+            arg0.iter()
+                .map(|arg| PathBuf::from(OsStr::from_bytes(arg.as_bytes()))),
+        );
+
+        if let Some(command) = names.iter().find_map(|name| {
+            config::check_blocked_command(name, &caps.allowed_commands, &caps.blocked_commands)
+        }) {
+            let reason = config::blocked_command_reason(&command, &caps.blocked_commands);
+            return Ok(Some((command, display, reason.to_string())));
+        }
+
+        // Only commands with rules need their arguments read
+        let has_rules = names.iter().any(|name| {
+            caps.command_rules
+                .iter()
+                .any(|(command, _)| name.file_name() == Some(OsStr::new(command)))
+        });
+        if !has_rules {
+            return Ok(None);
+        }
+        let args =
+            read_child_argv(notif.pid, argv, MAX_ARGS + 1).map_err(|e| match e.raw_os_error() {
+                Some(libc::ENAMETOOLONG) => libc::E2BIG,
+                _ => libc::EFAULT,
+            })?;
+        if args.len() > MAX_ARGS {
+            return Err(libc::E2BIG);
+        }
+        let args: Vec<&OsStr> = args
+            .iter()
+            .skip(1)
+            .map(|arg| OsStr::from_bytes(arg.as_bytes()))
+            .collect();
+        let cwd =
+            std::fs::read_link(format!("/proc/{}/cwd", notif.pid)).map_err(|_| libc::EFAULT)?;
+
+        Ok(names.iter().find_map(|name| {
+            config::command_rules::check_command_args(name, &args, &cwd, &caps.command_rules).map(
+                |reason| {
+                    let command = name.file_name().unwrap_or(name.as_os_str());
+                    (
+                        command.to_string_lossy().into_owned(),
+                        display.clone(),
+                        reason,
+                    )
+                },
+            )
+        }))
+    }
+
+    /// Canonical path an operation acts on: the file a symlink leads to when
+    /// `follow` is set, otherwise the directory entry itself
+    fn resolve_target(path: &Path, follow: bool) -> Option<PathBuf> {
+        if follow {
+            return path.canonicalize().ok();
+        }
+        let name = path.file_name()?;
+        let parent = path.parent()?.canonicalize().ok()?;
+        Some(parent.join(name))
+    }
+
+    /// `/proc` link relative paths resolve against
+    fn base_link(pid: u32, dirfd: i32) -> String {
+        if dirfd == libc::AT_FDCWD {
+            format!("/proc/{}/cwd", pid)
+        } else {
+            format!("/proc/{}/fd/{}", pid, dirfd)
+        }
+    }
+
+    /// Paths whose meaning depends on which process opens them
+    fn refers_to_caller(path: &Path) -> bool {
+        path.starts_with("/proc")
+            || path.starts_with("/dev/fd")
+            || ["/dev/stdin", "/dev/stdout", "/dev/stderr"]
+                .iter()
+                .any(|p| path == Path::new(p))
+    }
+
+    /// Rewrite a caller-relative path so it means the same thing to the supervisor
+    fn caller_view(path: &Path, tid: u32) -> Option<PathBuf> {
+        let tgid = thread_group_id(tid)?;
+        let proc_tid = PathBuf::from(format!("/proc/{}", tid));
+        let rewrites = [
+            ("/proc/self", PathBuf::from(format!("/proc/{}", tgid))),
+            (
+                "/proc/thread-self",
+                proc_tid.join("task").join(tid.to_string()),
+            ),
+            ("/dev/fd", proc_tid.join("fd")),
+            ("/dev/stdin", proc_tid.join("fd/0")),
+            ("/dev/stdout", proc_tid.join("fd/1")),
+            ("/dev/stderr", proc_tid.join("fd/2")),
+        ];
+        for (prefix, replacement) in rewrites {
+            if let Ok(rest) = path.strip_prefix(prefix) {
+                return Some(replacement.join(rest));
+            }
+        }
+        Some(path.to_path_buf())
+    }
+
+    fn parse_connect(
+        pid: u32,
+        caps: &CapabilitySet,
+        sockfd: i32,
+        addr_ptr: u64,
+        addr_len: usize,
+    ) -> std::result::Result<Option<Request>, i32> {
+        let addr = read_child_bytes(pid, addr_ptr, addr_len.min(MAX_SOCKADDR_LEN))
+            .map_err(|_| libc::EFAULT)?;
+        let Some((display, port)) = describe_inet_addr(&addr) else {
+            // Only TCP over IP is subject to the sandbox's network rules
+            return Ok(None);
+        };
+        if !caps.net_block && caps.effective_connect_ports().contains(&port) {
+            return Ok(None);
+        }
+
+        let socket = dup_child_fd(pid, sockfd)?;
+        if !is_tcp_socket(&socket) {
+            return Ok(None);
+        }
+
+        Ok(Some(Request::Connect {
+            socket,
+            addr,
+            display,
+        }))
+    }
+
+    /// Format an AF_INET/AF_INET6 socket address as `host:port`
+    fn describe_inet_addr(addr: &[u8]) -> Option<(String, u16)> {
+        if addr.len() < 4 {
+            return None;
+        }
+        let family = u16::from_ne_bytes([addr[0], addr[1]]) as libc::c_int;
+        let port = u16::from_be_bytes([addr[2], addr[3]]);
+
+        match family {
+            libc::AF_INET if addr.len() >= 8 => {
+                let ip = std::net::Ipv4Addr::new(addr[4], addr[5], addr[6], addr[7]);
+                Some((format!("{}:{}", ip, port), port))
+            }
+            libc::AF_INET6 if addr.len() >= 24 => {
+                let mut octets = [0u8; 16];
+                octets.copy_from_slice(&addr[8..24]);
+                let ip = std::net::Ipv6Addr::from(octets);
+                Some((format!("[{}]:{}", ip, port), port))
+            }
+            _ => None,
+        }
+    }
+
+    fn is_tcp_socket(socket: &OwnedFd) -> bool {
+        let get = |opt: libc::c_int| -> Option<libc::c_int> {
+            let mut value: libc::c_int = 0;
+            let mut len = std::mem::size_of::<libc::c_int>() as libc::socklen_t;
+            let ret = unsafe {
+                libc::getsockopt(
+                    socket.as_raw_fd(),
+                    libc::SOL_SOCKET,
+                    opt,
+                    &mut value as *mut libc::c_int as *mut libc::c_void,
+                    &mut len,
+                )
+            };
+            (ret == 0).then_some(value)
+        };
+        get(libc::SO_TYPE) == Some(libc::SOCK_STREAM)
+            && get(libc::SO_PROTOCOL) == Some(libc::IPPROTO_TCP)
+    }
+
+    /// Duplicate one of the caller's FDs into the supervisor (pidfd_getfd, Linux 5.6+)
+    fn dup_child_fd(tid: u32, fd: i32) -> std::result::Result<OwnedFd, i32> {
+        // pidfd_open needs the thread group leader, not the calling thread
+        let tgid = thread_group_id(tid).ok_or(libc::ESRCH)?;
+        let pidfd = unsafe { libc::syscall(libc::SYS_pidfd_open, tgid, 0) };
+        if pidfd < 0 {
+            return Err(last_errno());
+        }
+        let pidfd = unsafe { OwnedFd::from_raw_fd(pidfd as i32) };
+
+        let dup = unsafe { libc::syscall(libc::SYS_pidfd_getfd, pidfd.as_raw_fd(), fd, 0) };
+        if dup < 0 {
+            return Err(last_errno());
+        }
+        Ok(unsafe { OwnedFd::from_raw_fd(dup as i32) })
+    }
+
+    fn thread_group_id(tid: u32) -> Option<libc::pid_t> {
+        let status = std::fs::read_to_string(format!("/proc/{}/status", tid)).ok()?;
+        status
+            .lines()
+            .find_map(|l| l.strip_prefix("Tgid:"))
+            .and_then(|v| v.trim().parse().ok())
+    }
+
+    fn open_path_dir(path: &str) -> std::result::Result<OwnedFd, i32> {
+        let c_path = CString::new(path).map_err(|_| libc::EINVAL)?;
+        let fd = unsafe {
+            libc::open(
+                c_path.as_ptr(),
+                libc::O_PATH | libc::O_DIRECTORY | libc::O_CLOEXEC,
+            )
+        };
+        if fd < 0 {
+            return Err(last_errno());
+        }
+        Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+    }
+
+    /// Read a NUL-terminated string from the caller's memory
+    fn read_child_cstring(pid: u32, addr: u64) -> std::io::Result<CString> {
+        let mem = std::fs::File::open(format!("/proc/{}/mem", pid))?;
+        let mut buf = Vec::new();
+        let mut offset = addr;
+
+        // Read up to page boundaries so we never run into an unmapped page
+        // past the end of a string that is still valid
+        while buf.len() < MAX_PATH_LEN {
+            let to_page_end = 4096 - (offset % 4096) as usize;
+            let mut chunk = vec![0u8; to_page_end.min(MAX_PATH_LEN - buf.len())];
+            let n = mem.read_at(&mut chunk, offset)?;
+            if n == 0 {
+                break;
+            }
+            if let Some(nul) = chunk[..n].iter().position(|&b| b == 0) {
+                buf.extend_from_slice(&chunk[..nul]);
+                return CString::new(buf).map_err(std::io::Error::other);
+            }
+            buf.extend_from_slice(&chunk[..n]);
+            offset += n as u64;
+        }
+
+        Err(std::io::Error::from_raw_os_error(libc::ENAMETOOLONG))
+    }
+
+    /// Read the first `limit` entries of an exec's argv from the caller's
+    /// memory (fewer if argv is shorter; none if it is NULL)
+    fn read_child_argv(pid: u32, argv: u64, limit: usize) -> std::io::Result<Vec<CString>> {
+        let mut args = Vec::new();
+        if argv == 0 {
+            return Ok(args);
+        }
+        let width = std::mem::size_of::<usize>();
+        while args.len() < limit {
+            let ptr = read_child_bytes(pid, argv + (args.len() * width) as u64, width)?;
+            let mut raw = [0u8; std::mem::size_of::<usize>()];
+            raw.copy_from_slice(&ptr);
+            match usize::from_ne_bytes(raw) {
+                0 => break,
+                addr => args.push(read_child_cstring(pid, addr as u64)?),
+            }
+        }
+        Ok(args)
+    }
+
+    /// Read `len` bytes from the caller's memory
+    fn read_child_bytes(pid: u32, addr: u64, len: usize) -> std::io::Result<Vec<u8>> {
+        let mem = std::fs::File::open(format!("/proc/{}/mem", pid))?;
+        let mut buf = vec![0u8; len];
+        mem.read_exact_at(&mut buf, addr)?;
+        Ok(buf)
+    }
+
+    /// The probe for the capabilities currently in effect.
+    ///
+    /// A Landlock domain can only be narrowed by a new thread, so each time a
+    /// capability expires the next request spawns a fresh probe without it.
+    struct Probes {
+        /// Number of expired capabilities the probe was built for
+        current: Mutex<(usize, Probe)>,
+    }
+
+    impl Probes {
+        fn spawn(caps: &CapabilitySet, now: SystemTime) -> Result<Self> {
+            let probe = Probe::spawn(&caps.active_at(now))?;
+            Ok(Self {
+                current: Mutex::new((expired_count(caps, now), probe)),
+            })
+        }
+
+        /// Perform `request` under the policy in effect right now
+        fn perform(
+            &self,
+            caps: &CapabilitySet,
+            request: Arc<Request>,
+        ) -> std::result::Result<Option<OwnedFd>, i32> {
+            let now = SystemTime::now();
+            let probe = {
+                let mut current = match self.current.lock() {
+                    Ok(current) => current,
+                    Err(poisoned) => poisoned.into_inner(),
+                };
+                let expired = expired_count(caps, now);
+                if current.0 != expired {
+                    match Probe::spawn(&caps.active_at(now)) {
+                        Ok(probe) => *current = (expired, probe),
+                        Err(e) => {
+                            // Fail closed: the old probe would still allow it
+                            warn!("Failed to respawn probe: {}", e);
+                            return Err(libc::EACCES);
+                        }
+                    }
+                }
+                current.1.clone()
+            };
+            probe.perform(request)
+        }
+    }
+
+    type ProbeJob = Box<dyn FnOnce() + Send>;
+
+    /// Runs operations under a Landlock policy.
+    ///
+    /// The probe thread restricts itself with the given ruleset and spawns
+    /// one worker per operation, each inheriting that policy, so the
+    /// supervisor learns exactly what the kernel would have said to the child.
+    #[derive(Clone)]
+    struct Probe {
+        jobs: mpsc::Sender<ProbeJob>,
+    }
+
+    impl Probe {
+        fn spawn(caps: &CapabilitySet) -> Result<Self> {
+            let pending = sandbox::prepare(caps)?;
+            let (jobs, job_rx) = mpsc::channel::<ProbeJob>();
+            let (ready_tx, ready_rx) = mpsc::channel::<Result<()>>();
+
+            thread::Builder::new()
+                .name("nono-probe".to_string())
+                .spawn(move || {
+                    let applied = pending.apply_to_thread();
+                    let ok = applied.is_ok();
+                    let _ = ready_tx.send(applied);
+                    if !ok {
+                        return;
+                    }
+                    for job in job_rx {
+                        if let Err(e) = thread::Builder::new().spawn(job) {
+                            // Dropping the job drops its reply channel, failing the request
+                            warn!("Failed to spawn probe worker: {}", e);
+                        }
+                    }
+                })
+                .map_err(|e| NonoError::SandboxInit(format!("Failed to start probe: {}", e)))?;
+
+            ready_rx
+                .recv()
+                .map_err(|_| NonoError::SandboxInit("Probe thread exited".to_string()))??;
+
+            Ok(Self { jobs })
+        }
+
+        /// Perform `request` under the probe's policy
+        fn perform(&self, request: Arc<Request>) -> std::result::Result<Option<OwnedFd>, i32> {
+            let (tx, rx) = mpsc::channel();
+            let job: ProbeJob = Box::new(move || {
+                let _ = tx.send(request.perform());
+            });
+            if self.jobs.send(job).is_err() {
+                return Err(libc::EACCES);
+            }
+            rx.recv().unwrap_or(Err(libc::EACCES))
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_open_access_label() {
+            assert_eq!(open_access_label(libc::O_RDONLY), "read");
+            assert_eq!(open_access_label(libc::O_WRONLY), "write");
+            assert_eq!(open_access_label(libc::O_RDWR), "read+write");
+            assert_eq!(
+                open_access_label(libc::O_WRONLY | libc::O_CREAT | libc::O_TRUNC),
+                "write"
+            );
+            assert_eq!(
+                open_access_label(libc::O_RDONLY | libc::O_CREAT),
+                "read+write"
+            );
+        }
+
+        #[test]
+        fn test_open_access() {
+            assert_eq!(open_access(libc::O_RDONLY), FsAccess::Read);
+            assert_eq!(open_access(libc::O_WRONLY | libc::O_CREAT), FsAccess::Write);
+            assert_eq!(open_access(libc::O_RDWR), FsAccess::ReadWrite);
+        }
+
+        #[test]
+        fn test_refers_to_caller() {
+            assert!(refers_to_caller(Path::new("/proc/self/status")));
+            assert!(refers_to_caller(Path::new("/proc/1234/maps")));
+            assert!(refers_to_caller(Path::new("/dev/fd/3")));
+            assert!(refers_to_caller(Path::new("/dev/stdout")));
+            assert!(!refers_to_caller(Path::new("/home/user/.ssh/id_rsa")));
+            assert!(!refers_to_caller(Path::new("/procfs/file")));
+        }
+
+        #[test]
+        fn test_caller_view() {
+            let pid = std::process::id();
+            assert_eq!(
+                caller_view(Path::new("/proc/self/cwd/f"), pid),
+                Some(PathBuf::from(format!("/proc/{}/cwd/f", pid)))
+            );
+            assert_eq!(
+                caller_view(Path::new("/dev/fd/3"), pid),
+                Some(PathBuf::from(format!("/proc/{}/fd/3", pid)))
+            );
+            assert_eq!(
+                caller_view(Path::new("/proc/1/status"), pid),
+                Some(PathBuf::from("/proc/1/status"))
+            );
+        }
+
+        #[test]
+        fn test_resolve_target() {
+            let dir = tempfile::tempdir().unwrap();
+            let root = dir.path().canonicalize().unwrap();
+            let target = dir.path().join("target");
+            std::fs::write(&target, "x").unwrap();
+            let link = dir.path().join("link");
+            std::os::unix::fs::symlink(&target, &link).unwrap();
+
+            // Creating, removing or renaming acts on the entry itself
+            assert_eq!(resolve_target(&link, false), Some(root.join("link")));
+            assert_eq!(
+                resolve_target(&dir.path().join("new"), false),
+                Some(root.join("new"))
+            );
+            // Exec follows the symlink
+            assert_eq!(resolve_target(&link, true), Some(root.join("target")));
+            assert_eq!(resolve_target(&dir.path().join("missing"), true), None);
+        }
+
+        #[test]
+        fn test_path_syscalls_are_unique() {
+            for (i, a) in PATH_SYSCALLS.iter().enumerate() {
+                assert!(!seccomp::open_syscalls().contains(&a.nr));
+                for b in &PATH_SYSCALLS[i + 1..] {
+                    assert_ne!(a.nr, b.nr, "{} listed twice", a.operation);
+                }
+            }
+        }
+
+        #[test]
+        fn test_describe_inet_addr() {
+            let v4 = std::net::SocketAddrV4::new(std::net::Ipv4Addr::new(10, 0, 0, 1), 8080);
+            let raw = libc::sockaddr_in {
+                sin_family: libc::AF_INET as libc::sa_family_t,
+                sin_port: v4.port().to_be(),
+                sin_addr: libc::in_addr {
+                    s_addr: u32::from_ne_bytes(v4.ip().octets()),
+                },
+                sin_zero: [0; 8],
+            };
+            let bytes = unsafe {
+                std::slice::from_raw_parts(
+                    &raw as *const libc::sockaddr_in as *const u8,
+                    std::mem::size_of::<libc::sockaddr_in>(),
+                )
+            };
+            assert_eq!(
+                describe_inet_addr(bytes),
+                Some(("10.0.0.1:8080".to_string(), 8080))
+            );
+
+            let unix_family = (libc::AF_UNIX as u16).to_ne_bytes();
+            assert_eq!(
+                describe_inet_addr(&[unix_family[0], unix_family[1], 0, 0]),
+                None
+            );
+        }
+
+        #[test]
+        fn test_blocked_exec() {
+            let dir = tempfile::tempdir().unwrap();
+            let tool = dir.path().join("tool");
+            std::fs::write(&tool, "").unwrap();
+            let alias = dir.path().join("rm");
+            std::os::unix::fs::symlink(&tool, &alias).unwrap();
+
+            let exec_args = |path: &Path, args: &[&str], caps: &CapabilitySet| {
+                let path = CString::new(path.as_os_str().as_bytes()).unwrap();
+                let args: Vec<CString> = args.iter().map(|a| CString::new(*a).unwrap()).collect();
+                let mut argv: Vec<*const libc::c_char> = args.iter().map(|a| a.as_ptr()).collect();
+                argv.push(std::ptr::null());
+                let notif = Notification {
+                    id: 0,
+                    pid: std::process::id(),
+                    nr: libc::SYS_execve,
+                    args: [path.as_ptr() as u64, argv.as_ptr() as u64, 0, 0, 0, 0],
+                };
+                blocked_exec(&notif, caps)
+                    .expect("readable")
+                    .map(|(cmd, _, _)| cmd)
+            };
+            let exec =
+                |path: &Path, arg0: &str, caps: &CapabilitySet| exec_args(path, &[arg0], caps);
+
+            let caps = CapabilitySet::default();
+            assert_eq!(exec(&tool, "tool", &caps), None);
+            // The path, what it resolves to and argv[0] all count
+            assert_eq!(exec(&alias, "tool", &caps).as_deref(), Some("rm"));
+            assert_eq!(exec(&tool, "rm", &caps).as_deref(), Some("rm"));
+
+            let mut caps = CapabilitySet::default();
+            caps.allowed_commands.push("rm".to_string());
+            caps.blocked_commands.push("tool".to_string());
+            assert_eq!(exec(&alias, "rm", &caps).as_deref(), Some("tool"));
+
+            // Commands with rules have their arguments checked
+            let mut caps = CapabilitySet::default();
+            caps.command_rules.push((
+                "tool".to_string(),
+                config::security_lists::CommandRule {
+                    deny_args: vec!["-r".to_string()],
+                    ..Default::default()
+                },
+            ));
+            assert_eq!(exec_args(&tool, &["tool", "-f", "x"], &caps), None);
+            assert_eq!(
+                exec_args(&tool, &["tool", "-fr", "x"], &caps).as_deref(),
+                Some("tool")
+            );
+        }
+
+        #[test]
+        fn test_read_child_argv() {
+            let arg0 = CString::new("busybox").unwrap();
+            let arg1 = CString::new("rm").unwrap();
+            let argv = [arg0.as_ptr(), arg1.as_ptr(), std::ptr::null()];
+            let pid = std::process::id();
+            assert_eq!(
+                read_child_argv(pid, argv.as_ptr() as u64, 1).expect("readable"),
+                vec![arg0.clone()]
+            );
+            assert_eq!(
+                read_child_argv(pid, argv.as_ptr() as u64, 10).expect("readable"),
+                vec![arg0.clone(), arg1.clone()]
+            );
+            let empty: [*const libc::c_char; 1] = [std::ptr::null()];
+            assert!(read_child_argv(pid, empty.as_ptr() as u64, 10)
+                .expect("readable")
+                .is_empty());
+            assert!(read_child_argv(pid, 0, 10).expect("null argv").is_empty());
+        }
+
+        #[test]
+        fn test_read_child_cstring_self() {
+            let path = CString::new("/etc/hostname").unwrap();
+            let read =
+                read_child_cstring(std::process::id(), path.as_ptr() as u64).expect("readable");
+            assert_eq!(read, path);
+        }
+    }
+}
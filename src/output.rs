@@ -1,6 +1,6 @@
 //! CLI output styling for nono
 
-use crate::capability::{CapabilitySet, FsAccess};
+use crate::capability::{format_duration, format_ports, CapabilitySet, FsAccess};
 use crate::error::{NonoError, Result};
 use colored::Colorize;
 use rand::seq::IndexedRandom;
@@ -51,30 +51,195 @@ pub fn print_capabilities(caps: &CapabilitySet, silent: bool) {
             let kind = cap.kind_label();
             let access_str = format!("{}", cap.access);
             let access_colored = match cap.access {
-                crate::capability::FsAccess::Read => access_str.green(),
+                crate::capability::FsAccess::Read
+                | crate::capability::FsAccess::ReadNoExec
+                | crate::capability::FsAccess::Execute => access_str.green(),
                 crate::capability::FsAccess::Write => access_str.yellow(),
                 crate::capability::FsAccess::ReadWrite => access_str.truecolor(204, 102, 0), // orange
             };
+            let ttl = cap
+                .ttl
+                .map(|ttl| format!(" for {}", format_duration(ttl)))
+                .unwrap_or_default();
             eprintln!(
-                "    {} [{}] ({})",
+                "    {} [{}] ({}){}",
                 cap.resolved.display().to_string().white(),
                 access_colored,
-                kind.truecolor(150, 150, 150)
+                kind.truecolor(150, 150, 150),
+                ttl.yellow()
+            );
+        }
+        for path in &caps.deny {
+            eprintln!(
+                "    {} [{}]",
+                path.display().to_string().white(),
+                "denied".red()
             );
         }
     }
 
+    // Device ioctl allowlist (Linux only)
+    if cfg!(target_os = "linux") && !caps.ioctl_devices.is_empty() {
+        let devices: Vec<String> = caps
+            .ioctl_devices
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        eprintln!("  {}", "Devices:".white());
+        eprintln!("    ioctl: {}", devices.join(", ").yellow());
+    }
+
     // Network status
     eprintln!("  {}", "Network:".white());
     if caps.net_block {
         eprintln!("    outbound: {}", "blocked".red());
+    } else if caps.has_port_rules() {
+        if caps.has_host_allowlist() {
+            eprintln!(
+                "    hosts: {} {}",
+                caps.allow_hosts.join(", ").yellow(),
+                "(via local proxy)".truecolor(150, 150, 150)
+            );
+        }
+        eprintln!(
+            "    tcp connect: {}",
+            format_ports(&caps.tcp_connect_ports).yellow()
+        );
+        eprintln!(
+            "    tcp bind: {}",
+            format_ports(&caps.tcp_bind_ports).yellow()
+        );
     } else {
         eprintln!("    outbound: {}", "allowed".green());
     }
 
+    // Command allow/block lists from the profile and CLI, and argument rules
+    if !caps.allowed_commands.is_empty()
+        || !caps.blocked_commands.is_empty()
+        || !caps.command_rules.is_empty()
+    {
+        eprintln!("  {}", "Commands:".white());
+        if !caps.allowed_commands.is_empty() {
+            eprintln!("    allowed: {}", caps.allowed_commands.join(", ").yellow());
+        }
+        if !caps.blocked_commands.is_empty() {
+            eprintln!("    blocked: {}", caps.blocked_commands.join(", ").red());
+        }
+        for (command, rule) in &caps.command_rules {
+            eprintln!("    {}: {}", command, rule.to_string().yellow());
+        }
+    }
+
+    // Syscall filter changes from the profile (Linux only); the full
+    // filter is listed by --dry-run
+    if cfg!(target_os = "linux")
+        && (!caps.allowed_syscalls.is_empty() || !caps.denied_syscalls.is_empty())
+    {
+        eprintln!("  {}", "Syscalls:".white());
+        if !caps.allowed_syscalls.is_empty() {
+            eprintln!("    allowed: {}", caps.allowed_syscalls.join(", ").yellow());
+        }
+        if !caps.denied_syscalls.is_empty() {
+            eprintln!("    denied: {}", caps.denied_syscalls.join(", ").red());
+        }
+    }
+
+    // IPC scoping (Linux only) is shown when the profile lifts it
+    if cfg!(target_os = "linux") && (caps.allow_abstract_sockets || caps.allow_signals) {
+        eprintln!("  {}", "IPC:".white());
+        if caps.allow_abstract_sockets {
+            eprintln!(
+                "    abstract sockets outside the sandbox: {}",
+                "allowed".yellow()
+            );
+        }
+        if caps.allow_signals {
+            eprintln!(
+                "    signals to processes outside the sandbox: {}",
+                "allowed".yellow()
+            );
+        }
+    }
+
+    // Resource limits from the profile and CLI
+    if !caps.limits.is_empty() {
+        eprintln!("  {}", "Limits:".white());
+        eprintln!("    {}", caps.limits.describe().join(", ").yellow());
+    }
+
+    let config = crate::config::effective_config();
+    if let Some(ref policy) = config.system_policy {
+        eprintln!("  {}", "System policy:".white());
+        eprintln!(
+            "    {}/policy.toml v{} {}",
+            crate::config::system::SYSTEM_CONFIG_DIR,
+            policy.meta.version,
+            format!("(signed by {})", policy.signed_by).truecolor(150, 150, 150)
+        );
+    }
+
+    // Acknowledged exceptions from ~/.config/nono/config.toml
+    let overrides = config.applied_overrides();
+    if !overrides.is_empty() {
+        eprintln!("  {}", "Overrides (user config):".white());
+        for applied in &overrides {
+            eprintln!("    {}", applied.to_string().yellow());
+        }
+    }
+
     eprintln!();
 }
 
+/// Print the effective security lists for `nono lists show`, optionally only
+/// the categories whose name contains `filter`
+pub fn print_security_lists(config: &crate::config::EffectiveConfig, filter: Option<&str>) {
+    let source = match config.lists_update_signer {
+        Some(ref signer) => format!("update signed by {}", signer),
+        None => "embedded".to_string(),
+    };
+    println!(
+        "{} v{} ({})",
+        "Security lists".white().bold(),
+        config.security_lists_version,
+        source
+    );
+
+    let matches = |category: &str| {
+        filter.map_or(true, |f| {
+            category.to_lowercase().contains(&f.to_lowercase())
+        })
+    };
+    for (title, categories) in [
+        ("Sensitive paths", &config.sensitive_categories),
+        ("Dangerous commands", &config.command_categories),
+        ("Command rules", &config.command_rule_categories),
+        ("Dangerous syscalls", &config.syscall_categories),
+        ("System read paths", &config.system_read_categories),
+    ] {
+        let shown: Vec<_> = categories
+            .iter()
+            .filter(|(category, _)| matches(category))
+            .collect();
+        if shown.is_empty() {
+            continue;
+        }
+        println!();
+        println!("{}:", title.white().bold());
+        for (category, entries) in shown {
+            // Categories from other sources are labelled "name (source)"
+            let label = if category.ends_with(')') {
+                category.clone()
+            } else {
+                format!("{} (embedded)", category)
+            };
+            println!("  {}", label);
+            for entry in entries {
+                println!("    {}", entry.truecolor(150, 150, 150));
+            }
+        }
+    }
+}
+
 /// Print status message for applying sandbox
 pub fn print_applying_sandbox(silent: bool) {
     if silent {
@@ -86,6 +251,32 @@ pub fn print_applying_sandbox(silent: bool) {
     );
 }
 
+/// Print what the kernel can enforce (`nono run -v`)
+pub fn print_enforcement_report(report: &crate::sandbox::EnforcementReport, silent: bool) {
+    if silent {
+        return;
+    }
+    eprintln!("{}", "Enforcement:".white().bold());
+    eprintln!("  {}", report.backend);
+    let mut any_missing = false;
+    for protection in report.missing() {
+        any_missing = true;
+        let note = if protection.requested {
+            "not enforced".red()
+        } else {
+            "unavailable, not needed".truecolor(150, 150, 150)
+        };
+        eprintln!(
+            "    missing: {} ({}) {}",
+            protection.name, protection.description, note
+        );
+    }
+    if !any_missing {
+        eprintln!("    {}", "all protections available".green());
+    }
+    eprintln!();
+}
+
 /// Print success message when sandbox is active
 pub fn print_sandbox_active(silent: bool) {
     if silent {
@@ -98,8 +289,20 @@ pub fn print_sandbox_active(silent: bool) {
     eprintln!();
 }
 
-/// Print dry run message
-pub fn print_dry_run(program: &OsStr, cmd_args: &[OsString], silent: bool) {
+/// Print message when the supervisor is running and only the child will be sandboxed
+pub fn print_supervisor_active(silent: bool) {
+    if silent {
+        return;
+    }
+    eprintln!(
+        "{}",
+        "Supervisor active. Restrictions apply to the command only.".green()
+    );
+    eprintln!();
+}
+
+/// Print dry run message, with the syscall filter the command would run under
+pub fn print_dry_run(program: &OsStr, cmd_args: &[OsString], caps: &CapabilitySet, silent: bool) {
     if silent {
         return;
     }
@@ -116,6 +319,14 @@ pub fn print_dry_run(program: &OsStr, cmd_args: &[OsString], silent: bool) {
         "Dry run mode - sandbox would be applied with above capabilities".yellow()
     );
     eprintln!("Command: {:?}", command);
+    if cfg!(target_os = "linux") {
+        let denied = crate::config::denied_syscalls(&caps.allowed_syscalls, &caps.denied_syscalls);
+        if denied.is_empty() {
+            eprintln!("Syscall filter: none");
+        } else {
+            eprintln!("Syscall filter: deny (EPERM) {}", denied.join(", "));
+        }
+    }
 }
 
 /// Prompt the user to confirm sharing the current working directory.
@@ -135,7 +346,7 @@ pub fn prompt_cwd_sharing(cwd: &Path, access: &FsAccess) -> Result<bool> {
 
     let access_str = format!("{}", access);
     let access_colored = match access {
-        FsAccess::Read => access_str.green(),
+        FsAccess::Read | FsAccess::ReadNoExec | FsAccess::Execute => access_str.green(),
         FsAccess::Write => access_str.yellow(),
         FsAccess::ReadWrite => access_str.truecolor(204, 102, 0),
     };
@@ -3,7 +3,8 @@
 //! These profiles are trusted by default and don't require --trust-unsigned.
 
 use super::{
-    FilesystemConfig, HookConfig, HooksConfig, NetworkConfig, Profile, ProfileMeta, SecretsConfig,
+    CommandsConfig, DevicesConfig, FilesystemConfig, HookConfig, HooksConfig, IpcConfig,
+    LimitsConfig, NetworkConfig, Profile, ProfileMeta, SecretsConfig, SyscallsConfig,
     WorkdirAccess, WorkdirConfig,
 };
 use std::collections::HashMap;
@@ -53,30 +54,39 @@ fn claude_code() -> Profile {
             // ~/.vscode: VS Code extensions directory
             // ~/Library/Application Support/Code: VS Code app data (required for extension install)
             allow: vec![
-                "$HOME/.claude".to_string(),
-                "$HOME/.vscode".to_string(),
-                "$HOME/Library/Application Support/Code".to_string(),
+                "$HOME/.claude".into(),
+                "$HOME/.vscode".into(),
+                "$HOME/Library/Application Support/Code".into(),
             ],
             read: vec![],
             write: vec![],
             // ~/.claude.json: agent writes settings/state here
-            allow_file: vec!["$HOME/.claude.json".to_string()],
+            allow_file: vec!["$HOME/.claude.json".into()],
             // macOS Keychain: OAuth token read for authentication
             // ~/.gitconfig, ~/.gitignore_global: git configuration for repository operations
             read_file: vec![
-                "$HOME/Library/Keychains/login.keychain-db".to_string(),
-                "$HOME/.gitconfig".to_string(),
-                "$HOME/.gitignore_global".to_string(),
+                "$HOME/Library/Keychains/login.keychain-db".into(),
+                "$HOME/.gitconfig".into(),
+                "$HOME/.gitignore_global".into(),
             ],
             write_file: vec![],
+            read_noexec: vec![],
+            exec_only: vec![],
+            deny: vec![],
         },
-        network: NetworkConfig { block: false },
+        devices: DevicesConfig::default(),
+        network: NetworkConfig::default(),
+        commands: CommandsConfig::default(),
+        syscalls: SyscallsConfig::default(),
+        ipc: IpcConfig::default(),
+        limits: LimitsConfig::default(),
         secrets: SecretsConfig::default(),
         workdir: WorkdirConfig {
             access: WorkdirAccess::ReadWrite,
         },
         hooks: HooksConfig { hooks },
         interactive: true, // Claude Code has interactive TUI
+        signed_by: None,
     }
 }
 
@@ -92,24 +102,33 @@ fn openclaw() -> Profile {
         },
         filesystem: FilesystemConfig {
             allow: vec![
-                "$HOME/.openclaw".to_string(),
-                "$HOME/.config/openclaw".to_string(),
-                "$HOME/.local".to_string(),
-                "$TMPDIR/openclaw-$UID".to_string(),
+                "$HOME/.openclaw".into(),
+                "$HOME/.config/openclaw".into(),
+                "$HOME/.local".into(),
+                "$TMPDIR/openclaw-$UID".into(),
             ],
             read: vec![],
             write: vec![],
             allow_file: vec![],
             read_file: vec![],
             write_file: vec![],
+            read_noexec: vec![],
+            exec_only: vec![],
+            deny: vec![],
         },
-        network: NetworkConfig { block: false },
+        devices: DevicesConfig::default(),
+        network: NetworkConfig::default(),
+        commands: CommandsConfig::default(),
+        syscalls: SyscallsConfig::default(),
+        ipc: IpcConfig::default(),
+        limits: LimitsConfig::default(),
         secrets: SecretsConfig::default(),
         workdir: WorkdirConfig {
             access: WorkdirAccess::Read,
         },
         hooks: HooksConfig::default(),
         interactive: false,
+        signed_by: None,
     }
 }
 
@@ -125,31 +144,40 @@ fn opencode() -> Profile {
         },
         filesystem: FilesystemConfig {
             allow: vec![
-                "$HOME/.config/opencode".to_string(),
-                "$HOME/.cache/opencode".to_string(),
-                "$HOME/.local/share/opencode".to_string(),
-                "$HOME/.local/state/opencode".to_string(),
-                "$HOME/.opencode".to_string(),
-                "$HOME/.npm".to_string(),
-                "$HOME/.nvm".to_string(),
+                "$HOME/.config/opencode".into(),
+                "$HOME/.cache/opencode".into(),
+                "$HOME/.local/share/opencode".into(),
+                "$HOME/.local/state/opencode".into(),
+                "$HOME/.opencode".into(),
+                "$HOME/.npm".into(),
+                "$HOME/.nvm".into(),
                 // /tmp required: opencode writes directly to $TMPDIR with dynamic
                 // filenames (e.g., {timestamp}.md, opencode-clipboard.png) rather
                 // than using a subdirectory. Cannot grant specific paths.
-                "/tmp".to_string(),
+                "/tmp".into(),
             ],
-            read: vec!["$HOME/.config/git".to_string()],
+            read: vec!["$HOME/.config/git".into()],
             write: vec![],
             allow_file: vec![],
             read_file: vec![],
             write_file: vec![],
+            read_noexec: vec![],
+            exec_only: vec![],
+            deny: vec![],
         },
-        network: NetworkConfig { block: false },
+        devices: DevicesConfig::default(),
+        network: NetworkConfig::default(),
+        commands: CommandsConfig::default(),
+        syscalls: SyscallsConfig::default(),
+        ipc: IpcConfig::default(),
+        limits: LimitsConfig::default(),
         secrets: SecretsConfig::default(),
         workdir: WorkdirConfig {
             access: WorkdirAccess::ReadWrite,
         },
         hooks: HooksConfig::default(),
         interactive: true,
+        signed_by: None,
     }
 }
 
@@ -164,7 +192,7 @@ mod tests {
         assert_eq!(profile.meta.name, "claude-code");
         assert!(!profile.network.block); // network allowed
         assert_eq!(profile.workdir.access, WorkdirAccess::ReadWrite);
-        assert!(!profile.filesystem.allow.contains(&"$WORKDIR".to_string()));
+        assert!(!profile.filesystem.allow.contains(&"$WORKDIR".into()));
     }
 
     #[test]
@@ -172,10 +200,7 @@ mod tests {
         let profile = get_builtin("openclaw").unwrap();
         assert_eq!(profile.meta.name, "openclaw");
         assert!(!profile.network.block); // network allowed
-        assert!(profile
-            .filesystem
-            .allow
-            .contains(&"$HOME/.openclaw".to_string()));
+        assert!(profile.filesystem.allow.contains(&"$HOME/.openclaw".into()));
     }
 
     #[test]
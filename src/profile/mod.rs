@@ -5,10 +5,12 @@
 //! into the binary) or user-defined (in ~/.config/nono/profiles/).
 
 mod builtin;
+pub mod signing;
 
+use crate::config::security_lists::CommandRule;
 use crate::error::{NonoError, Result};
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -23,32 +25,93 @@ pub struct ProfileMeta {
     pub description: Option<String>,
     #[serde(default)]
     pub author: Option<String>,
-    ///  signature support
+    /// Embedded minisign signature over the profile's canonical form
+    /// (see `signing::canonical_form`)
     #[serde(default)]
     pub signature: Option<String>,
 }
 
+/// A path in a `[filesystem]` list.
+///
+/// Either a plain string or a table with an expiry:
+/// `{ path = "/tmp", ttl = "5m" }`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(from = "RawPathEntry")]
+pub struct PathEntry {
+    pub path: String,
+    /// How long the grant lasts after the session starts (e.g. "30s", "5m", "2h")
+    pub ttl: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawPathEntry {
+    Path(String),
+    Table {
+        path: String,
+        #[serde(default)]
+        ttl: Option<String>,
+    },
+}
+
+impl From<RawPathEntry> for PathEntry {
+    fn from(raw: RawPathEntry) -> Self {
+        match raw {
+            RawPathEntry::Path(path) => Self { path, ttl: None },
+            RawPathEntry::Table { path, ttl } => Self { path, ttl },
+        }
+    }
+}
+
+impl From<&str> for PathEntry {
+    fn from(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            ttl: None,
+        }
+    }
+}
+
 /// Filesystem configuration in a profile
 #[derive(Debug, Clone, Default, Deserialize)]
 pub struct FilesystemConfig {
     /// Directories with read+write access
     #[serde(default)]
-    pub allow: Vec<String>,
+    pub allow: Vec<PathEntry>,
     /// Directories with read-only access
     #[serde(default)]
-    pub read: Vec<String>,
+    pub read: Vec<PathEntry>,
     /// Directories with write-only access
     #[serde(default)]
-    pub write: Vec<String>,
+    pub write: Vec<PathEntry>,
     /// Single files with read+write access
     #[serde(default)]
-    pub allow_file: Vec<String>,
+    pub allow_file: Vec<PathEntry>,
     /// Single files with read-only access
     #[serde(default)]
-    pub read_file: Vec<String>,
+    pub read_file: Vec<PathEntry>,
     /// Single files with write-only access
     #[serde(default)]
-    pub write_file: Vec<String>,
+    pub write_file: Vec<PathEntry>,
+    /// Directories whose files can be read but not executed
+    #[serde(default)]
+    pub read_noexec: Vec<PathEntry>,
+    /// Directories whose files can be executed but not read or listed
+    #[serde(default)]
+    pub exec_only: Vec<PathEntry>,
+    /// Files or directories hidden from the sandbox, even inside the
+    /// directories granted above
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+/// Device configuration in a profile
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DevicesConfig {
+    /// Devices, or directories of devices, that may receive ioctls (Linux).
+    /// All other device ioctls are denied.
+    #[serde(default)]
+    pub ioctl: Vec<String>,
 }
 
 /// Network configuration in a profile
@@ -57,7 +120,84 @@ pub struct NetworkConfig {
     /// Block network access (network allowed by default; true = blocked)
     #[serde(default)]
     pub block: bool,
-    // Future: dns_only, proxy_allow
+    /// TCP ports the sandbox may connect to. Enables port filtering.
+    #[serde(default)]
+    pub connect_ports: Vec<u16>,
+    /// TCP ports the sandbox may bind and listen on. Enables port filtering.
+    #[serde(default)]
+    pub bind_ports: Vec<u16>,
+    /// Hosts reachable through nono's filtering proxy (exact or `*.domain`).
+    /// When set, all other outbound TCP goes nowhere.
+    #[serde(default)]
+    pub allow_hosts: Vec<String>,
+    // Future: dns_only
+}
+
+/// Command allow/block lists in a profile, merged with `--allow-command`
+/// and `--block-command`, and argument rules for commands
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CommandsConfig {
+    /// Commands allowed even though the dangerous commands list blocks them
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Additional commands to block
+    #[serde(default)]
+    pub block: Vec<String>,
+    /// Argument rules by command name (`[commands.rules]`), added to those
+    /// of the security lists. Paths may use the same variables as
+    /// `[filesystem]`.
+    #[serde(default)]
+    pub rules: BTreeMap<String, CommandRule>,
+}
+
+/// Syscall filter configuration in a profile (Linux)
+///
+/// Changes the dangerous syscalls list of the security lists for this
+/// profile.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SyscallsConfig {
+    /// Syscalls allowed even though the dangerous syscalls list denies them
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Additional syscalls to deny
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+/// IPC configuration in a profile
+///
+/// On Linux the sandbox cannot reach abstract UNIX sockets (D-Bus, X11,
+/// other agents) or signal processes outside it. These switches lift that
+/// for apps that need it, e.g. the session bus.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct IpcConfig {
+    /// Allow connecting to abstract UNIX sockets created outside the sandbox
+    #[serde(default)]
+    pub allow_abstract_sockets: bool,
+    /// Allow sending signals to processes outside the sandbox
+    #[serde(default)]
+    pub allow_signals: bool,
+}
+
+/// Resource limits in a profile, overridden limit by limit by
+/// `--max-memory` and the other `--max-*` flags
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LimitsConfig {
+    /// Address space, e.g. "2G"
+    #[serde(default)]
+    pub memory: Option<String>,
+    /// Processes of the user, including those outside the sandbox
+    #[serde(default)]
+    pub processes: Option<u64>,
+    /// CPU time, e.g. "10m"
+    #[serde(default)]
+    pub cpu_time: Option<String>,
+    /// Size of any file written, e.g. "1G"
+    #[serde(default)]
+    pub file_size: Option<String>,
+    /// Open file descriptors
+    #[serde(default)]
+    pub open_files: Option<u64>,
 }
 
 /// Secrets configuration in a profile
@@ -133,8 +273,18 @@ pub struct Profile {
     #[serde(default)]
     pub filesystem: FilesystemConfig,
     #[serde(default)]
+    pub devices: DevicesConfig,
+    #[serde(default)]
     pub network: NetworkConfig,
     #[serde(default)]
+    pub commands: CommandsConfig,
+    #[serde(default)]
+    pub syscalls: SyscallsConfig,
+    #[serde(default)]
+    pub ipc: IpcConfig,
+    #[serde(default)]
+    pub limits: LimitsConfig,
+    #[serde(default)]
     pub secrets: SecretsConfig,
     #[serde(default)]
     pub workdir: WorkdirConfig,
@@ -143,12 +293,15 @@ pub struct Profile {
     /// App has interactive UI that needs TTY preserved (implies --exec mode)
     #[serde(default)]
     pub interactive: bool,
+    /// Name of the trusted key whose signature was verified on load
+    #[serde(skip)]
+    pub signed_by: Option<String>,
 }
 
 impl Profile {
-    /// Check if this profile has a signature
+    /// Check if this profile carries a verified signature
     pub fn is_signed(&self) -> bool {
-        self.meta.signature.is_some()
+        self.signed_by.is_some()
     }
 }
 
@@ -158,7 +311,7 @@ impl Profile {
 /// 1. User profiles from ~/.config/nono/profiles/<name>.toml (allows customization)
 /// 2. Built-in profiles (compiled into binary, fallback)
 ///
-/// User profiles require --trust-unsigned unntil signed (planned feature)
+/// User profiles require --trust-unsigned unless signed by a trusted key
 pub fn load_profile(name: &str, trust_unsigned: bool) -> Result<Profile> {
     // Validate profile name (alphanumeric + hyphen only)
     if !is_valid_profile_name(name) {
@@ -172,7 +325,19 @@ pub fn load_profile(name: &str, trust_unsigned: bool) -> Result<Profile> {
     let profile_path = get_user_profile_path(name)?;
     if profile_path.exists() {
         tracing::info!("Loading user profile from: {}", profile_path.display());
-        let profile = load_from_file(&profile_path)?;
+        let content = read_profile(&profile_path)?;
+        let mut profile = parse_profile(&content)?;
+
+        // A bad signature is an error; a missing one needs --trust-unsigned
+        profile.signed_by = signing::verify_profile(
+            &profile_path,
+            &content,
+            profile.meta.signature.as_deref(),
+            &signing::trusted_keys()?,
+        )?;
+        if let Some(ref signer) = profile.signed_by {
+            tracing::info!("Profile {} is signed by {}", name, signer);
+        }
 
         // Require --trust-unsigned for unsigned user profiles
         if !profile.is_signed() && !trust_unsigned {
@@ -191,14 +356,35 @@ pub fn load_profile(name: &str, trust_unsigned: bool) -> Result<Profile> {
     Err(NonoError::ProfileNotFound(name.to_string()))
 }
 
-/// Load a profile from a TOML file
-fn load_from_file(path: &Path) -> Result<Profile> {
-    let content = fs::read_to_string(path).map_err(|e| NonoError::ProfileRead {
+/// Read a profile file
+fn read_profile(path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(|e| NonoError::ProfileRead {
         path: path.to_path_buf(),
         source: e,
-    })?;
+    })
+}
 
-    toml::from_str(&content).map_err(|e| NonoError::ProfileParse(e.to_string()))
+/// Parse a profile from TOML
+fn parse_profile(content: &str) -> Result<Profile> {
+    toml::from_str(content).map_err(|e| NonoError::ProfileParse(e.to_string()))
+}
+
+/// Resolve a profile given by name (in ~/.config/nono/profiles/) or by path
+pub fn resolve_user_profile(name_or_path: &str) -> Result<PathBuf> {
+    let path = if name_or_path.contains('/') || name_or_path.ends_with(".toml") {
+        PathBuf::from(name_or_path)
+    } else if is_valid_profile_name(name_or_path) {
+        get_user_profile_path(name_or_path)?
+    } else {
+        return Err(NonoError::ProfileParse(format!(
+            "Invalid profile name '{}': must be alphanumeric with hyphens only",
+            name_or_path
+        )));
+    };
+    if !path.exists() {
+        return Err(NonoError::ProfileNotFound(name_or_path.to_string()));
+    }
+    Ok(path)
 }
 
 /// Get the path to a user profile
@@ -376,6 +562,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_filesystem_entries_with_ttl() {
+        let toml_str = r#"
+            [meta]
+            name = "test-profile"
+
+            [filesystem]
+            allow = ["$WORKDIR", { path = "/tmp", ttl = "5m" }]
+            read_file = [{ path = "$HOME/.gitconfig" }]
+        "#;
+
+        let profile: Profile = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            profile.filesystem.allow,
+            vec![
+                PathEntry::from("$WORKDIR"),
+                PathEntry {
+                    path: "/tmp".to_string(),
+                    ttl: Some("5m".to_string()),
+                },
+            ]
+        );
+        assert_eq!(profile.filesystem.read_file[0].ttl, None);
+    }
+
     #[test]
     fn test_empty_secrets_config() {
         let toml_str = r#"
@@ -439,4 +650,26 @@ mod tests {
         let profile: Profile = toml::from_str(toml_str).unwrap();
         assert_eq!(profile.workdir.access, WorkdirAccess::None);
     }
+
+    #[test]
+    fn test_network_port_rules_parsing() {
+        let toml_str = r#"
+            [meta]
+            name = "test-profile"
+
+            [network]
+            connect_ports = [443, 80]
+            bind_ports = [3000]
+            allow_hosts = ["api.anthropic.com", "*.github.com"]
+        "#;
+
+        let profile: Profile = toml::from_str(toml_str).unwrap();
+        assert!(!profile.network.block);
+        assert_eq!(profile.network.connect_ports, vec![443, 80]);
+        assert_eq!(profile.network.bind_ports, vec![3000]);
+        assert_eq!(
+            profile.network.allow_hosts,
+            vec!["api.anthropic.com", "*.github.com"]
+        );
+    }
 }
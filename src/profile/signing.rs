@@ -0,0 +1,266 @@
+//! Profile signatures
+//!
+//! A user profile is signed with minisign, either with a detached
+//! `<name>.toml.minisig` next to it, or with the signature embedded as
+//! `[meta] signature` over the profile's canonical form. Signatures are
+//! checked against the keys under `[trusted_keys]` in the user config.
+
+use crate::config::{user, verify};
+use crate::error::{NonoError, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub use crate::config::user::{trusted_keys, TrustedKey};
+
+/// Path of the detached signature for `profile_path`
+pub fn detached_signature_path(profile_path: &Path) -> PathBuf {
+    let mut path = profile_path.as_os_str().to_owned();
+    path.push(".minisig");
+    PathBuf::from(path)
+}
+
+/// Canonical form of a profile, which embedded signatures cover: the TOML
+/// re-serialized with sorted keys and without `meta.signature`
+pub fn canonical_form(content: &str) -> Result<String> {
+    let mut table: toml::Table = toml::from_str(content)
+        .map_err(|e| NonoError::ProfileParse(format!("Failed to parse profile: {}", e)))?;
+
+    if let Some(toml::Value::Table(meta)) = table.get_mut("meta") {
+        meta.remove("signature");
+        if meta.is_empty() {
+            table.remove("meta");
+        }
+    }
+
+    toml::to_string(&table)
+        .map_err(|e| NonoError::ProfileParse(format!("Failed to serialize profile: {}", e)))
+}
+
+/// Verify the signature of the profile at `path` with `content`.
+///
+/// Returns the name of the trusted key that signed it, or None if the
+/// profile carries no signature. A signature that does not verify against
+/// any trusted key is an error, even with --trust-unsigned.
+pub fn verify_profile(
+    path: &Path,
+    content: &str,
+    embedded: Option<&str>,
+    keys: &[TrustedKey],
+) -> Result<Option<String>> {
+    let sig_path = detached_signature_path(path);
+    let (data, signature) = if sig_path.exists() {
+        let signature = fs::read_to_string(&sig_path).map_err(|e| NonoError::ProfileRead {
+            path: sig_path.clone(),
+            source: e,
+        })?;
+        (content.to_string(), signature)
+    } else if let Some(signature) = embedded {
+        (canonical_form(content)?, signature.to_string())
+    } else {
+        return Ok(None);
+    };
+
+    keys.iter()
+        .find(|(_, key)| verify::verify_minisign(data.as_bytes(), &signature, key).is_ok())
+        .map(|(name, _)| Some(name.clone()))
+        .ok_or_else(|| NonoError::SignatureInvalid {
+            reason: format!(
+                "profile {} is not signed by any trusted key (see [trusted_keys] in {})",
+                path.display(),
+                user::user_config_path()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_else(|_| "the user config".to_string())
+            ),
+        })
+}
+
+/// Sign the profile at `path` with the `minisign` tool.
+///
+/// Writes a detached `<path>.minisig`, or with `embed`, rewrites the profile
+/// in canonical form with the signature in `[meta]` (comments are lost).
+/// Returns the file holding the signature.
+pub fn sign_profile(path: &Path, key: Option<&Path>, embed: bool) -> Result<PathBuf> {
+    let minisign = which::which("minisign").map_err(|_| {
+        NonoError::ProfileSign(
+            "minisign not found in PATH (see https://jedisct1.github.io/minisign/)".to_string(),
+        )
+    })?;
+    let content = fs::read_to_string(path).map_err(|e| NonoError::ProfileRead {
+        path: path.to_path_buf(),
+        source: e,
+    })?;
+
+    let run = |message: &Path, signature: &Path| -> Result<()> {
+        let mut cmd = Command::new(&minisign);
+        cmd.arg("-S")
+            .arg("-m")
+            .arg(message)
+            .arg("-x")
+            .arg(signature);
+        if let Some(key) = key {
+            cmd.arg("-s").arg(key);
+        }
+        let status = cmd
+            .status()
+            .map_err(|e| NonoError::ProfileSign(format!("failed to run minisign: {}", e)))?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(NonoError::ProfileSign(format!(
+                "minisign exited with {}",
+                status
+            )))
+        }
+    };
+
+    let sig_path = detached_signature_path(path);
+    if !embed {
+        run(path, &sig_path)?;
+        return Ok(sig_path);
+    }
+
+    // A stale detached signature would take precedence over the embedded one
+    if sig_path.exists() {
+        fs::remove_file(&sig_path).map_err(|e| NonoError::ConfigWrite {
+            path: sig_path.clone(),
+            source: e,
+        })?;
+    }
+
+    let canonical = canonical_form(&content)?;
+    let dir = tempfile_dir()?;
+    let message = dir.join("profile.toml");
+    let signature_file = dir.join("profile.toml.minisig");
+    let result = fs::write(&message, &canonical)
+        .map_err(|e| NonoError::ConfigWrite {
+            path: message.clone(),
+            source: e,
+        })
+        .and_then(|()| run(&message, &signature_file))
+        .and_then(|()| {
+            fs::read_to_string(&signature_file).map_err(|e| NonoError::ConfigRead {
+                path: signature_file.clone(),
+                source: e,
+            })
+        });
+    let _ = fs::remove_dir_all(&dir);
+    let signature = result?;
+
+    let mut table: toml::Table = toml::from_str(&canonical)
+        .map_err(|e| NonoError::ProfileParse(format!("Failed to parse profile: {}", e)))?;
+    let meta = table
+        .entry("meta")
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+    if let toml::Value::Table(meta) = meta {
+        meta.insert("signature".to_string(), toml::Value::String(signature));
+    }
+    let signed = toml::to_string(&table)
+        .map_err(|e| NonoError::ProfileParse(format!("Failed to serialize profile: {}", e)))?;
+    fs::write(path, signed).map_err(|e| NonoError::ConfigWrite {
+        path: path.to_path_buf(),
+        source: e,
+    })?;
+    Ok(path.to_path_buf())
+}
+
+/// A private scratch directory for minisign's input and output
+fn tempfile_dir() -> Result<PathBuf> {
+    use std::os::unix::fs::DirBuilderExt;
+
+    let dir = std::env::temp_dir().join(format!("nono-sign-{}", std::process::id()));
+    fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&dir)
+        .map_err(|e| NonoError::ConfigWrite {
+            path: dir.clone(),
+            source: e,
+        })?;
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Signed once with a throwaway minisign key; only the public half is kept
+    const PROFILE: &str =
+        "[meta]\nname = \"signed\"\nversion = \"1.0.0\"\n\n[filesystem]\nallow = [\"/tmp\"]\n";
+    const PUBLIC_KEY: &str = "RWQe8u3smHYT3clyAws+2Hubl5pulhJatZo+qxhVakPNaoFH4eBCVkg8";
+    const DETACHED: &str = "untrusted comment: signature from minisign secret key\nRUQe8u3smHYT3RZjzWl/mbq38se/poyNn99J25JjEghoxXjl9l8d4B4aMNIvfBp/OtALc/bfIFHkKH49tguUwZYei0BXP/3eigc=\ntrusted comment: timestamp:1760000000 file:signed.toml\npGARnKUfK6fnWS4f5bDcUsy4sbNeYOIoMcErmTY6rakc7FALVgVa2ZTas/B6Ce8SKzFnLZP3euQYQ+tDUMUvBQ==\n";
+    // Signature over canonical_form(PROFILE)
+    const EMBEDDED: &str = "untrusted comment: signature from minisign secret key\nRUQe8u3smHYT3b2OwMTnLuYn6N7fWFAm+Fx7zo0bebAdeLVWNLi3TBuUMa9j8VsXKBw+Q6LP0nLGuBGKEXTc34wfGh5UQe+CJgw=\ntrusted comment: timestamp:1760000000 file:profile.toml\nDlmPTapCI+/xmeKomgdpKFfdaDoPDe38JPgGqax0XudgkUgtLiy0iwB2UQH/dDwmNJ5r5SiNkfWPjrYheR/vDw==\n";
+
+    fn keys() -> Vec<TrustedKey> {
+        vec![("Team".to_string(), PUBLIC_KEY.to_string())]
+    }
+
+    fn with_embedded(profile: &str) -> String {
+        profile.replacen(
+            "version = \"1.0.0\"\n",
+            &format!(
+                "version = \"1.0.0\"\nsignature = {}\n",
+                toml::Value::String(EMBEDDED.to_string())
+            ),
+            1,
+        )
+    }
+
+    #[test]
+    fn test_canonical_form_strips_signature() {
+        let canonical = canonical_form(&with_embedded(PROFILE)).expect("canonical");
+        assert_eq!(canonical, canonical_form(PROFILE).expect("canonical"));
+        assert!(!canonical.contains("signature"));
+        // Already canonical input is unchanged
+        assert_eq!(canonical_form(&canonical).expect("canonical"), canonical);
+    }
+
+    #[test]
+    fn test_verify_detached_signature() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("signed.toml");
+        fs::write(&path, PROFILE).expect("write profile");
+        fs::write(detached_signature_path(&path), DETACHED).expect("write signature");
+
+        let signer = verify_profile(&path, PROFILE, None, &keys()).expect("should verify");
+        assert_eq!(signer.as_deref(), Some("Team"));
+
+        let tampered = PROFILE.replace("/tmp", "/");
+        assert!(matches!(
+            verify_profile(&path, &tampered, None, &keys()),
+            Err(NonoError::SignatureInvalid { .. })
+        ));
+    }
+
+    #[test]
+    fn test_verify_embedded_signature() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("signed.toml");
+        let content = with_embedded(PROFILE);
+
+        let signer =
+            verify_profile(&path, &content, Some(EMBEDDED), &keys()).expect("should verify");
+        assert_eq!(signer.as_deref(), Some("Team"));
+
+        // Key order and formatting do not matter, the content does
+        let reordered = "[filesystem]\nallow = [ \"/tmp\" ]\n\n[meta]\nversion = \"1.0.0\"\nname = \"signed\"\n";
+        assert!(verify_profile(&path, reordered, Some(EMBEDDED), &keys()).is_ok());
+        let tampered = content.replace("/tmp", "/");
+        assert!(verify_profile(&path, &tampered, Some(EMBEDDED), &keys()).is_err());
+    }
+
+    #[test]
+    fn test_verify_untrusted_or_unsigned() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("signed.toml");
+
+        assert_eq!(
+            verify_profile(&path, PROFILE, None, &keys()).expect("unsigned"),
+            None
+        );
+        assert!(matches!(
+            verify_profile(&path, PROFILE, Some(EMBEDDED), &[]),
+            Err(NonoError::SignatureInvalid { .. })
+        ));
+    }
+}
@@ -0,0 +1,204 @@
+//! Interactive permission prompts (`--prompt`).
+//!
+//! Instead of letting the sandbox silently deny a file open or TCP connect,
+//! the supervised child stops on those syscalls (seccomp user notification,
+//! see [`crate::notify`]) and the supervisor decides:
+//!
+//! 1. The operation is first performed on a *probe* thread of the supervisor
+//!    that carries the child's exact Landlock policy. If the policy allows it,
+//!    the result (e.g. the opened file descriptor) is handed to the child.
+//! 2. If the policy denies it, the operator is asked on the terminal whether to
+//!    allow it once, for the rest of the session, or never. Approved
+//!    operations are performed by the unsandboxed supervisor on the child's
+//!    behalf; everything else fails with `EACCES` as it would without `--prompt`.
+//!
+//! Because the supervisor performs the operation itself, using the path or
+//! address it read and showed to the operator, the child cannot swap the
+//! target after approval.
+
+use crate::audit::{AuditEvent, AuditLog};
+use crate::error::{NonoError, Result};
+use colored::Colorize;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::sync::Mutex;
+
+/// Operator's answer to a permission prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    /// Allow this single operation
+    Once,
+    /// Allow this operation on this target until the session ends
+    Session,
+    /// Deny, and deny again without asking for the rest of the session
+    Never,
+}
+
+impl Decision {
+    /// Parse an answer; anything unrecognized (including empty) is `Never`
+    pub fn parse(answer: &str) -> Self {
+        match answer.trim().to_ascii_lowercase().as_str() {
+            "o" | "once" => Decision::Once,
+            "s" | "session" => Decision::Session,
+            _ => Decision::Never,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Decision::Once => "once",
+            Decision::Session => "session",
+            Decision::Never => "never",
+        }
+    }
+
+    pub fn allows(self) -> bool {
+        self != Decision::Never
+    }
+}
+
+/// Terminal and remembered answers, guarded together so prompts never overlap
+struct PromptState {
+    tty: File,
+    allowed: HashSet<String>,
+    denied: HashSet<String>,
+}
+
+/// Operator side of `--prompt`.
+///
+/// Created before fork (it opens the terminal) and handed to the
+/// [`NotifySupervisor`](crate::notify::NotifySupervisor), which asks it about
+/// operations the sandbox denied.
+pub struct Prompter {
+    audit: Option<AuditLog>,
+    state: Mutex<PromptState>,
+}
+
+impl Prompter {
+    /// Open the controlling terminal for prompting
+    pub fn new(audit: Option<&AuditLog>) -> Result<Self> {
+        if !cfg!(target_os = "linux") {
+            return Err(NonoError::SandboxInit(
+                "--prompt is only supported on Linux (requires seccomp user notification)"
+                    .to_string(),
+            ));
+        }
+
+        let tty = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open("/dev/tty")
+            .map_err(|e| {
+                NonoError::SandboxInit(format!("--prompt requires a terminal: /dev/tty: {}", e))
+            })?;
+
+        Ok(Self {
+            audit: audit.cloned(),
+            state: Mutex::new(PromptState {
+                tty,
+                allowed: HashSet::new(),
+                denied: HashSet::new(),
+            }),
+        })
+    }
+
+    /// Decide whether `operation` on `target` may proceed, asking if needed
+    pub fn decide(&self, operation: &str, target: &str, pid: u32) -> bool {
+        let key = format!("{} {}", operation, target);
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        if state.allowed.contains(&key) {
+            return true;
+        }
+        if state.denied.contains(&key) {
+            return false;
+        }
+
+        let decision = match ask(&mut state.tty, operation, target, pid) {
+            Ok(decision) => decision,
+            Err(e) => {
+                tracing::warn!("Permission prompt failed: {}", e);
+                Decision::Never
+            }
+        };
+
+        match decision {
+            Decision::Once => {}
+            Decision::Session => {
+                state.allowed.insert(key);
+            }
+            Decision::Never => {
+                state.denied.insert(key);
+            }
+        }
+
+        if let Some(ref audit) = self.audit {
+            audit.record(&AuditEvent::PermissionPrompt {
+                operation,
+                target,
+                decision: decision.label(),
+            });
+        }
+
+        decision.allows()
+    }
+}
+
+/// Show the prompt on the terminal and read the answer.
+///
+/// Lines end in `\r\n` and input is read byte by byte so the prompt also
+/// works while the command has the terminal in raw mode.
+fn ask(tty: &mut File, operation: &str, target: &str, pid: u32) -> std::io::Result<Decision> {
+    write!(
+        tty,
+        "\r\n{} {} {} {}\r\n{} ",
+        "[nono] Sandbox blocked".yellow(),
+        operation.white().bold(),
+        target.white().bold(),
+        format!("(pid {})", pid).truecolor(150, 150, 150),
+        "[nono] Allow? [o]nce, [s]ession, [N]ever:".white(),
+    )?;
+    tty.flush()?;
+
+    let mut answer = Vec::new();
+    let mut byte = [0u8; 1];
+    loop {
+        match tty.read(&mut byte)? {
+            0 => break,
+            _ if byte[0] == b'\n' || byte[0] == b'\r' => break,
+            _ => answer.push(byte[0]),
+        }
+    }
+
+    let decision = Decision::parse(&String::from_utf8_lossy(&answer));
+    write!(tty, "\r\n")?;
+    tty.flush()?;
+    Ok(decision)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decision_parse() {
+        assert_eq!(Decision::parse("o"), Decision::Once);
+        assert_eq!(Decision::parse("ONCE\n"), Decision::Once);
+        assert_eq!(Decision::parse("s"), Decision::Session);
+        assert_eq!(Decision::parse(" session "), Decision::Session);
+        assert_eq!(Decision::parse("n"), Decision::Never);
+        assert_eq!(Decision::parse(""), Decision::Never);
+        assert_eq!(Decision::parse("yes"), Decision::Never);
+    }
+
+    #[test]
+    fn test_decision_allows() {
+        assert!(Decision::Once.allows());
+        assert!(Decision::Session.allows());
+        assert!(!Decision::Never.allows());
+    }
+}
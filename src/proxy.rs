@@ -0,0 +1,587 @@
+//! Local filtering proxy for host allowlists.
+//!
+//! Neither Landlock nor Seatbelt can filter network access by hostname, so
+//! per-host rules are enforced by a small HTTP proxy that runs in the
+//! unsandboxed supervisor. The child is pointed at it through `HTTP_PROXY` /
+//! `HTTPS_PROXY`, and TCP port rules restrict the child so that the proxy port
+//! (plus any explicitly allowed ports) is the only TCP port it can connect
+//! to. On macOS the proxy port is only reachable on loopback; Landlock
+//! matches ports but not addresses, so on Linux a program that ignores the
+//! proxy variables can still reach any host on the proxy's port.
+//!
+//! The proxy itself only forwards to the allowed TCP ports, or to the
+//! standard HTTP and HTTPS ports when no port rules are set, and serves at
+//! most [`MAX_CLIENTS`] connections at a time.
+//!
+//! Supported requests:
+//! - `CONNECT host:port` (HTTPS and other TLS traffic), tunnelled after the
+//!   host is checked
+//! - Absolute-form plain HTTP requests (`GET http://host/path`), forwarded
+//!   in origin-form to the upstream server
+//!
+//! Anything else, and any host not matching the allowlist, gets a `403`.
+
+use crate::error::{NonoError, Result};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Environment variables pointing the child at the proxy
+pub const PROXY_ENV_VARS: &[&str] = &[
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "ALL_PROXY",
+    "http_proxy",
+    "https_proxy",
+    "all_proxy",
+];
+
+/// Environment variables cleared so no host bypasses the proxy
+pub const NO_PROXY_ENV_VARS: &[&str] = &["NO_PROXY", "no_proxy"];
+
+/// Maximum size of a request head (request line + headers)
+const MAX_HEAD_BYTES: usize = 64 * 1024;
+
+/// Timeout for connecting to an upstream server
+const UPSTREAM_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Upstream ports the proxy forwards to when no TCP connect ports are set
+const DEFAULT_UPSTREAM_PORTS: &[u16] = &[80, 443];
+
+/// Client connections served at once (each takes up to two threads)
+const MAX_CLIENTS: usize = 64;
+
+/// Upstream ports the proxy may forward to: the allowed TCP connect ports,
+/// or HTTP and HTTPS if there are none, narrowed to the system policy's
+/// ports if it lists any
+pub fn upstream_ports(connect_ports: &[u16], policy_ports: &[u16]) -> Vec<u16> {
+    let ports = if connect_ports.is_empty() {
+        DEFAULT_UPSTREAM_PORTS
+    } else {
+        connect_ports
+    };
+    ports
+        .iter()
+        .copied()
+        .filter(|port| policy_ports.is_empty() || policy_ports.contains(port))
+        .collect()
+}
+
+/// Check if a host matches an allowlist pattern.
+///
+/// Patterns are either exact hostnames (`api.anthropic.com`) or a leading
+/// wildcard (`*.github.com`), which matches any subdomain but not the bare
+/// domain itself. Matching is case-insensitive and ignores a trailing dot.
+pub fn host_matches(host: &str, pattern: &str) -> bool {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    let pattern = pattern.trim_end_matches('.').to_ascii_lowercase();
+
+    if host.is_empty() || pattern.is_empty() {
+        return false;
+    }
+
+    match pattern.strip_prefix("*.") {
+        Some(suffix) => host
+            .strip_suffix(suffix)
+            .is_some_and(|prefix| prefix.len() > 1 && prefix.ends_with('.')),
+        None => host == pattern,
+    }
+}
+
+/// Find the first allowlist pattern that matches `host`
+pub fn find_allowing_pattern<'a>(host: &str, patterns: &'a [String]) -> Option<&'a str> {
+    patterns
+        .iter()
+        .find(|p| host_matches(host, p))
+        .map(|p| p.as_str())
+}
+
+/// A parsed proxy request target
+#[derive(Debug, PartialEq, Eq)]
+enum ProxyTarget {
+    /// `CONNECT host:port` tunnel
+    Connect { host: String, port: u16 },
+    /// Absolute-form HTTP request, rewritten to origin-form
+    Http {
+        host: String,
+        port: u16,
+        /// Request line to send upstream (`GET /path HTTP/1.1`)
+        request_line: String,
+    },
+}
+
+/// Split `host[:port]`, handling bracketed IPv6 literals
+fn split_host_port(authority: &str, default_port: u16) -> Option<(String, u16)> {
+    let (host, port) = if let Some(rest) = authority.strip_prefix('[') {
+        let end = rest.find(']')?;
+        let host = &rest[..end];
+        let port = match &rest[end + 1..] {
+            "" => default_port,
+            p => p.strip_prefix(':')?.parse().ok()?,
+        };
+        (host, port)
+    } else {
+        match authority.rsplit_once(':') {
+            Some((h, p)) => (h, p.parse().ok()?),
+            None => (authority, default_port),
+        }
+    };
+
+    if host.is_empty() {
+        return None;
+    }
+    Some((host.to_string(), port))
+}
+
+/// Parse the request line of a proxy request
+fn parse_request_line(line: &str) -> Option<ProxyTarget> {
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?;
+    let target = parts.next()?;
+    let version = parts.next()?;
+    if parts.next().is_some() || !version.starts_with("HTTP/") {
+        return None;
+    }
+
+    if method.eq_ignore_ascii_case("CONNECT") {
+        let (host, port) = split_host_port(target, 443)?;
+        return Some(ProxyTarget::Connect { host, port });
+    }
+
+    // Only plain http:// is accepted in absolute form; https goes through CONNECT
+    let rest = target.strip_prefix("http://")?;
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    // Strip userinfo if present
+    let authority = authority.rsplit('@').next()?;
+    let (host, port) = split_host_port(authority, 80)?;
+
+    Some(ProxyTarget::Http {
+        host,
+        port,
+        request_line: format!("{} {} {}", method, path, version),
+    })
+}
+
+/// Local HTTP/HTTPS proxy that only lets allowlisted hosts through.
+///
+/// The listener is bound in [`FilteringProxy::bind`] so the port is known
+/// before the sandbox is built; no thread is started until
+/// [`FilteringProxy::start`], which must be called after fork.
+pub struct FilteringProxy {
+    listener: TcpListener,
+    allow_hosts: Arc<Vec<String>>,
+    /// Upstream ports the proxy may connect to (see [`upstream_ports`])
+    allow_ports: Arc<Vec<u16>>,
+}
+
+impl FilteringProxy {
+    /// Bind the proxy to an ephemeral port on the loopback interface.
+    ///
+    /// Only the upstream ports in `allow_ports` are forwarded to.
+    pub fn bind(allow_hosts: &[String], allow_ports: &[u16]) -> Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", 0))
+            .map_err(|e| NonoError::SandboxInit(format!("Failed to start proxy: {}", e)))?;
+        Ok(Self {
+            listener,
+            allow_hosts: Arc::new(allow_hosts.to_vec()),
+            allow_ports: Arc::new(allow_ports.to_vec()),
+        })
+    }
+
+    /// The port the proxy is listening on
+    pub fn port(&self) -> Result<u16> {
+        self.listener
+            .local_addr()
+            .map(|a| a.port())
+            .map_err(|e| NonoError::SandboxInit(format!("Failed to query proxy port: {}", e)))
+    }
+
+    /// Proxy URL to export to the child
+    pub fn url(&self) -> Result<String> {
+        Ok(format!("http://127.0.0.1:{}", self.port()?))
+    }
+
+    /// Start accepting connections on a background thread.
+    ///
+    /// Must be called in the supervisor after fork: it spawns threads.
+    pub fn start(&self) -> Result<()> {
+        let listener = self
+            .listener
+            .try_clone()
+            .map_err(|e| NonoError::SandboxInit(format!("Failed to start proxy: {}", e)))?;
+        let allow_hosts = Arc::clone(&self.allow_hosts);
+        let allow_ports = Arc::clone(&self.allow_ports);
+
+        info!(
+            "Filtering proxy listening on {:?}",
+            listener.local_addr().ok()
+        );
+
+        std::thread::spawn(move || {
+            let active = Arc::new(AtomicUsize::new(0));
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(mut client) => {
+                        // The command must not be able to make the
+                        // supervisor spawn threads without bound
+                        if active.fetch_add(1, Ordering::SeqCst) >= MAX_CLIENTS {
+                            active.fetch_sub(1, Ordering::SeqCst);
+                            warn!("Proxy refused a connection: too many open connections");
+                            let _ = respond(
+                                &mut client,
+                                "503 Service Unavailable",
+                                "nono: too many proxy connections\n",
+                            );
+                            continue;
+                        }
+                        let allow_hosts = Arc::clone(&allow_hosts);
+                        let allow_ports = Arc::clone(&allow_ports);
+                        let done = Arc::clone(&active);
+                        let spawned = std::thread::Builder::new().spawn(move || {
+                            handle_client(client, &allow_hosts, &allow_ports);
+                            done.fetch_sub(1, Ordering::SeqCst);
+                        });
+                        if let Err(e) = spawned {
+                            active.fetch_sub(1, Ordering::SeqCst);
+                            debug!("Proxy failed to start a connection thread: {}", e);
+                        }
+                    }
+                    Err(e) => debug!("Proxy accept failed: {}", e),
+                }
+            }
+        });
+
+        Ok(())
+    }
+}
+
+/// Handle a single proxy client connection
+fn handle_client(client: TcpStream, allow_hosts: &[String], allow_ports: &[u16]) {
+    let mut reader = BufReader::new(match client.try_clone() {
+        Ok(c) => c,
+        Err(e) => {
+            debug!("Proxy failed to clone client stream: {}", e);
+            return;
+        }
+    });
+    let mut client = client;
+
+    let head = match read_head(&mut reader) {
+        Some(head) => head,
+        None => {
+            let _ = respond(
+                &mut client,
+                "400 Bad Request",
+                "nono: malformed proxy request\n",
+            );
+            return;
+        }
+    };
+
+    let mut lines = head.split("\r\n");
+    let request_line = lines.next().unwrap_or_default();
+    let target = match parse_request_line(request_line) {
+        Some(t) => t,
+        None => {
+            let _ = respond(
+                &mut client,
+                "400 Bad Request",
+                "nono: unsupported proxy request\n",
+            );
+            return;
+        }
+    };
+
+    let (host, port) = match &target {
+        ProxyTarget::Connect { host, port } | ProxyTarget::Http { host, port, .. } => {
+            (host.as_str(), *port)
+        }
+    };
+
+    if find_allowing_pattern(host, allow_hosts).is_none() {
+        warn!("Proxy denied connection to {}:{}", host, port);
+        eprintln!(
+            "[nono] Blocked connection to {}: host not in allowed hosts",
+            host
+        );
+        let _ = respond(
+            &mut client,
+            "403 Forbidden",
+            &format!("nono: host '{}' is not in the allowed hosts list\n", host),
+        );
+        return;
+    }
+
+    if !allow_ports.contains(&port) {
+        warn!("Proxy denied connection to {}:{}", host, port);
+        eprintln!(
+            "[nono] Blocked connection to {}:{}: port not allowed",
+            host, port
+        );
+        let _ = respond(
+            &mut client,
+            "403 Forbidden",
+            &format!("nono: port {} is not allowed\n", port),
+        );
+        return;
+    }
+
+    let mut upstream = match connect_upstream(host, port) {
+        Ok(s) => s,
+        Err(e) => {
+            debug!("Proxy failed to reach {}:{}: {}", host, port, e);
+            let _ = respond(
+                &mut client,
+                "502 Bad Gateway",
+                &format!("nono: cannot reach {}:{}\n", host, port),
+            );
+            return;
+        }
+    };
+
+    debug!("Proxy forwarding to {}:{}", host, port);
+
+    let forwarded = match &target {
+        ProxyTarget::Connect { .. } => {
+            client.write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")
+        }
+        ProxyTarget::Http { request_line, .. } => {
+            // Re-send the head in origin-form, dropping hop-by-hop proxy headers
+            let mut out = format!("{}\r\n", request_line);
+            for line in lines.filter(|l| !l.is_empty()) {
+                let name = line.split(':').next().unwrap_or_default();
+                if name.eq_ignore_ascii_case("proxy-connection")
+                    || name.eq_ignore_ascii_case("proxy-authorization")
+                {
+                    continue;
+                }
+                out.push_str(line);
+                out.push_str("\r\n");
+            }
+            out.push_str("\r\n");
+            upstream.write_all(out.as_bytes())
+        }
+    };
+    if forwarded.is_err() {
+        return;
+    }
+
+    // Forward anything the client sent after the head (request body, TLS hello)
+    if !reader.buffer().is_empty() && upstream.write_all(reader.buffer()).is_err() {
+        return;
+    }
+
+    relay(client, upstream);
+}
+
+/// Read the request head up to and including the blank line.
+///
+/// Returns the head without the trailing blank line, or None if the client
+/// closed the connection or the head exceeded [`MAX_HEAD_BYTES`].
+fn read_head(reader: &mut BufReader<TcpStream>) -> Option<String> {
+    let mut head = String::new();
+    loop {
+        let mut line = String::new();
+        let n = reader
+            .by_ref()
+            .take((MAX_HEAD_BYTES - head.len()) as u64)
+            .read_line(&mut line)
+            .ok()?;
+        if n == 0 || !line.ends_with('\n') {
+            return None;
+        }
+        if line == "\r\n" || line == "\n" {
+            return Some(head.trim_end_matches("\r\n").to_string());
+        }
+        head.push_str(line.trim_end_matches(['\r', '\n']));
+        head.push_str("\r\n");
+    }
+}
+
+/// Connect to an upstream server, trying each resolved address in turn
+fn connect_upstream(host: &str, port: u16) -> std::io::Result<TcpStream> {
+    let addrs: Vec<SocketAddr> = (host, port).to_socket_addrs()?.collect();
+    let mut last_err = std::io::Error::new(std::io::ErrorKind::NotFound, "no addresses");
+    for addr in addrs {
+        match TcpStream::connect_timeout(&addr, UPSTREAM_CONNECT_TIMEOUT) {
+            Ok(s) => return Ok(s),
+            Err(e) => last_err = e,
+        }
+    }
+    Err(last_err)
+}
+
+/// Write a minimal HTTP response and close the connection
+fn respond(client: &mut TcpStream, status: &str, body: &str) -> std::io::Result<()> {
+    write!(
+        client,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    client.flush()
+}
+
+/// Copy bytes in both directions until either side closes
+fn relay(client: TcpStream, upstream: TcpStream) {
+    let (mut client_read, mut upstream_write) = match (client.try_clone(), upstream.try_clone()) {
+        (Ok(c), Ok(u)) => (c, u),
+        _ => return,
+    };
+    let (mut upstream_read, mut client_write) = (upstream, client);
+
+    let uplink = std::thread::spawn(move || {
+        let _ = std::io::copy(&mut client_read, &mut upstream_write);
+        let _ = upstream_write.shutdown(Shutdown::Write);
+    });
+
+    let _ = std::io::copy(&mut upstream_read, &mut client_write);
+    let _ = client_write.shutdown(Shutdown::Write);
+    let _ = uplink.join();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_matches_exact() {
+        assert!(host_matches("api.anthropic.com", "api.anthropic.com"));
+        assert!(host_matches("API.Anthropic.com", "api.anthropic.com"));
+        assert!(host_matches("api.anthropic.com.", "api.anthropic.com"));
+        assert!(!host_matches("evil.com", "api.anthropic.com"));
+        assert!(!host_matches(
+            "api.anthropic.com.evil.com",
+            "api.anthropic.com"
+        ));
+    }
+
+    #[test]
+    fn test_host_matches_wildcard() {
+        assert!(host_matches("api.github.com", "*.github.com"));
+        assert!(host_matches("a.b.github.com", "*.github.com"));
+        // Wildcard does not match the bare domain
+        assert!(!host_matches("github.com", "*.github.com"));
+        // Suffix must be on a label boundary
+        assert!(!host_matches("evilgithub.com", "*.github.com"));
+        assert!(!host_matches("", "*.github.com"));
+    }
+
+    #[test]
+    fn test_find_allowing_pattern() {
+        let patterns = vec!["api.anthropic.com".to_string(), "*.github.com".to_string()];
+        assert_eq!(
+            find_allowing_pattern("api.github.com", &patterns),
+            Some("*.github.com")
+        );
+        assert_eq!(find_allowing_pattern("example.com", &patterns), None);
+    }
+
+    #[test]
+    fn test_parse_connect() {
+        assert_eq!(
+            parse_request_line("CONNECT api.anthropic.com:443 HTTP/1.1"),
+            Some(ProxyTarget::Connect {
+                host: "api.anthropic.com".to_string(),
+                port: 443
+            })
+        );
+        assert_eq!(
+            parse_request_line("CONNECT [::1]:8443 HTTP/1.1"),
+            Some(ProxyTarget::Connect {
+                host: "::1".to_string(),
+                port: 8443
+            })
+        );
+        assert_eq!(parse_request_line("CONNECT :443 HTTP/1.1"), None);
+    }
+
+    #[test]
+    fn test_parse_absolute_form() {
+        assert_eq!(
+            parse_request_line("GET http://example.com:8080/a/b?c=d HTTP/1.1"),
+            Some(ProxyTarget::Http {
+                host: "example.com".to_string(),
+                port: 8080,
+                request_line: "GET /a/b?c=d HTTP/1.1".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_request_line("GET http://example.com HTTP/1.1"),
+            Some(ProxyTarget::Http {
+                host: "example.com".to_string(),
+                port: 80,
+                request_line: "GET / HTTP/1.1".to_string(),
+            })
+        );
+        // Origin-form requests are not proxy requests
+        assert_eq!(parse_request_line("GET /index.html HTTP/1.1"), None);
+        assert_eq!(parse_request_line("garbage"), None);
+    }
+
+    /// Send a raw request through the proxy and return the full response
+    fn proxy_roundtrip(proxy_port: u16, request: &str) -> String {
+        let mut stream = TcpStream::connect(("127.0.0.1", proxy_port)).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        stream.shutdown(Shutdown::Write).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_proxy_allows_and_denies() {
+        // Upstream echo server
+        let upstream = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let upstream_port = upstream.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            for stream in upstream.incoming().flatten() {
+                let mut s = stream;
+                let mut buf = Vec::new();
+                let _ = s.read_to_end(&mut buf);
+                let _ = s.write_all(&buf);
+            }
+        });
+
+        let proxy = FilteringProxy::bind(&["127.0.0.1".to_string()], &[upstream_port]).unwrap();
+        proxy.start().unwrap();
+        let port = proxy.port().unwrap();
+
+        let response = proxy_roundtrip(
+            port,
+            &format!("CONNECT 127.0.0.1:{} HTTP/1.1\r\n\r\nping", upstream_port),
+        );
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.ends_with("ping"));
+
+        let response = proxy_roundtrip(port, "CONNECT localhost.evil:443 HTTP/1.1\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 403"));
+
+        // Other ports are refused even for allowed hosts
+        let proxy = FilteringProxy::bind(&["127.0.0.1".to_string()], &[443]).unwrap();
+        proxy.start().unwrap();
+        let response = proxy_roundtrip(
+            proxy.port().unwrap(),
+            &format!("CONNECT 127.0.0.1:{} HTTP/1.1\r\n\r\n", upstream_port),
+        );
+        assert!(response.starts_with("HTTP/1.1 403"));
+    }
+
+    #[test]
+    fn test_upstream_ports() {
+        // No port rules: HTTP and HTTPS only
+        assert_eq!(upstream_ports(&[], &[]), vec![80, 443]);
+        // Port rules replace the defaults
+        assert_eq!(upstream_ports(&[8443], &[]), vec![8443]);
+        // The system policy narrows both
+        assert_eq!(upstream_ports(&[], &[443]), vec![443]);
+        assert!(upstream_ports(&[8443], &[443]).is_empty());
+    }
+}
@@ -7,10 +7,12 @@
 
 use serde::{Deserialize, Serialize};
 use std::path::Path;
+use std::time::SystemTime;
 
-use crate::capability::{CapabilitySet, FsAccess};
+use crate::capability::{format_timestamp, CapabilitySet, FsAccess};
 use crate::config;
 use crate::error::{NonoError, Result};
+use crate::proxy;
 
 /// Result of a query operation
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
@@ -23,6 +25,9 @@ pub enum QueryResult {
         reason: AllowReason,
         /// What granted the permission
         granted_by: String,
+        /// When the granting capability expires, if it is time-limited
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expires_at: Option<String>,
     },
     /// Operation would be denied
     #[serde(rename = "denied")]
@@ -41,6 +46,12 @@ pub enum QueryResult {
         /// Explanation message
         message: String,
     },
+    /// Running inside a nono sandbox (`why --self` without a query)
+    #[serde(rename = "sandboxed")]
+    Sandboxed {
+        /// Whether the sandbox has a PID namespace of its own
+        pid_namespace: bool,
+    },
 }
 
 /// Reason why an operation is allowed
@@ -55,6 +66,10 @@ pub enum AllowReason {
     SystemPath,
     /// Network allowed by default
     NetworkAllowedByDefault,
+    /// TCP port explicitly allowed via --allow-port or [network] connect_ports
+    PortAllowed,
+    /// Host matches --allow-host or [network] allow_hosts (reached via proxy)
+    HostAllowed,
 }
 
 /// Reason why an operation is denied
@@ -67,13 +82,25 @@ pub enum DenyReason {
     NotInAllowedPaths,
     /// Network access is blocked
     NetworkBlocked,
+    /// TCP port filtering is active and the port is not allowed
+    PortNotAllowed,
+    /// A host allowlist is active and the host is not on it
+    HostNotAllowed,
+    /// The path was granted by a time-limited capability that has expired
+    CapabilityExpired,
+    /// The path is excluded with `--deny` or a profile `deny` entry
+    ExplicitDeny,
 }
 
+/// Suggestion for operations the system policy in /etc/nono rules out
+const POLICY_SUGGESTION: &str = "none: the system policy does not allow this";
+
 /// Query if a path operation would be allowed
 ///
 /// Checks the path against:
-/// 1. Sensitive paths list (always denied unless explicitly overridden)
-/// 2. Granted capabilities from CLI args or profile
+/// 1. Sensitive paths list (always denied unless overridden in the user config)
+/// 2. Explicitly denied paths, which override any grant
+/// 3. Granted capabilities from CLI args or profile
 ///
 /// # Errors
 /// Returns `NonoError::EnvVarValidation` if tilde expansion is needed but HOME is missing or invalid
@@ -81,13 +108,20 @@ pub fn query_path(path: &Path, op: FsAccess, caps: &CapabilitySet) -> Result<Que
     let path_str = path.display().to_string();
 
     // First check sensitive paths - these are blocked by default
-    if let Some(category) = config::check_sensitive_path(&path_str) {
-        return Ok(QueryResult::Denied {
-            reason: DenyReason::SensitivePath,
-            category: Some(category.to_string()),
+    if let Some(category) = config::check_sensitive_path(&path_str, op) {
+        let suggestion = if config::effective_config()
+            .overlaps_policy_path(Path::new(&config::expand_home(&path_str)))
+        {
+            POLICY_SUGGESTION.to_string()
+        } else {
             // SECURITY: Do not call path.is_file() here - it leaks metadata about denied paths
             // (reveals whether path exists and its type). Always use directory-level flags.
-            suggestion: suggest_flag(path, op),
+            suggest_flag(path, op)
+        };
+        return Ok(QueryResult::Denied {
+            reason: DenyReason::SensitivePath,
+            category: Some(category),
+            suggestion,
         });
     }
 
@@ -124,7 +158,22 @@ pub fn query_path(path: &Path, op: FsAccess, caps: &CapabilitySet) -> Result<Que
     let expanded_path = Path::new(&expanded_path_str);
     let query_path = Path::new(&path_str);
 
+    // Denied paths are stored canonicalized
+    let denied = caps.is_denied(expanded_path)
+        || expanded_path
+            .canonicalize()
+            .is_ok_and(|resolved| caps.is_denied(&resolved));
+    if denied {
+        return Ok(QueryResult::Denied {
+            reason: DenyReason::ExplicitDeny,
+            category: None,
+            suggestion: "remove the matching --deny flag or profile deny entry".to_string(),
+        });
+    }
+
     // Check against granted capabilities
+    let now = SystemTime::now();
+    let mut expired_grant = false;
     for cap in &caps.fs {
         // Check if the path matches or is under the capability path
         // SECURITY: Path::starts_with() compares path components, not strings
@@ -143,6 +192,10 @@ pub fn query_path(path: &Path, op: FsAccess, caps: &CapabilitySet) -> Result<Que
         };
 
         if matches && access_allows(&cap.access, op) {
+            if cap.is_expired_at(now) {
+                expired_grant = true;
+                continue;
+            }
             return Ok(QueryResult::Allowed {
                 reason: AllowReason::ExplicitGrant,
                 granted_by: format!(
@@ -150,10 +203,19 @@ pub fn query_path(path: &Path, op: FsAccess, caps: &CapabilitySet) -> Result<Que
                     access_to_flag(&cap.access),
                     cap.original.display()
                 ),
+                expires_at: cap.expires_at.map(format_timestamp),
             });
         }
     }
 
+    if expired_grant {
+        return Ok(QueryResult::Denied {
+            reason: DenyReason::CapabilityExpired,
+            category: None,
+            suggestion: suggest_flag(path, op),
+        });
+    }
+
     // Not allowed
     Ok(QueryResult::Denied {
         reason: DenyReason::NotInAllowedPaths,
@@ -163,34 +225,95 @@ pub fn query_path(path: &Path, op: FsAccess, caps: &CapabilitySet) -> Result<Que
     })
 }
 
+/// Describe the sandbox the caller runs in, for `nono why --self`
+pub fn sandbox_status(caps: &CapabilitySet) -> QueryResult {
+    QueryResult::Sandboxed {
+        pid_namespace: caps.pid_namespace,
+    }
+}
+
 /// Query if network access would be allowed
-pub fn query_network(_host: &str, _port: u16, caps: &CapabilitySet) -> QueryResult {
+///
+/// With a host allowlist, `host` is checked against it (those connections go
+/// through the filtering proxy). `port` is checked against TCP connect rules
+/// when port filtering is active.
+pub fn query_network(host: &str, port: u16, caps: &CapabilitySet) -> QueryResult {
+    let config = config::effective_config();
+    let policy_ports = config.policy_connect_ports();
+    let port_suggestion = if policy_ports.is_empty() || policy_ports.contains(&port) {
+        format!("--allow-port {}", port)
+    } else {
+        POLICY_SUGGESTION.to_string()
+    };
+
     if caps.net_block {
+        let policy_block = config
+            .system_policy
+            .as_ref()
+            .is_some_and(|policy| policy.network.block);
         QueryResult::Denied {
             reason: DenyReason::NetworkBlocked,
             category: None,
-            suggestion: "remove --net-block flag".to_string(),
+            suggestion: if policy_block {
+                POLICY_SUGGESTION.to_string()
+            } else {
+                "remove --net-block flag".to_string()
+            },
+        }
+    } else if caps.has_host_allowlist() {
+        if !policy_ports.is_empty() && !policy_ports.contains(&port) {
+            // The proxy refuses upstream ports outside the policy too
+            QueryResult::Denied {
+                reason: DenyReason::PortNotAllowed,
+                category: None,
+                suggestion: POLICY_SUGGESTION.to_string(),
+            }
+        } else if let Some(pattern) = proxy::find_allowing_pattern(host, &caps.allow_hosts) {
+            QueryResult::Allowed {
+                reason: AllowReason::HostAllowed,
+                granted_by: format!("--allow-host {}", pattern),
+                expires_at: None,
+            }
+        } else if caps.tcp_connect_ports.contains(&port) {
+            // Direct connections are still possible on explicitly allowed ports
+            QueryResult::Allowed {
+                reason: AllowReason::PortAllowed,
+                granted_by: format!("--allow-port {}", port),
+                expires_at: None,
+            }
+        } else {
+            QueryResult::Denied {
+                reason: DenyReason::HostNotAllowed,
+                category: None,
+                suggestion: format!("--allow-host {}", host),
+            }
+        }
+    } else if caps.has_port_rules() {
+        if caps.tcp_connect_ports.contains(&port) {
+            QueryResult::Allowed {
+                reason: AllowReason::PortAllowed,
+                granted_by: format!("--allow-port {}", port),
+                expires_at: None,
+            }
+        } else {
+            QueryResult::Denied {
+                reason: DenyReason::PortNotAllowed,
+                category: None,
+                suggestion: port_suggestion,
+            }
         }
     } else {
         QueryResult::Allowed {
             reason: AllowReason::NetworkAllowedByDefault,
             granted_by: "network allowed by default".to_string(),
+            expires_at: None,
         }
     }
 }
 
 /// Check if a capability's access level allows the requested operation
 fn access_allows(cap_access: &FsAccess, requested: FsAccess) -> bool {
-    match (cap_access, requested) {
-        // ReadWrite allows anything
-        (FsAccess::ReadWrite, _) => true,
-        // Read allows Read
-        (FsAccess::Read, FsAccess::Read) => true,
-        // Write allows Write
-        (FsAccess::Write, FsAccess::Write) => true,
-        // Otherwise denied
-        _ => false,
-    }
+    cap_access.allows(requested)
 }
 
 /// Convert access level to CLI flag name
@@ -199,6 +322,8 @@ fn access_to_flag(access: &FsAccess) -> &'static str {
         FsAccess::Read => "read",
         FsAccess::Write => "write",
         FsAccess::ReadWrite => "allow",
+        FsAccess::ReadNoExec => "read-noexec",
+        FsAccess::Execute => "exec-only",
     }
 }
 
@@ -213,9 +338,10 @@ fn access_to_flag(access: &FsAccess) -> &'static str {
 /// both files and directories, preventing information disclosure about denied paths.
 fn suggest_flag(path: &Path, op: FsAccess) -> String {
     let flag = match op {
-        FsAccess::Read => "--read",
+        FsAccess::Read | FsAccess::ReadNoExec => "--read",
         FsAccess::Write => "--write",
         FsAccess::ReadWrite => "--allow",
+        FsAccess::Execute => "--exec-only",
     };
     format!("{} {}", flag, path.display())
 }
@@ -223,10 +349,17 @@ fn suggest_flag(path: &Path, op: FsAccess) -> String {
 /// Print a query result in human-readable format
 pub fn print_result(result: &QueryResult) {
     match result {
-        QueryResult::Allowed { reason, granted_by } => {
+        QueryResult::Allowed {
+            reason,
+            granted_by,
+            expires_at,
+        } => {
             println!("ALLOWED");
             println!("  Reason: {:?}", reason);
             println!("  Granted by: {}", granted_by);
+            if let Some(at) = expires_at {
+                println!("  Expires: {}", at);
+            }
         }
         QueryResult::Denied {
             reason,
@@ -244,6 +377,15 @@ pub fn print_result(result: &QueryResult) {
             println!("NOT SANDBOXED");
             println!("  {}", message);
         }
+        QueryResult::Sandboxed { pid_namespace } => {
+            println!("SANDBOXED");
+            let pids = if *pid_namespace {
+                "active (other processes are not visible)"
+            } else {
+                "inactive"
+            };
+            println!("  PID namespace: {}", pids);
+        }
     }
 }
 
@@ -315,6 +457,58 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_query_network_port_rules() {
+        let caps = CapabilitySet {
+            tcp_connect_ports: vec![443],
+            ..Default::default()
+        };
+
+        match query_network("api.openai.com", 443, &caps) {
+            QueryResult::Allowed {
+                reason: AllowReason::PortAllowed,
+                granted_by,
+                ..
+            } => assert_eq!(granted_by, "--allow-port 443"),
+            other => panic!("Expected port allowed, got {:?}", other),
+        }
+
+        match query_network("example.com", 22, &caps) {
+            QueryResult::Denied {
+                reason: DenyReason::PortNotAllowed,
+                suggestion,
+                ..
+            } => assert_eq!(suggestion, "--allow-port 22"),
+            other => panic!("Expected port denied, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_query_network_host_allowlist() {
+        let caps = CapabilitySet {
+            allow_hosts: vec!["api.anthropic.com".to_string(), "*.github.com".to_string()],
+            ..Default::default()
+        };
+
+        match query_network("api.github.com", 443, &caps) {
+            QueryResult::Allowed {
+                reason: AllowReason::HostAllowed,
+                granted_by,
+                ..
+            } => assert_eq!(granted_by, "--allow-host *.github.com"),
+            other => panic!("Expected host allowed, got {:?}", other),
+        }
+
+        match query_network("evil.example", 443, &caps) {
+            QueryResult::Denied {
+                reason: DenyReason::HostNotAllowed,
+                suggestion,
+                ..
+            } => assert_eq!(suggestion, "--allow-host evil.example"),
+            other => panic!("Expected host denied, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_access_allows() {
         // ReadWrite allows anything
@@ -331,6 +525,83 @@ mod tests {
         assert!(access_allows(&FsAccess::Write, FsAccess::Write));
         assert!(!access_allows(&FsAccess::Write, FsAccess::Read));
         assert!(!access_allows(&FsAccess::Write, FsAccess::ReadWrite));
+
+        // Exec needs an executable grant
+        assert!(access_allows(&FsAccess::Read, FsAccess::Execute));
+        assert!(access_allows(&FsAccess::Execute, FsAccess::Execute));
+        assert!(!access_allows(&FsAccess::ReadNoExec, FsAccess::Execute));
+        assert!(access_allows(&FsAccess::ReadNoExec, FsAccess::Read));
+        // Exec-only files can be read by name on Linux (Landlock needs
+        // ReadFile for execve)
+        assert_eq!(
+            access_allows(&FsAccess::Execute, FsAccess::Read),
+            cfg!(target_os = "linux")
+        );
+    }
+
+    #[test]
+    fn test_query_time_limited_capability() {
+        use crate::capability::FsCapability;
+        use std::path::PathBuf;
+        use std::time::Duration;
+
+        let mut caps = CapabilitySet::default();
+        caps.fs.push(FsCapability {
+            original: PathBuf::from("/scratch"),
+            resolved: PathBuf::from("/scratch"),
+            access: FsAccess::ReadWrite,
+            is_file: false,
+            ttl: Some(Duration::from_secs(60)),
+            expires_at: None,
+        });
+
+        // Still valid: the expiry is reported
+        caps.arm_expiry(SystemTime::now());
+        match query_path(Path::new("/scratch/f"), FsAccess::Read, &caps).unwrap() {
+            QueryResult::Allowed { expires_at, .. } => assert!(expires_at.is_some()),
+            other => panic!("Expected allowed, got {:?}", other),
+        }
+
+        // Expired: denied with the reason
+        caps.arm_expiry(SystemTime::now() - Duration::from_secs(120));
+        match query_path(Path::new("/scratch/f"), FsAccess::Read, &caps).unwrap() {
+            QueryResult::Denied { reason, .. } => {
+                assert_eq!(reason, DenyReason::CapabilityExpired)
+            }
+            other => panic!("Expected denied, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_query_denied_inside_allowed_dir() {
+        use crate::capability::FsCapability;
+        use std::path::PathBuf;
+
+        let mut caps = CapabilitySet::default();
+        caps.fs.push(FsCapability {
+            original: PathBuf::from("/work"),
+            resolved: PathBuf::from("/work"),
+            access: FsAccess::ReadWrite,
+            is_file: false,
+            ttl: None,
+            expires_at: None,
+        });
+        caps.add_deny(PathBuf::from("/work/.git/hooks"));
+
+        match query_path(
+            Path::new("/work/.git/hooks/pre-commit"),
+            FsAccess::Write,
+            &caps,
+        )
+        .unwrap()
+        {
+            QueryResult::Denied { reason, .. } => assert_eq!(reason, DenyReason::ExplicitDeny),
+            other => panic!("Expected denied, got {:?}", other),
+        }
+        assert!(matches!(
+            query_path(Path::new("/work/.git/config"), FsAccess::Write, &caps).unwrap(),
+            QueryResult::Allowed { .. }
+        ));
     }
 
     #[test]
@@ -348,6 +619,10 @@ mod tests {
             suggest_flag(Path::new("./dir"), FsAccess::ReadWrite),
             "--allow ./dir"
         );
+        assert_eq!(
+            suggest_flag(Path::new("./bin"), FsAccess::Execute),
+            "--exec-only ./bin"
+        );
     }
 
     /// SECURITY REGRESSION TEST
@@ -368,6 +643,8 @@ mod tests {
             resolved: PathBuf::from("/home"),
             access: FsAccess::ReadWrite,
             is_file: false,
+            ttl: None,
+            expires_at: None,
         });
 
         // /home/user should be allowed (legitimate child path)
@@ -1,16 +1,112 @@
+use super::namespace::RootMount;
+use super::seccomp::{self, SeccompFilter};
+use super::{EnforcementReport, HiddenPath, Protection, Reveal};
 use crate::capability::{CapabilitySet, FsAccess};
-use crate::config;
+use crate::config::{self, OverrideInfo};
 use crate::error::{NonoError, Result};
 use landlock::{
-    Access, AccessFs, AccessNet, BitFlags, PathBeneath, PathFd, Ruleset, RulesetAttr,
-    RulesetCreatedAttr, ABI,
+    Access, AccessFs, AccessNet, BitFlags, NetPort, PathBeneath, PathFd, Ruleset, RulesetAttr,
+    RulesetCreated, RulesetCreatedAttr, RulesetStatus, Scope, ABI,
 };
+use nix::libc;
 use std::fs;
-use std::path::Path;
+use std::os::fd::{AsRawFd, OwnedFd};
+use std::path::{Path, PathBuf};
 use tracing::{debug, info, warn};
 
 /// The target ABI version we support (highest we know about)
-const TARGET_ABI: ABI = ABI::V5;
+const TARGET_ABI: ABI = ABI::V6;
+
+/// `landlock_create_ruleset` flag that asks for the ABI version
+const LANDLOCK_CREATE_RULESET_VERSION: libc::c_uint = 1 << 0;
+
+/// Landlock features beyond basic filesystem control: the ABI version that
+/// introduced each, its name and what it protects
+const FEATURES: &[(i32, &str, &str)] = &[
+    (2, "refer", "rename and link across directories"),
+    (3, "truncate", "file truncation"),
+    (4, "network", "TCP bind and connect filtering"),
+    (5, "ioctl", "device ioctl control"),
+    (6, "scoping", "abstract UNIX socket and signal scoping"),
+];
+
+/// The Landlock ABI version provided by the running kernel, or None if
+/// Landlock is unavailable
+pub fn kernel_abi() -> Option<i32> {
+    // SAFETY: with a null attribute pointer and size 0, the VERSION flag only
+    // queries the ABI version; no memory is read or written.
+    let version = unsafe {
+        libc::syscall(
+            libc::SYS_landlock_create_ruleset,
+            std::ptr::null::<libc::c_void>(),
+            0usize,
+            LANDLOCK_CREATE_RULESET_VERSION,
+        )
+    };
+    (version > 0).then_some(version as i32)
+}
+
+/// Which Landlock features the kernel provides, and which `caps` relies on
+pub fn enforcement_report(caps: &CapabilitySet) -> EnforcementReport {
+    report_for_abi(caps, kernel_abi())
+}
+
+/// [`enforcement_report`] for a kernel with Landlock ABI `abi`
+fn report_for_abi(caps: &CapabilitySet, abi: Option<i32>) -> EnforcementReport {
+    let backend = match abi {
+        Some(version) => format!(
+            "Landlock ABI v{} (nono targets v{})",
+            version, TARGET_ABI as i32
+        ),
+        None => "Landlock unavailable".to_string(),
+    };
+    let protections = FEATURES
+        .iter()
+        .map(|&(since, name, description)| Protection {
+            name,
+            description,
+            available: abi.is_some_and(|version| version >= since),
+            requested: match name {
+                // Without it renames and links across directories are
+                // refused, which only matters where the sandbox may write
+                "refer" => caps.fs.iter().any(|cap| cap.access.writes()),
+                // Without it any file the user can write may be truncated,
+                // whatever the grants
+                "truncate" => true,
+                "network" => caps.net_block || caps.has_port_rules(),
+                "ioctl" => !caps.ioctl_devices.is_empty(),
+                // The default IPC scopes are hardening on top of the grants,
+                // applied when the kernel has them
+                _ => false,
+            },
+        })
+        .collect();
+    EnforcementReport {
+        backend,
+        protections,
+    }
+}
+
+/// Fail if `caps` asks for full enforcement and the kernel lacks a protection
+/// it relies on
+fn check_full_enforcement(caps: &CapabilitySet) -> Result<()> {
+    if !caps.require_full_enforcement {
+        return Ok(());
+    }
+    let report = enforcement_report(caps);
+    let missing: Vec<String> = report
+        .missing_requested()
+        .map(|p| format!("{} ({})", p.name, p.description))
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+    Err(NonoError::SandboxInit(format!(
+        "--require-full-enforcement: {} cannot enforce {}",
+        report.backend,
+        missing.join(", ")
+    )))
+}
 
 /// Check if Landlock is supported on this system
 pub fn is_supported() -> bool {
@@ -28,7 +124,13 @@ pub fn support_info() -> String {
         .handle_access(AccessFs::from_all(TARGET_ABI))
         .and_then(|r| r.create())
     {
-        Ok(_) => format!("Landlock available (targeting ABI v{:?})", TARGET_ABI),
+        Ok(_) => match kernel_abi() {
+            Some(version) => format!(
+                "Landlock available (kernel ABI v{}, targeting v{})",
+                version, TARGET_ABI as i32
+            ),
+            None => format!("Landlock available (targeting ABI v{})", TARGET_ABI as i32),
+        },
         Err(_) => {
             "Landlock not available. Requires Linux kernel 5.13+ with Landlock enabled.".to_string()
         }
@@ -43,6 +145,10 @@ pub fn support_info() -> String {
 fn access_to_landlock(access: FsAccess, _abi: ABI) -> BitFlags<AccessFs> {
     match access {
         FsAccess::Read => AccessFs::ReadFile | AccessFs::ReadDir | AccessFs::Execute,
+        FsAccess::ReadNoExec => AccessFs::ReadFile | AccessFs::ReadDir,
+        // Landlock checks ReadFile when execve opens the binary, so exec-only
+        // files stay readable by name; leaving out ReadDir stops listing.
+        FsAccess::Execute => AccessFs::Execute | AccessFs::ReadFile,
         FsAccess::Write => {
             // Write access includes all operations needed for normal file manipulation:
             // - WriteFile: modify file contents
@@ -76,9 +182,181 @@ fn access_to_landlock(access: FsAccess, _abi: ABI) -> BitFlags<AccessFs> {
     }
 }
 
-/// Apply Landlock sandbox with the given capabilities
+/// IPC scopes to enforce: every ABI V6 scope the profile has not opted out of
+fn ipc_scopes(caps: &CapabilitySet) -> BitFlags<Scope> {
+    let mut scopes = Scope::from_all(TARGET_ABI);
+    if caps.allow_abstract_sockets {
+        scopes.remove(Scope::AbstractUnixSocket);
+    }
+    if caps.allow_signals {
+        scopes.remove(Scope::Signal);
+    }
+    scopes
+}
+
+/// A Landlock ruleset that has been fully built but not yet enforced.
+///
+/// Building the ruleset opens path FDs and allocates, so it must happen in the
+/// parent. Enforcing it is a single `landlock_restrict_self` call, which is
+/// safe to perform in a freshly forked child.
+pub struct PendingSandbox {
+    ruleset: RulesetCreated,
+    /// A duplicate of the ruleset FD, for rules the child adds itself
+    fd: Option<OwnedFd>,
+    /// The syscall filter, installed after the ruleset by
+    /// [`PendingSandbox::apply_in_child`] and [`apply`]
+    syscalls: Option<SeccompFilter>,
+}
+
+impl PendingSandbox {
+    /// Enforce the ruleset (not the syscall filter) on the current thread,
+    /// logging the outcome.
+    pub fn apply(self) -> Result<()> {
+        // Apply the ruleset - THIS IS IRREVERSIBLE
+        let status = self
+            .ruleset
+            .restrict_self()
+            .map_err(|e| NonoError::SandboxInit(format!("Failed to restrict self: {}", e)))?;
+
+        match status.ruleset {
+            RulesetStatus::FullyEnforced => {
+                info!("Landlock sandbox fully enforced");
+            }
+            RulesetStatus::PartiallyEnforced => {
+                // This is normal - the kernel supports a subset of features we requested.
+                // The sandbox is still active and enforcing restrictions.
+                debug!("Landlock sandbox enforced in best-effort mode");
+            }
+            RulesetStatus::NotEnforced => {
+                return Err(NonoError::SandboxInit(
+                    "Landlock sandbox was not enforced".to_string(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Enforce the ruleset and then the syscall filter from a forked child.
+    ///
+    /// Only performs `prctl(PR_SET_NO_NEW_PRIVS)`, `landlock_restrict_self` and
+    /// `seccomp()`, all async-signal-safe. Returns `false` if the sandbox is
+    /// not in effect.
+    pub fn apply_in_child(self) -> bool {
+        let enforced = match self.ruleset.restrict_self() {
+            Ok(status) => !matches!(status.ruleset, RulesetStatus::NotEnforced),
+            Err(_) => false,
+        };
+        enforced
+            && self
+                .syscalls
+                .as_ref()
+                .map_or(true, install_syscall_filter_in_child)
+    }
+
+    /// Allow reading `path` (NUL-terminated), which was mounted in the child
+    /// after the ruleset was built, like a system path.
+    ///
+    /// Async-signal-safe: `open(O_PATH)` and `landlock_add_rule` only. Returns
+    /// `false` on failure.
+    pub fn allow_read_in_child(&self, path: &[u8]) -> bool {
+        /// `struct landlock_path_beneath_attr` (not in libc)
+        #[repr(C, packed)]
+        struct PathBeneathAttr {
+            allowed_access: u64,
+            parent_fd: i32,
+        }
+        const LANDLOCK_RULE_PATH_BENEATH: libc::c_int = 1;
+
+        let Some(fd) = &self.fd else {
+            // Landlock is unavailable, so there are no rules to add to
+            return true;
+        };
+        // SAFETY: `path` is NUL-terminated and `attr` outlives the syscall
+        unsafe {
+            let parent = libc::open(
+                path.as_ptr() as *const libc::c_char,
+                libc::O_PATH | libc::O_CLOEXEC,
+            );
+            if parent < 0 {
+                return false;
+            }
+            let attr = PathBeneathAttr {
+                allowed_access: access_to_landlock(FsAccess::Read, TARGET_ABI).bits(),
+                parent_fd: parent,
+            };
+            let rc = libc::syscall(
+                libc::SYS_landlock_add_rule,
+                fd.as_raw_fd(),
+                LANDLOCK_RULE_PATH_BENEATH,
+                &attr as *const PathBeneathAttr,
+                0u32,
+            );
+            libc::close(parent);
+            rc == 0
+        }
+    }
+}
+
+/// Apply Landlock sandbox with the given capabilities, then the syscall filter
 pub fn apply(caps: &CapabilitySet) -> Result<()> {
+    let mut pending = prepare(caps)?;
+    let syscalls = pending.syscalls.take();
+    pending.apply()?;
+    match syscalls {
+        Some(filter) if !install_syscall_filter_in_child(&filter) => {
+            Err(NonoError::SandboxInit(format!(
+                "Failed to install the syscall filter: {}",
+                std::io::Error::last_os_error()
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The seccomp filter denying what the security lists and `caps` deny, or
+/// `None` if nothing is denied. Names nono does not know (from a lists
+/// update for a newer nono) are skipped.
+fn syscall_filter(caps: &CapabilitySet) -> Option<SeccompFilter> {
+    let denied = config::denied_syscalls(&caps.allowed_syscalls, &caps.denied_syscalls);
+    let numbers: Vec<libc::c_long> = denied
+        .iter()
+        .filter_map(|name| {
+            let nr = seccomp::syscall_number(name);
+            if nr.is_none() {
+                warn!("Not filtering unknown syscall '{}'", name);
+            }
+            nr
+        })
+        .collect();
+    if numbers.is_empty() {
+        return None;
+    }
+    debug!("Denying syscalls: {}", denied.join(", "));
+    Some(SeccompFilter::deny(&numbers))
+}
+
+/// Install `filter` on the calling thread. Async-signal-safe.
+fn install_syscall_filter_in_child(filter: &SeccompFilter) -> bool {
+    // Landlock has set this already unless the kernel lacks it
+    // SAFETY: prctl with integer arguments has no memory-safety preconditions
+    let no_new_privs = unsafe { libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) };
+    no_new_privs == 0 && filter.install_in_child() == 0
+}
+
+/// Build the Landlock ruleset for the given capabilities without enforcing it
+pub fn prepare(caps: &CapabilitySet) -> Result<PendingSandbox> {
+    prepare_with_private_dirs(caps, &[])
+}
+
+/// [`prepare`], also granting full access to `private_dirs`: the private
+/// directories of an isolated root, which only the command can see
+pub fn prepare_with_private_dirs(
+    caps: &CapabilitySet,
+    private_dirs: &[PathBuf],
+) -> Result<PendingSandbox> {
     info!("Using Landlock ABI {:?}", TARGET_ABI);
+    check_full_enforcement(caps)?;
 
     // Determine which access rights to handle based on ABI
     let handled_fs = AccessFs::from_all(TARGET_ABI);
@@ -91,20 +369,31 @@ pub fn apply(caps: &CapabilitySet) -> Result<()> {
         .handle_access(handled_fs)
         .map_err(|e| NonoError::SandboxInit(format!("Failed to handle fs access: {}", e)))?;
 
-    // Add network access handling if blocking network (ABI V4+ required)
-    let ruleset_builder = if caps.net_block {
-        let handled_net = AccessNet::from_all(TARGET_ABI);
-        if !handled_net.is_empty() {
-            debug!("Handling network access (blocking): {:?}", handled_net);
-            ruleset_builder.handle_access(handled_net).map_err(|e| {
-                NonoError::SandboxInit(format!("Failed to handle net access: {}", e))
-            })?
-        } else {
-            warn!("Network blocking requested but kernel ABI doesn't support it (requires V4+)");
-            ruleset_builder
+    // Add network access handling if blocking network or filtering TCP ports
+    // (ABI V4+ required). With no NetPort rules, handling means full denial.
+    let ruleset_builder = if caps.net_block || caps.has_port_rules() {
+        if kernel_abi().map_or(true, |version| version < 4) {
+            warn!("Network filtering requested but kernel ABI doesn't support it (requires V4+)");
         }
+        let handled_net = AccessNet::from_all(TARGET_ABI);
+        debug!("Handling network access: {:?}", handled_net);
+        ruleset_builder
+            .handle_access(handled_net)
+            .map_err(|e| NonoError::SandboxInit(format!("Failed to handle net access: {}", e)))?
+    } else {
+        ruleset_builder
+    };
+
+    // IPC scoping (ABI V6+): keep the sandbox from connecting to abstract UNIX
+    // sockets or signalling processes outside it. Older kernels ignore it.
+    let scopes = ipc_scopes(caps);
+    let ruleset_builder = if scopes.is_empty() {
+        ruleset_builder
     } else {
+        debug!("Scoping IPC: {:?}", scopes);
         ruleset_builder
+            .scope(scopes)
+            .map_err(|e| NonoError::SandboxInit(format!("Failed to scope IPC: {}", e)))?
     };
 
     let mut ruleset = ruleset_builder
@@ -176,28 +465,264 @@ pub fn apply(caps: &CapabilitySet) -> Result<()> {
             })?;
     }
 
-    // Apply the ruleset - THIS IS IRREVERSIBLE
-    let status = ruleset
-        .restrict_self()
-        .map_err(|e| NonoError::SandboxInit(format!("Failed to restrict self: {}", e)))?;
+    let private_access = access_to_landlock(FsAccess::ReadWrite, TARGET_ABI);
+    for path in private_dirs {
+        debug!("Adding private directory rule: {}", path.display());
+        let path_fd = PathFd::new(path)?;
+        ruleset = ruleset
+            .add_rule(PathBeneath::new(path_fd, private_access))
+            .map_err(|e| {
+                NonoError::SandboxInit(format!(
+                    "Cannot add Landlock rule for {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+    }
+
+    // Device ioctls (ABI V5+) are denied except on the listed devices. The
+    // rule grants nothing else: opening a device still needs read or write access.
+    for path in &caps.ioctl_devices {
+        debug!("Adding ioctl rule: {}", path.display());
+        let path_fd = PathFd::new(path)?;
+        ruleset = ruleset
+            .add_rule(PathBeneath::new(path_fd, AccessFs::IoctlDev))
+            .map_err(|e| {
+                NonoError::SandboxInit(format!(
+                    "Cannot add Landlock ioctl rule for {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+    }
 
-    match status.ruleset {
-        landlock::RulesetStatus::FullyEnforced => {
-            info!("Landlock sandbox fully enforced");
+    // Add TCP port rules. Everything not listed here is denied once network
+    // access is handled.
+    if caps.has_port_rules() {
+        for port in caps.effective_connect_ports() {
+            debug!("Adding TCP connect rule: port {}", port);
+            ruleset = ruleset
+                .add_rule(NetPort::new(port, AccessNet::ConnectTcp))
+                .map_err(|e| {
+                    NonoError::SandboxInit(format!(
+                        "Cannot add Landlock connect rule for port {}: {}",
+                        port, e
+                    ))
+                })?;
         }
-        landlock::RulesetStatus::PartiallyEnforced => {
-            // This is normal - the kernel supports a subset of features we requested.
-            // The sandbox is still active and enforcing restrictions.
-            debug!("Landlock sandbox enforced in best-effort mode");
+        for &port in &caps.tcp_bind_ports {
+            debug!("Adding TCP bind rule: port {}", port);
+            ruleset = ruleset
+                .add_rule(NetPort::new(port, AccessNet::BindTcp))
+                .map_err(|e| {
+                    NonoError::SandboxInit(format!(
+                        "Cannot add Landlock bind rule for port {}: {}",
+                        port, e
+                    ))
+                })?;
         }
-        landlock::RulesetStatus::NotEnforced => {
-            return Err(NonoError::SandboxInit(
-                "Landlock sandbox was not enforced".to_string(),
-            ));
+    }
+
+    let fd = ruleset
+        .try_clone()
+        .map_err(|e| NonoError::SandboxInit(format!("Failed to duplicate ruleset: {}", e)))?
+        .into();
+    Ok(PendingSandbox {
+        ruleset,
+        fd,
+        syscalls: syscall_filter(caps),
+    })
+}
+
+/// What the command's root contains with `--isolate`: a private `/tmp`, the
+/// system read paths (read-only) and the capability paths, plus
+/// `extra_files` read-only. Parents come before the paths beneath them.
+///
+/// The command mounts its own `/proc` over the host's once it is in its PID
+/// namespace.
+pub fn isolation_mounts(caps: &CapabilitySet, extra_files: &[PathBuf]) -> Vec<RootMount> {
+    let system = config::get_system_read_paths()
+        .iter()
+        .filter_map(|path_str| {
+            let path = Path::new(path_str);
+            let meta = fs::symlink_metadata(path).ok()?;
+            if meta.file_type().is_symlink() {
+                // e.g. /lib -> usr/lib, /dev/stdin -> /proc/self/fd/0
+                return Some(RootMount::Symlink {
+                    path: path.to_path_buf(),
+                    target: fs::read_link(path).ok()?,
+                });
+            }
+            Some(RootMount::Bind {
+                path: path.canonicalize().ok()?,
+                read_only: true,
+                required: false,
+            })
+        })
+        .collect();
+    root_mounts(caps, system, extra_files)
+}
+
+fn root_mounts(
+    caps: &CapabilitySet,
+    system: Vec<RootMount>,
+    extra_files: &[PathBuf],
+) -> Vec<RootMount> {
+    // A system path inside another one comes with it
+    let system_dirs: Vec<PathBuf> = system
+        .iter()
+        .filter_map(|entry| match entry {
+            RootMount::Bind { path, .. } => Some(path.clone()),
+            _ => None,
+        })
+        .collect();
+    let mut mounts = vec![RootMount::Tmpfs {
+        path: PathBuf::from("/tmp"),
+    }];
+    for entry in system {
+        if let RootMount::Bind { ref path, .. } = entry {
+            if system_dirs
+                .iter()
+                .any(|dir| dir != path && path.starts_with(dir))
+                || mounts.contains(&entry)
+            {
+                continue;
+            }
         }
+        mounts.push(entry);
     }
+    mounts.extend(caps.fs.iter().map(|cap| RootMount::Bind {
+        path: cap.resolved.clone(),
+        read_only: !cap.access.writes(),
+        required: true,
+    }));
+    mounts.extend(extra_files.iter().map(|path| RootMount::Bind {
+        path: path.clone(),
+        read_only: true,
+        required: false,
+    }));
+
+    // Stable, so a grant of a system path is mounted over the system bind
+    mounts.sort_by_key(|entry| entry.path().components().count());
+    mounts
+}
+
+/// Sensitive paths (`config::get_sensitive_paths`) that a broader grant
+/// would expose.
+///
+/// A Landlock rule covers the whole hierarchy beneath it, so `--read ~` also
+/// grants `~/.ssh`. These paths are hidden from the command in the
+/// supervisor's mount namespace instead (see `namespace`). As on macOS, a
+/// sensitive path stays reachable only if the user granted it, or something
+/// inside it, explicitly, or if the user config overrides it. Returns
+/// canonical paths that exist.
+pub fn exposed_sensitive_paths(caps: &CapabilitySet) -> Vec<HiddenPath> {
+    let home = std::env::var("HOME")
+        .ok()
+        .filter(|h| Path::new(h).is_absolute());
+    let expand = |path: &String| match (path.strip_prefix('~'), &home) {
+        (Some(rest), Some(home)) => Some(format!("{}{}", home, rest)),
+        (Some(_), None) => None,
+        (None, _) => Some(path.clone()),
+    };
+    let sensitive: Vec<PathBuf> = config::get_sensitive_paths()
+        .iter()
+        .filter_map(expand)
+        .filter_map(|path| Path::new(&path).canonicalize().ok())
+        .collect();
+    let system: Vec<PathBuf> = config::get_system_read_paths()
+        .iter()
+        .filter_map(|path| Path::new(path).canonicalize().ok())
+        .collect();
+    let overrides: Vec<(PathBuf, &OverrideInfo)> = config::effective_config()
+        .allowed_sensitive
+        .iter()
+        .filter_map(|(path, info)| {
+            let path = Path::new(&expand(path)?).canonicalize().ok()?;
+            Some((path, info))
+        })
+        .collect();
+    exposed_paths(caps, &sensitive, &system, &overrides)
+}
+
+/// The `sensitive` paths that a directory capability or system read path
+/// covers, minus those the user granted explicitly, with the entries that
+/// `overrides` open up
+fn exposed_paths(
+    caps: &CapabilitySet,
+    sensitive: &[PathBuf],
+    system: &[PathBuf],
+    overrides: &[(PathBuf, &OverrideInfo)],
+) -> Vec<HiddenPath> {
+    let mut exposed: Vec<PathBuf> = sensitive
+        .iter()
+        .filter(|path| !caps.fs.iter().any(|cap| cap.resolved.starts_with(path)))
+        .filter(|path| {
+            caps.fs
+                .iter()
+                .any(|cap| !cap.is_file && path.starts_with(&cap.resolved))
+                || system.iter().any(|dir| path.starts_with(dir))
+        })
+        .cloned()
+        .collect();
+    exposed.sort();
+    exposed.dedup();
+
+    // A mask can be lifted, or made read-only, but it cannot let writes
+    // through while hiding the contents
+    let reveal = |path: &Path, info: &OverrideInfo| {
+        if info.allows(FsAccess::Read) {
+            Some(Reveal {
+                path: path.to_path_buf(),
+                read_only: !info.allows(FsAccess::Write),
+            })
+        } else {
+            warn!(
+                "Write-only override for {} cannot be enforced on Linux; it stays hidden",
+                path.display()
+            );
+            None
+        }
+    };
+
+    exposed
+        .into_iter()
+        .filter_map(|path| {
+            // The closest override on the path itself or one of its parents
+            let whole = overrides
+                .iter()
+                .filter(|(overridden, _)| path.starts_with(overridden))
+                .max_by_key(|(overridden, _)| overridden.components().count());
+            if let Some((_, info)) = whole {
+                return match reveal(&path, info) {
+                    Some(r) if !r.read_only => None,
+                    Some(r) => Some(HiddenPath {
+                        path,
+                        reveal: vec![r],
+                    }),
+                    None => Some(HiddenPath::new(path)),
+                };
+            }
 
-    Ok(())
+            let mut inner: Vec<Reveal> = overrides
+                .iter()
+                .filter(|(overridden, _)| overridden.starts_with(&path))
+                .filter_map(|(overridden, info)| reveal(overridden, info))
+                .collect();
+            inner.sort_by(|a, b| a.path.cmp(&b.path));
+            // Entries inside another revealed entry come with it
+            let mut outer: Vec<Reveal> = Vec::new();
+            for r in inner {
+                if !outer.iter().any(|o| r.path.starts_with(&o.path)) {
+                    outer.push(r);
+                }
+            }
+            Some(HiddenPath {
+                path,
+                reveal: outer,
+            })
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -211,12 +736,149 @@ mod tests {
         let _ = is_supported();
     }
 
+    #[test]
+    fn test_report_basic_caps_below_v6() {
+        use crate::capability::FsCapability;
+
+        let mut caps = CapabilitySet::new();
+        caps.fs.push(FsCapability {
+            original: PathBuf::from("/tmp"),
+            resolved: PathBuf::from("/tmp"),
+            access: FsAccess::ReadWrite,
+            is_file: false,
+            ttl: None,
+            expires_at: None,
+        });
+
+        // A plain `--allow DIR` relies on nothing a v4 kernel lacks
+        let report = report_for_abi(&caps, Some(4));
+        assert_eq!(report.missing().count(), 2);
+        assert_eq!(report.missing_requested().count(), 0);
+
+        // Device ioctl rules do rely on v5
+        caps.ioctl_devices.push(PathBuf::from("/dev/null"));
+        let report = report_for_abi(&caps, Some(4));
+        let missing: Vec<_> = report.missing_requested().map(|p| p.name).collect();
+        assert_eq!(missing, vec!["ioctl"]);
+
+        // So does network filtering on v3, and cross-directory renames of
+        // writable paths on v1
+        caps.net_block = true;
+        let report = report_for_abi(&caps, Some(1));
+        let missing: Vec<_> = report.missing_requested().map(|p| p.name).collect();
+        assert_eq!(missing, vec!["refer", "truncate", "network", "ioctl"]);
+    }
+
     #[test]
     fn test_support_info() {
         let info = support_info();
         assert!(!info.is_empty());
     }
 
+    #[test]
+    fn test_exposed_paths() {
+        use crate::capability::FsCapability;
+
+        let grant = |path: &str, is_file: bool| FsCapability {
+            original: PathBuf::from(path),
+            resolved: PathBuf::from(path),
+            access: FsAccess::Read,
+            is_file,
+            ttl: None,
+            expires_at: None,
+        };
+        let sensitive = [
+            PathBuf::from("/home/u/.ssh"),
+            PathBuf::from("/home/u/.aws"),
+            PathBuf::from("/home/u/.bashrc"),
+            PathBuf::from("/scratch/.env"),
+        ];
+        let system = [PathBuf::from("/scratch")];
+
+        let mut caps = CapabilitySet::new();
+        caps.fs.push(grant("/home/u", false));
+        // Granting something inside a sensitive path opts it out, as on macOS
+        caps.fs.push(grant("/home/u/.aws/config", true));
+
+        assert_eq!(
+            exposed_paths(&caps, &sensitive, &system, &[]),
+            vec![
+                HiddenPath::new(PathBuf::from("/home/u/.bashrc")),
+                HiddenPath::new(PathBuf::from("/home/u/.ssh")),
+                HiddenPath::new(PathBuf::from("/scratch/.env")),
+            ]
+        );
+
+        // Nothing covers them without the broad grant
+        let caps = CapabilitySet::new();
+        assert_eq!(
+            exposed_paths(&caps, &sensitive[..3], &system, &[]),
+            Vec::<HiddenPath>::new()
+        );
+    }
+
+    #[test]
+    fn test_exposed_paths_with_overrides() {
+        use crate::capability::FsCapability;
+        use crate::config::OverrideSource;
+
+        let info = |access: Option<&str>| OverrideInfo {
+            reason: "test".to_string(),
+            acknowledged: Some("2025-01-15".to_string()),
+            source: OverrideSource::UserConfig,
+            access: access.map(String::from),
+        };
+        let (both, read, write) = (info(None), info(Some("read")), info(Some("write")));
+
+        let mut caps = CapabilitySet::new();
+        caps.fs.push(FsCapability {
+            original: PathBuf::from("/home/u"),
+            resolved: PathBuf::from("/home/u"),
+            access: FsAccess::ReadWrite,
+            is_file: false,
+            ttl: None,
+            expires_at: None,
+        });
+        let sensitive = [
+            PathBuf::from("/home/u/.ssh"),
+            PathBuf::from("/home/u/.aws"),
+            PathBuf::from("/home/u/.gnupg"),
+            PathBuf::from("/home/u/.kube"),
+        ];
+        let overrides = [
+            // Inside a sensitive path: revealed in the mask
+            (PathBuf::from("/home/u/.ssh/id_rsa.pub"), &read),
+            (PathBuf::from("/home/u/.ssh/known_hosts"), &both),
+            // The whole path: read-only, or not hidden at all
+            (PathBuf::from("/home/u/.aws"), &read),
+            (PathBuf::from("/home/u/.gnupg"), &both),
+            // Write-only cannot be enforced and stays hidden
+            (PathBuf::from("/home/u/.kube"), &write),
+        ];
+
+        let reveal = |path: &str, read_only| Reveal {
+            path: PathBuf::from(path),
+            read_only,
+        };
+        assert_eq!(
+            exposed_paths(&caps, &sensitive, &[], &overrides),
+            vec![
+                HiddenPath {
+                    path: PathBuf::from("/home/u/.aws"),
+                    reveal: vec![reveal("/home/u/.aws", true)],
+                },
+                HiddenPath::new(PathBuf::from("/home/u/.kube")),
+                HiddenPath {
+                    path: PathBuf::from("/home/u/.ssh"),
+                    reveal: vec![
+                        reveal("/home/u/.ssh/id_rsa.pub", true),
+                        reveal("/home/u/.ssh/known_hosts", false),
+                    ],
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_access_conversion() {
         let abi = ABI::V3;
@@ -235,6 +897,14 @@ mod tests {
         // Verify directory removal is still NOT included (defense in depth)
         assert!(!write.contains(AccessFs::RemoveDir));
 
+        let noexec = access_to_landlock(FsAccess::ReadNoExec, abi);
+        assert!(noexec.contains(AccessFs::ReadFile | AccessFs::ReadDir));
+        assert!(!noexec.contains(AccessFs::Execute));
+
+        let exec = access_to_landlock(FsAccess::Execute, abi);
+        assert!(exec.contains(AccessFs::Execute));
+        assert!(!exec.contains(AccessFs::ReadDir));
+
         let rw = access_to_landlock(FsAccess::ReadWrite, abi);
         assert!(rw.contains(AccessFs::ReadFile));
         assert!(rw.contains(AccessFs::WriteFile));
@@ -245,4 +915,107 @@ mod tests {
         // Verify directory removal is still NOT included
         assert!(!rw.contains(AccessFs::RemoveDir));
     }
+
+    #[test]
+    fn test_root_mounts() {
+        use crate::capability::FsCapability;
+
+        let grant = |path: &str, access: FsAccess| FsCapability {
+            original: PathBuf::from(path),
+            resolved: PathBuf::from(path),
+            access,
+            is_file: false,
+            ttl: None,
+            expires_at: None,
+        };
+        let system_bind = |path: &str| RootMount::Bind {
+            path: PathBuf::from(path),
+            read_only: true,
+            required: false,
+        };
+        let system = vec![
+            system_bind("/usr/lib/x86_64-linux-gnu"),
+            system_bind("/usr/lib"),
+            RootMount::Symlink {
+                path: PathBuf::from("/lib"),
+                target: PathBuf::from("usr/lib"),
+            },
+            system_bind("/usr/lib"),
+        ];
+        let mut caps = CapabilitySet::new();
+        caps.fs.push(grant("/home/u/project", FsAccess::ReadWrite));
+        caps.fs.push(grant("/home/u/.config/tool", FsAccess::Read));
+
+        let mounts = root_mounts(&caps, system, &[PathBuf::from("/tmp/.nono-1.json")]);
+        let paths: Vec<&Path> = mounts.iter().map(|m| m.path()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                Path::new("/tmp"),
+                Path::new("/lib"),
+                Path::new("/usr/lib"),
+                Path::new("/tmp/.nono-1.json"),
+                Path::new("/home/u/project"),
+                Path::new("/home/u/.config/tool"),
+            ]
+        );
+        assert_eq!(
+            mounts[4],
+            RootMount::Bind {
+                path: PathBuf::from("/home/u/project"),
+                read_only: false,
+                required: true,
+            }
+        );
+        assert_eq!(
+            mounts[5],
+            RootMount::Bind {
+                path: PathBuf::from("/home/u/.config/tool"),
+                read_only: true,
+                required: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_ipc_scopes() {
+        let mut caps = CapabilitySet::new();
+        assert_eq!(ipc_scopes(&caps), Scope::AbstractUnixSocket | Scope::Signal);
+
+        caps.allow_abstract_sockets = true;
+        assert_eq!(ipc_scopes(&caps), BitFlags::from(Scope::Signal));
+
+        caps.allow_signals = true;
+        assert!(ipc_scopes(&caps).is_empty());
+    }
+
+    #[test]
+    fn test_enforcement_report() {
+        let requested = |report: &EnforcementReport, name: &str| {
+            report
+                .protections
+                .iter()
+                .find(|p| p.name == name)
+                .unwrap()
+                .requested
+        };
+
+        let mut caps = CapabilitySet::new();
+        let report = enforcement_report(&caps);
+        assert!(requested(&report, "truncate"));
+        assert!(!requested(&report, "refer"));
+        assert!(!requested(&report, "network"));
+        assert!(!requested(&report, "ioctl"));
+        assert!(!requested(&report, "scoping"));
+
+        caps.net_block = true;
+        let report = enforcement_report(&caps);
+        assert!(requested(&report, "network"));
+
+        // Availability follows the running kernel's ABI
+        let abi = kernel_abi().unwrap_or(0);
+        for (protection, &(since, _, _)) in report.protections.iter().zip(FEATURES) {
+            assert_eq!(protection.available, abi >= since);
+        }
+    }
 }
@@ -10,14 +10,36 @@ use tracing::{debug, info};
 // These are private APIs but have been stable for years
 // Reference: https://reverse.put.as/wp-content/uploads/2011/09/Apple-Sandbox-Guide-v1.0.pdf
 
-// Flags for sandbox_init
-// 0 = raw profile string (what we use)
-// SANDBOX_NAMED = 1 = use a named profile from disk
-#[allow(dead_code)]
-const SANDBOX_NAMED: u32 = 0x0001;
+// A profile is compiled from its source (what sandbox_init does internally)
+// and the compiled form applied separately, so that a forked child only has
+// to make the system call that installs it.
+
+/// Compiled profile (opaque)
+#[repr(C)]
+struct SandboxProfile {
+    _private: [u8; 0],
+}
+
+/// Profile parameters (opaque)
+#[repr(C)]
+struct SandboxParams {
+    _private: [u8; 0],
+}
 
 extern "C" {
-    fn sandbox_init(profile: *const c_char, flags: u64, errorbuf: *mut *mut c_char) -> i32;
+    fn sandbox_create_params() -> *mut SandboxParams;
+
+    fn sandbox_free_params(params: *mut SandboxParams);
+
+    fn sandbox_compile_string(
+        profile: *const c_char,
+        params: *mut SandboxParams,
+        errorbuf: *mut *mut c_char,
+    ) -> *mut SandboxProfile;
+
+    fn sandbox_apply(profile: *mut SandboxProfile) -> i32;
+
+    fn sandbox_free_profile(profile: *mut SandboxProfile);
 
     fn sandbox_free_error(errorbuf: *mut c_char);
 }
@@ -56,6 +78,79 @@ fn expand_home(path: &str) -> String {
     path.to_string()
 }
 
+/// System paths every sandboxed command may write to: devices, the temp
+/// directories and the user's caches and logs
+pub fn system_write_paths() -> Vec<String> {
+    let mut paths: Vec<String> = [
+        "/dev",
+        "/private/tmp",
+        "/tmp",
+        "/private/var/folders",
+        "/var/folders",
+    ]
+    .iter()
+    .map(|p| p.to_string())
+    .collect();
+    if let Ok(tmpdir) = std::env::var("TMPDIR") {
+        paths.push(tmpdir);
+    }
+    if let Ok(home) = std::env::var("HOME") {
+        paths.push(format!("{}/Library/Caches", home));
+        paths.push(format!("{}/Library/Logs", home));
+    }
+    paths
+}
+
+/// Escape regex metacharacters for a Seatbelt `regex` filter
+fn escape_regex(literal: &str) -> String {
+    let mut escaped = String::with_capacity(literal.len());
+    for c in literal.chars() {
+        if "\\^$.|?*+()[]{}\"".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Seatbelt filter matching a capability's file or directory
+fn path_filter(cap: &crate::capability::FsCapability) -> String {
+    let escaped = cap
+        .resolved
+        .display()
+        .to_string()
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"");
+    if cap.is_file {
+        format!("literal \"{}\"", escaped)
+    } else {
+        format!("subpath \"{}\"", escaped)
+    }
+}
+
+/// Paths strictly inside `sensitive` that a user config override opens for
+/// reading and that a read capability covers
+fn overridden_inner_paths(sensitive: &str, caps: &CapabilitySet) -> Vec<String> {
+    let sensitive = std::path::Path::new(sensitive);
+    let mut inner: Vec<String> = config::effective_config()
+        .allowed_sensitive
+        .iter()
+        .filter(|(_, info)| info.allows(FsAccess::Read))
+        .map(|(target, _)| expand_home(target))
+        .filter(|target| {
+            let target = std::path::Path::new(target);
+            target != sensitive
+                && target.starts_with(sensitive)
+                && caps
+                    .fs
+                    .iter()
+                    .any(|cap| cap.access.reads() && target.starts_with(&cap.resolved))
+        })
+        .collect();
+    inner.sort();
+    inner
+}
+
 /// Collect parent directories that need metadata access for path resolution.
 ///
 /// Programs need to lstat() each path component when resolving paths.
@@ -109,6 +204,32 @@ fn generate_profile(caps: &CapabilitySet) -> String {
     profile.push_str("(allow process-exec*)\n"); // Execute programs
     profile.push_str("(allow process-fork)\n"); // Fork child processes
 
+    // Read-only-no-exec grants refuse exec beneath them; exec-only grants
+    // (coming later) allow it again where nested inside one
+    for access in [FsAccess::ReadNoExec, FsAccess::Execute] {
+        for cap in caps.fs.iter().filter(|cap| cap.access == access) {
+            let verb = if access == FsAccess::Execute {
+                "allow"
+            } else {
+                "deny"
+            };
+            profile.push_str(&format!(
+                "({} process-exec* ({}))\n",
+                verb,
+                path_filter(cap)
+            ));
+        }
+    }
+
+    // Blocked commands cannot be executed anywhere in the process tree,
+    // under any path (matched by file name, like the top-level check)
+    for command in config::blocked_commands(&caps.allowed_commands, &caps.blocked_commands) {
+        profile.push_str(&format!(
+            "(deny process-exec* (regex #\"/{}$\"))\n",
+            escape_regex(&command)
+        ));
+    }
+
     // Process info: allow self-inspection (needed for dyld, code signing, etc.)
     // but deny inspecting OTHER processes (blocks `ps aux` style info leaks)
     profile.push_str("(allow process-info* (target self))\n");
@@ -133,7 +254,7 @@ fn generate_profile(caps: &CapabilitySet) -> String {
     // Explicitly allow only system paths and user-granted paths
 
     // Allow reading the root directory entry itself (NOT subpaths)
-    // This is required because nono uses sandbox_init() then exec().
+    // This is required because nono applies the profile then exec().
     // When exec() runs, the kernel resolves the binary path which requires
     // stat/readdir on "/" for path canonicalization.
     // Note: (literal "/") only allows access to "/" itself, NOT files under it.
@@ -195,9 +316,13 @@ fn generate_profile(caps: &CapabilitySet) -> String {
         };
 
         match cap.access {
-            FsAccess::Read | FsAccess::ReadWrite => {
+            FsAccess::Read | FsAccess::ReadWrite | FsAccess::ReadNoExec => {
                 profile.push_str(&format!("(allow file-read* ({}))\n", path_filter));
             }
+            FsAccess::Execute => {
+                // Exec-only: files can be found and run, not read or listed
+                profile.push_str(&format!("(allow file-read-metadata ({}))\n", path_filter));
+            }
             FsAccess::Write => {
                 // Write-only doesn't need read access
             }
@@ -229,7 +354,13 @@ fn generate_profile(caps: &CapabilitySet) -> String {
             cap.resolved.starts_with(sensitive_path)
         });
 
-        if !user_granted {
+        // An acknowledged override in the user config that opens the whole
+        // path for reading lifts the protection like an explicit grant
+        let overridden = config::effective_config()
+            .sensitive_override(std::path::Path::new(&path))
+            .is_some_and(|(_, info)| info.allows(FsAccess::Read));
+
+        if !user_granted && !overridden {
             // Allow metadata access (stat, existence checks) for graceful error handling
             profile.push_str(&format!(
                 "(allow file-read-metadata (subpath \"{}\"))\n",
@@ -240,37 +371,22 @@ fn generate_profile(caps: &CapabilitySet) -> String {
                 "(deny file-read-data (subpath \"{}\"))\n",
                 escaped_path
             ));
+
+            // Overrides for entries inside the sensitive path re-allow reads
+            // after the deny, but only where a capability grants them
+            for inner in overridden_inner_paths(&path, caps) {
+                let escaped = inner.replace('\\', "\\\\").replace('"', "\\\"");
+                profile.push_str(&format!("(allow file-read* (subpath \"{}\"))\n", escaped));
+            }
         }
     }
 
     // Allow writes only to specific system paths and granted paths
-    profile.push_str("(allow file-write*\n");
-    profile.push_str("    (subpath \"/dev\")\n");
-    profile.push_str("    (subpath \"/private/tmp\")\n");
-    profile.push_str("    (subpath \"/tmp\")\n");
-    profile.push_str("    (subpath \"/private/var/folders\")\n");
-    profile.push_str("    (subpath \"/var/folders\")\n");
-    profile.push_str(")\n");
-
-    // Allow TMPDIR for writes too
-    if let Ok(tmpdir) = std::env::var("TMPDIR") {
-        let escaped = tmpdir.replace('\\', "\\\\").replace('"', "\\\"");
+    for path in system_write_paths() {
+        let escaped = path.replace('\\', "\\\\").replace('"', "\\\"");
         profile.push_str(&format!("(allow file-write* (subpath \"{}\"))\n", escaped));
     }
 
-    // Allow writes to user's ~/Library/Caches and ~/Library/Logs
-    if let Ok(home) = std::env::var("HOME") {
-        let home_escaped = home.replace('\\', "\\\\").replace('"', "\\\"");
-        profile.push_str(&format!(
-            "(allow file-write* (subpath \"{}/Library/Caches\"))\n",
-            home_escaped
-        ));
-        profile.push_str(&format!(
-            "(allow file-write* (subpath \"{}/Library/Logs\"))\n",
-            home_escaped
-        ));
-    }
-
     // 5. Block destructive file operations globally (BEFORE user-granted allows)
     // In Seatbelt, specific allows override broader denies when the allow comes later.
     // By placing the global deny first, user-granted paths can still allow deletion.
@@ -295,18 +411,67 @@ fn generate_profile(caps: &CapabilitySet) -> String {
                 // Allow file deletion (unlink) for writable paths
                 profile.push_str(&format!("(allow file-write-unlink ({}))\n", path_filter));
             }
-            FsAccess::Read => {
-                // Read-only doesn't need write access
+            FsAccess::Read | FsAccess::ReadNoExec | FsAccess::Execute => {
+                // Read-only and exec-only don't need write access
             }
         }
     }
 
+    // 7. Deny paths excluded with --deny or a profile `deny` entry
+    // These come after every allow above, so they win even inside granted
+    // directories. Like sensitive paths, metadata stays visible.
+    for path in &caps.deny {
+        let escaped_path = path
+            .display()
+            .to_string()
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"");
+        profile.push_str(&format!(
+            "(deny file-read-data file-write* (subpath \"{}\"))\n",
+            escaped_path
+        ));
+    }
+
     // Network rules
     // Note: macOS Seatbelt supports some filtering (tcp/udp, local/remote, ports)
     // but not per-host filtering. For that, a proxy-based approach is needed.
     if caps.net_block {
         // Network blocked
         profile.push_str("(deny network*)\n");
+    } else if caps.has_port_rules() {
+        // TCP port filtering: only listed ports may be connected to or bound.
+        // UDP and unix socket connects (DNS resolution through mDNSResponder
+        // among them) stay allowed, matching Landlock, which only filters TCP.
+        profile.push_str("(allow network-outbound (remote udp \"*:*\"))\n");
+        profile.push_str("(allow network-bind (local udp \"*:*\"))\n");
+        profile.push_str("(allow network-inbound (local udp \"*:*\"))\n");
+        profile.push_str("(allow network-outbound (remote unix-socket))\n");
+        for port in &caps.tcp_connect_ports {
+            profile.push_str(&format!(
+                "(allow network-outbound (remote tcp \"*:{}\"))\n",
+                port
+            ));
+        }
+        // The proxy listens on loopback only; any other host on its port
+        // would be a way around the host allowlist
+        if let Some(port) = caps.proxy_port {
+            if !caps.tcp_connect_ports.contains(&port) {
+                profile.push_str(&format!(
+                    "(allow network-outbound (remote tcp \"localhost:{}\"))\n",
+                    port
+                ));
+            }
+        }
+        for port in &caps.tcp_bind_ports {
+            profile.push_str(&format!(
+                "(allow network-bind (local tcp \"*:{}\"))\n",
+                port
+            ));
+            profile.push_str(&format!(
+                "(allow network-inbound (local tcp \"*:{}\"))\n",
+                port
+            ));
+        }
     } else {
         // Network access enabled (default) - allow outbound, inbound, and bind
         profile.push_str("(allow network-outbound)\n");
@@ -317,27 +482,71 @@ fn generate_profile(caps: &CapabilitySet) -> String {
     profile
 }
 
+/// A compiled Seatbelt profile that has not yet been applied.
+///
+/// The profile is generated and compiled in the parent (both allocate), so
+/// that the child only has to call `sandbox_apply` after fork.
+pub struct PendingSandbox {
+    compiled: *mut SandboxProfile,
+}
+
+impl PendingSandbox {
+    /// Apply the profile to the current process.
+    pub fn apply(self) -> Result<()> {
+        let result = unsafe { sandbox_apply(self.compiled) };
+        if result != 0 {
+            return Err(NonoError::SandboxInit(format!(
+                "sandbox_apply failed: {}",
+                std::io::Error::last_os_error()
+            )));
+        }
+
+        info!("Seatbelt sandbox applied successfully");
+        Ok(())
+    }
+
+    /// Apply the profile from a forked child.
+    ///
+    /// Async-signal-safe: a single system call on the profile compiled before
+    /// fork, which is not freed (the child is about to exec). Returns `false`
+    /// if `sandbox_apply` failed.
+    pub fn apply_in_child(self) -> bool {
+        let result = unsafe { sandbox_apply(self.compiled) };
+        std::mem::forget(self);
+        result == 0
+    }
+}
+
+impl Drop for PendingSandbox {
+    fn drop(&mut self) {
+        unsafe { sandbox_free_profile(self.compiled) };
+    }
+}
+
 /// Apply Seatbelt sandbox with the given capabilities
 pub fn apply(caps: &CapabilitySet) -> Result<()> {
+    prepare(caps)?.apply()
+}
+
+/// Generate and compile the Seatbelt profile for the given capabilities
+/// without applying it
+pub fn prepare(caps: &CapabilitySet) -> Result<PendingSandbox> {
     let profile = generate_profile(caps);
 
     debug!("Generated Seatbelt profile:\n{}", profile);
 
-    let profile_cstr = CString::new(profile)
+    let profile = CString::new(profile)
         .map_err(|e| NonoError::SandboxInit(format!("Invalid profile string: {}", e)))?;
 
     let mut error_buf: *mut c_char = ptr::null_mut();
-
-    // Use 0 flag for raw profile string (not a named profile)
-    let result = unsafe {
-        sandbox_init(
-            profile_cstr.as_ptr(),
-            0, // Raw profile mode
-            &mut error_buf,
-        )
+    let compiled = unsafe {
+        let params = sandbox_create_params();
+        let compiled = sandbox_compile_string(profile.as_ptr(), params, &mut error_buf);
+        sandbox_free_params(params);
+        compiled
     };
 
-    if result != 0 {
+    if compiled.is_null() {
         let error_msg = if !error_buf.is_null() {
             let msg = unsafe {
                 std::ffi::CStr::from_ptr(error_buf)
@@ -347,14 +556,13 @@ pub fn apply(caps: &CapabilitySet) -> Result<()> {
             unsafe { sandbox_free_error(error_buf) };
             msg
         } else {
-            format!("sandbox_init returned error code {}", result)
+            "sandbox_compile_string failed".to_string()
         };
 
         return Err(NonoError::SandboxInit(error_msg));
     }
 
-    info!("Seatbelt sandbox applied successfully");
-    Ok(())
+    Ok(PendingSandbox { compiled })
 }
 
 #[cfg(test)]
@@ -382,6 +590,8 @@ mod tests {
             resolved: PathBuf::from("/test"),
             access: FsAccess::ReadWrite,
             is_file: false,
+            ttl: None,
+            expires_at: None,
         });
 
         let profile = generate_profile(&caps);
@@ -399,6 +609,8 @@ mod tests {
             resolved: PathBuf::from("/test.txt"),
             access: FsAccess::Write,
             is_file: true,
+            ttl: None,
+            expires_at: None,
         });
 
         let profile = generate_profile(&caps);
@@ -407,6 +619,62 @@ mod tests {
         assert!(profile.contains("literal \"/test.txt\""));
     }
 
+    #[test]
+    fn test_generate_profile_noexec_and_exec_only() {
+        let mut caps = CapabilitySet::default();
+        for (path, access) in [
+            ("/project", FsAccess::ReadNoExec),
+            ("/project/bin", FsAccess::Execute),
+        ] {
+            caps.fs.push(FsCapability {
+                original: PathBuf::from(path),
+                resolved: PathBuf::from(path),
+                access,
+                is_file: false,
+                ttl: None,
+                expires_at: None,
+            });
+        }
+
+        let profile = generate_profile(&caps);
+
+        assert!(profile.contains("(allow file-read* (subpath \"/project\"))"));
+        assert!(profile.contains("(allow file-read-metadata (subpath \"/project/bin\"))"));
+        assert!(!profile.contains("(allow file-read* (subpath \"/project/bin\"))"));
+        // The nested exec-only grant overrides the enclosing no-exec one
+        let deny = profile
+            .find("(deny process-exec* (subpath \"/project\"))")
+            .expect("noexec rule");
+        let allow = profile
+            .find("(allow process-exec* (subpath \"/project/bin\"))")
+            .expect("exec rule");
+        assert!(allow > deny);
+    }
+
+    #[test]
+    fn test_generate_profile_deny_after_allow() {
+        let mut caps = CapabilitySet::default();
+        caps.fs.push(FsCapability {
+            original: PathBuf::from("/work"),
+            resolved: PathBuf::from("/work"),
+            access: FsAccess::ReadWrite,
+            is_file: false,
+            ttl: None,
+            expires_at: None,
+        });
+        caps.add_deny(PathBuf::from("/work/.git/hooks"));
+
+        let profile = generate_profile(&caps);
+
+        let allow = profile
+            .find("(allow file-write* (subpath \"/work\"))")
+            .expect("write allow");
+        let deny = profile
+            .find("(deny file-read-data file-write* (subpath \"/work/.git/hooks\"))")
+            .expect("deny rule");
+        assert!(deny > allow, "deny must come after the allow it overrides");
+    }
+
     #[test]
     fn test_generate_profile_network_allowed() {
         let caps = CapabilitySet {
@@ -438,6 +706,41 @@ mod tests {
         assert!(!profile.contains("(allow network-outbound)"));
     }
 
+    #[test]
+    fn test_generate_profile_port_rules() {
+        let caps = CapabilitySet {
+            tcp_connect_ports: vec![443],
+            tcp_bind_ports: vec![3000],
+            ..Default::default()
+        };
+
+        let profile = generate_profile(&caps);
+
+        assert!(profile.contains("(allow network-outbound (remote tcp \"*:443\"))"));
+        assert!(profile.contains("(allow network-bind (local tcp \"*:3000\"))"));
+        // Unix sockets are not TCP, as on Linux
+        assert!(profile.contains("(allow network-outbound (remote unix-socket))"));
+        // Blanket allow rules must not be emitted when filtering ports
+        assert!(!profile.contains("(allow network-outbound)\n"));
+        assert!(!profile.contains("(allow network-bind)\n"));
+    }
+
+    #[test]
+    fn test_generate_profile_proxy_port() {
+        let caps = CapabilitySet {
+            allow_hosts: vec!["api.anthropic.com".to_string()],
+            proxy_port: Some(40000),
+            ..Default::default()
+        };
+
+        let profile = generate_profile(&caps);
+
+        // Only the proxy is reachable over TCP, and only on loopback
+        assert!(profile.contains("(allow network-outbound (remote tcp \"localhost:40000\"))"));
+        assert!(!profile.contains("(remote tcp \"*:40000\")"));
+        assert!(!profile.contains("(allow network-outbound)\n"));
+    }
+
     #[test]
     fn test_support_info() {
         let info = support_info();
@@ -494,6 +797,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_blocked_commands_cannot_exec() {
+        let mut caps = CapabilitySet::default();
+        caps.allowed_commands.push("dd".to_string());
+        caps.blocked_commands.push("my.tool".to_string());
+        let profile = generate_profile(&caps);
+
+        assert!(profile.contains("(deny process-exec* (regex #\"/rm$\"))"));
+        assert!(profile.contains("(deny process-exec* (regex #\"/my\\.tool$\"))"));
+        assert!(!profile.contains("#\"/dd$\""));
+        // Denies follow the blanket allow they override
+        let allow = profile.find("(allow process-exec*)").expect("allow rule");
+        let deny = profile.find("(deny process-exec*").expect("deny rule");
+        assert!(deny > allow);
+    }
+
     #[test]
     fn test_profile_no_blanket_file_read_allow() {
         let caps = CapabilitySet::default();
@@ -535,6 +854,8 @@ mod tests {
             resolved: PathBuf::from("/Users/test/project"),
             access: FsAccess::Read,
             is_file: false,
+            ttl: None,
+            expires_at: None,
         });
 
         let profile = generate_profile(&caps);
@@ -562,6 +883,8 @@ mod tests {
             resolved: PathBuf::from("/Users/test/.claude"),
             access: FsAccess::ReadWrite,
             is_file: false,
+            ttl: None,
+            expires_at: None,
         });
 
         let parents = collect_parent_dirs(&caps);
@@ -583,6 +906,8 @@ mod tests {
             resolved: PathBuf::from("/Users/test/.claude"),
             access: FsAccess::ReadWrite,
             is_file: false,
+            ttl: None,
+            expires_at: None,
         });
 
         let profile = generate_profile(&caps);
@@ -656,6 +981,8 @@ mod tests {
             resolved: PathBuf::from("/Users/test/.sshfoo"),
             access: FsAccess::Read,
             is_file: false,
+            ttl: None,
+            expires_at: None,
         });
 
         let profile = generate_profile(&caps);
@@ -678,6 +1005,8 @@ mod tests {
             resolved: PathBuf::from("/Users/test/.ssh"),
             access: FsAccess::Read,
             is_file: false,
+            ttl: None,
+            expires_at: None,
         });
 
         let profile = generate_profile(&caps);
@@ -705,6 +1034,8 @@ mod tests {
             resolved: PathBuf::from("/Users/test/.ssh/keys"),
             access: FsAccess::Read,
             is_file: false,
+            ttl: None,
+            expires_at: None,
         });
 
         let profile = generate_profile(&caps);
@@ -728,6 +1059,8 @@ mod tests {
             resolved: PathBuf::from("/Users/test/projects"),
             access: FsAccess::Read,
             is_file: false,
+            ttl: None,
+            expires_at: None,
         });
 
         let profile = generate_profile(&caps);
@@ -756,6 +1089,8 @@ mod tests {
             resolved: PathBuf::from("/Users/test/.awsbackup"),
             access: FsAccess::Read,
             is_file: false,
+            ttl: None,
+            expires_at: None,
         });
         // Try to bypass ~/.gnupg with ~/.gnupg2
         caps.fs.push(FsCapability {
@@ -763,6 +1098,8 @@ mod tests {
             resolved: PathBuf::from("/Users/test/.gnupg2"),
             access: FsAccess::Read,
             is_file: false,
+            ttl: None,
+            expires_at: None,
         });
 
         let profile = generate_profile(&caps);
@@ -790,6 +1127,8 @@ mod tests {
             resolved: PathBuf::from("/Users/test/.ssh/"),
             access: FsAccess::Read,
             is_file: false,
+            ttl: None,
+            expires_at: None,
         });
 
         let profile = generate_profile(&caps);
@@ -1,9 +1,19 @@
 use crate::capability::CapabilitySet;
 use crate::error::Result;
+use std::path::PathBuf;
 
 #[cfg(target_os = "linux")]
 mod linux;
 
+#[cfg(target_os = "linux")]
+pub use linux::kernel_abi as landlock_abi;
+
+#[cfg(target_os = "linux")]
+pub mod namespace;
+
+#[cfg(target_os = "linux")]
+pub mod seccomp;
+
 #[cfg(target_os = "macos")]
 mod macos;
 
@@ -31,6 +41,323 @@ pub fn apply(caps: &CapabilitySet) -> Result<()> {
     }
 }
 
+/// Whether the command can modify or remove `path` (canonical): through a
+/// write grant, or on macOS through the system paths it may always write to
+pub fn is_writable(caps: &CapabilitySet, path: &std::path::Path) -> bool {
+    #[cfg(target_os = "macos")]
+    let system = macos::system_write_paths()
+        .iter()
+        .any(|dir| path.starts_with(dir));
+    #[cfg(not(target_os = "macos"))]
+    let system = false;
+
+    system || caps.grants_write_to(path)
+}
+
+/// A path the command must not see although a capability covers it
+#[derive(Debug, Clone, PartialEq)]
+pub struct HiddenPath {
+    pub path: PathBuf,
+    /// Entries inside `path` that a user config override keeps visible.
+    /// An entry for `path` itself leaves it visible but read-only.
+    pub reveal: Vec<Reveal>,
+}
+
+/// An overridden entry inside a hidden path
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reveal {
+    pub path: PathBuf,
+    pub read_only: bool,
+}
+
+impl HiddenPath {
+    /// A path hidden entirely
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            reveal: Vec::new(),
+        }
+    }
+}
+
+/// Paths the command must not see although a capability covers them.
+///
+/// These are the `--deny` paths and, on Linux, sensitive paths inside a
+/// broader grant. Paths inside another hidden path are dropped, as are
+/// reveals that overlap a `--deny` path. Always empty on macOS, where the
+/// Seatbelt profile denies both itself.
+pub fn hidden_paths(caps: &CapabilitySet) -> Vec<HiddenPath> {
+    #[cfg(target_os = "linux")]
+    {
+        let mut hidden: Vec<HiddenPath> = Vec::new();
+        for entry in caps
+            .deny
+            .iter()
+            .cloned()
+            .map(HiddenPath::new)
+            .chain(linux::exposed_sensitive_paths(caps))
+        {
+            if hidden
+                .iter()
+                .any(|outer| entry.path.starts_with(&outer.path))
+            {
+                continue;
+            }
+            hidden.retain(|inner| !inner.path.starts_with(&entry.path));
+            hidden.push(entry);
+        }
+        for entry in &mut hidden {
+            entry.reveal.retain(|reveal| {
+                !caps
+                    .deny
+                    .iter()
+                    .any(|deny| deny.starts_with(&reveal.path) || reveal.path.starts_with(deny))
+            });
+        }
+        hidden
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = caps;
+        Vec::new()
+    }
+}
+
+/// Hide `paths` (see [`hidden_paths`]) from processes started after this call.
+///
+/// The paths are masked in a private mount namespace, so this must run in the
+/// supervisor while it is still single-threaded and before the policy is
+/// prepared.
+pub fn hide_paths(paths: &[HiddenPath]) -> Result<()> {
+    #[cfg(target_os = "linux")]
+    {
+        namespace::hide(paths)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = paths;
+        Ok(())
+    }
+}
+
+/// Let the command be started in a PID namespace of its own (see
+/// `--isolate-pids`).
+///
+/// Like [`hide_paths`], this must run in the supervisor while it is still
+/// single-threaded.
+pub fn prepare_pid_namespace() -> Result<()> {
+    #[cfg(target_os = "linux")]
+    {
+        namespace::enter()
+            .map_err(|e| crate::error::NonoError::SandboxInit(format!("--isolate-pids: {}", e)))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(crate::error::NonoError::SandboxInit(
+            "--isolate-pids requires Linux (PID and user namespaces)".to_string(),
+        ))
+    }
+}
+
+/// The root filesystem of an `--isolate` run, holding only the granted and
+/// system read paths. Removed when dropped.
+pub struct IsolatedRoot {
+    #[cfg(target_os = "linux")]
+    inner: namespace::IsolatedRoot,
+}
+
+impl IsolatedRoot {
+    /// Move a forked child into the root.
+    ///
+    /// Async-signal-safe. Returns `false` on failure, in which case the caller
+    /// must not exec.
+    pub fn enter_in_child(&self) -> bool {
+        #[cfg(target_os = "linux")]
+        {
+            self.inner.enter_in_child()
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            false
+        }
+    }
+}
+
+/// Build a root holding only what `caps` grants (see `--isolate`), plus
+/// `extra_files` read-only.
+///
+/// Like [`hide_paths`], this must run in the supervisor while it is still
+/// single-threaded, after any other mounts the command should see.
+pub fn isolate(caps: &CapabilitySet, extra_files: &[PathBuf]) -> Result<IsolatedRoot> {
+    #[cfg(target_os = "linux")]
+    {
+        Ok(IsolatedRoot {
+            inner: namespace::IsolatedRoot::build(&linux::isolation_mounts(caps, extra_files))?,
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (caps, extra_files);
+        Err(crate::error::NonoError::SandboxInit(
+            "--isolate requires Linux (user and mount namespaces)".to_string(),
+        ))
+    }
+}
+
+/// A sandbox policy compiled in the current process but not yet enforced.
+///
+/// Used by the supervised strategy: the parent prepares the policy (which may
+/// allocate and open files), then only the forked child enforces it, leaving
+/// the parent unsandboxed.
+pub struct PendingSandbox {
+    #[cfg(target_os = "linux")]
+    inner: linux::PendingSandbox,
+
+    #[cfg(target_os = "macos")]
+    inner: macos::PendingSandbox,
+}
+
+impl PendingSandbox {
+    /// Enforce the policy on the calling thread.
+    ///
+    /// Landlock domains are per-thread, so on Linux this restricts only the
+    /// calling thread and the threads it spawns afterwards. The notify
+    /// supervisor relies on this to test operations against the child's
+    /// exact policy without sandboxing itself.
+    #[cfg(target_os = "linux")]
+    pub fn apply_to_thread(self) -> Result<()> {
+        self.inner.apply()
+    }
+
+    /// Let a forked child read the private `/proc` it mounted for its PID
+    /// namespace. Async-signal-safe; returns `false` on failure.
+    #[cfg(target_os = "linux")]
+    pub fn allow_proc_in_child(&self) -> bool {
+        self.inner.allow_read_in_child(b"/proc\0")
+    }
+
+    /// Enforce the policy in a forked child.
+    ///
+    /// Async-signal-safe: performs no allocation and no logging. Returns `false`
+    /// if the sandbox could not be applied, in which case the caller must not exec.
+    pub fn apply_in_child(self) -> bool {
+        #[cfg(any(target_os = "linux", target_os = "macos"))]
+        {
+            self.inner.apply_in_child()
+        }
+
+        #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+        {
+            false
+        }
+    }
+}
+
+/// A kernel protection the sandbox builds on (on Linux, a Landlock feature)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Protection {
+    /// Short name, e.g. "truncate"
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Whether the running kernel provides it
+    pub available: bool,
+    /// Whether the capability set relies on it
+    pub requested: bool,
+}
+
+/// What the running kernel can enforce for a capability set
+#[derive(Debug, Clone)]
+pub struct EnforcementReport {
+    /// The sandbox backend and the version in use, e.g. "Landlock ABI v5"
+    pub backend: String,
+    /// Protections beyond the basic filesystem rules
+    pub protections: Vec<Protection>,
+}
+
+impl EnforcementReport {
+    /// Protections the running kernel does not provide
+    pub fn missing(&self) -> impl Iterator<Item = &Protection> {
+        self.protections.iter().filter(|p| !p.available)
+    }
+
+    /// Protections the capability set relies on that the kernel does not provide
+    pub fn missing_requested(&self) -> impl Iterator<Item = &Protection> {
+        self.missing().filter(|p| p.requested)
+    }
+}
+
+/// Report which protections the running kernel can enforce for `caps`.
+///
+/// Seatbelt has no optional features, so on macOS nothing is ever missing.
+pub fn enforcement_report(caps: &CapabilitySet) -> EnforcementReport {
+    #[cfg(target_os = "linux")]
+    {
+        linux::enforcement_report(caps)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = caps;
+        EnforcementReport {
+            backend: if cfg!(target_os = "macos") {
+                "Seatbelt".to_string()
+            } else {
+                std::env::consts::OS.to_string()
+            },
+            protections: Vec::new(),
+        }
+    }
+}
+
+/// Prepare the sandbox for the given capabilities without applying it.
+///
+/// See [`PendingSandbox`] for how the result is meant to be used.
+pub fn prepare(caps: &CapabilitySet) -> Result<PendingSandbox> {
+    #[cfg(target_os = "linux")]
+    {
+        Ok(PendingSandbox {
+            inner: linux::prepare(caps)?,
+        })
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        Ok(PendingSandbox {
+            inner: macos::prepare(caps)?,
+        })
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        let _ = caps;
+        Err(crate::error::NonoError::UnsupportedPlatform(
+            std::env::consts::OS.to_string(),
+        ))
+    }
+}
+
+/// [`prepare`] for a command that runs in `root`, which may also use the
+/// root's private `/tmp`
+pub fn prepare_isolated(caps: &CapabilitySet, root: &IsolatedRoot) -> Result<PendingSandbox> {
+    #[cfg(target_os = "linux")]
+    {
+        Ok(PendingSandbox {
+            inner: linux::prepare_with_private_dirs(caps, root.inner.private_dirs())?,
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = root;
+        prepare(caps)
+    }
+}
+
 /// Check if sandboxing is supported on this platform
 pub fn is_supported() -> bool {
     #[cfg(target_os = "linux")]
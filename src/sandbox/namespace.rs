@@ -0,0 +1,722 @@
+//! Private user and mount namespace for the supervised session (Linux only).
+//!
+//! Landlock can only add access, and a rule on a directory covers everything
+//! beneath it. To change what the command sees (copy-on-write overlays for
+//! `--ephemeral`, masks for `--deny` and sensitive paths, the fresh root for
+//! `--isolate`), the supervisor moves itself into a new user and mount
+//! namespace before it forks the command and mounts over the affected paths
+//! there. The host's mount table is never touched.
+//!
+//! The sandboxed child inherits these mounts and cannot undo them: a process
+//! under Landlock may not mount, unmount or pivot_root. It also gives up its
+//! capabilities in the namespace before it execs (see
+//! [`drop_capabilities_in_child`]), so a command run as root cannot override
+//! the permissions of a mask either.
+//!
+//! With `--isolate-pids`, the command is also forked into a new PID namespace,
+//! where it mounts its own `/proc` and stays behind as the namespace's init.
+
+use super::{HiddenPath, Reveal};
+use crate::error::{NonoError, Result};
+use nix::errno::Errno;
+use nix::libc;
+use nix::unistd::{ForkResult, Pid};
+use std::ffi::CString;
+use std::fs;
+use std::os::fd::AsRawFd;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use tracing::{debug, warn};
+
+/// Set once this process has moved into its private namespaces
+static ENTERED: AtomicBool = AtomicBool::new(false);
+
+/// Move the calling process into a private user and mount namespace.
+///
+/// Idempotent. Must be called while the process is still single-threaded
+/// (a requirement of `unshare(CLONE_NEWUSER)`).
+pub fn enter() -> Result<()> {
+    if ENTERED.load(Ordering::SeqCst) {
+        return Ok(());
+    }
+
+    let uid = nix::unistd::getuid();
+    let gid = nix::unistd::getgid();
+
+    // SAFETY: unshare has no memory-safety preconditions
+    if unsafe { libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNS) } != 0 {
+        let err = std::io::Error::last_os_error();
+        return Err(NonoError::SandboxInit(format!(
+            "unshare(CLONE_NEWUSER|CLONE_NEWNS) failed: {} ({})",
+            err,
+            userns_hint(|path| fs::read_to_string(path).ok())
+        )));
+    }
+
+    // Map our own IDs so files keep their real owners
+    let write = |path: &str, contents: String| {
+        fs::write(path, contents)
+            .map_err(|e| NonoError::SandboxInit(format!("failed to write {}: {}", path, e)))
+    };
+    write("/proc/self/setgroups", "deny".to_string())?;
+    write("/proc/self/uid_map", format!("{} {} 1", uid, uid))?;
+    write("/proc/self/gid_map", format!("{} {} 1", gid, gid))?;
+
+    // Keep our mounts out of the host namespace
+    mount(
+        None,
+        Path::new("/"),
+        None,
+        libc::MS_REC | libc::MS_PRIVATE,
+        None,
+    )
+    .map_err(|e| NonoError::SandboxInit(format!("failed to make / private: {}", e)))?;
+
+    ENTERED.store(true, Ordering::SeqCst);
+    debug!("Entered private user and mount namespace");
+    Ok(())
+}
+
+/// Sysctls that turn off unprivileged user namespaces, with the value that
+/// does so
+const USERNS_SYSCTLS: &[(&str, &str)] = &[
+    ("kernel.unprivileged_userns_clone", "0"),
+    ("user.max_user_namespaces", "0"),
+    ("kernel.apparmor_restrict_unprivileged_userns", "1"),
+];
+
+/// Explain why a user namespace could not be created, naming the sysctl
+/// that disables them if one is set. `read` returns a file's contents.
+fn userns_hint(read: impl Fn(&str) -> Option<String>) -> String {
+    for (sysctl, disabled) in USERNS_SYSCTLS {
+        let path = format!("/proc/sys/{}", sysctl.replace('.', "/"));
+        if read(&path).is_some_and(|value| value.trim() == *disabled) {
+            let enable = if *disabled == "0" { "1" } else { "0" };
+            return format!(
+                "unprivileged user namespaces are disabled by {}={}; \
+                 allow them with `sysctl -w {}={}`",
+                sysctl, disabled, sysctl, enable
+            );
+        }
+    }
+    format!(
+        "unprivileged user namespaces may be disabled on this system; check {}",
+        USERNS_SYSCTLS
+            .iter()
+            .map(|(sysctl, _)| *sysctl)
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+/// Drop every capability the calling process holds in the private user
+/// namespace, and keep an exec as root from granting them again.
+///
+/// The namespace's owner holds all capabilities in it, and a command run as
+/// root keeps them across exec; with CAP_DAC_OVERRIDE it could list and
+/// read through the mode of a mask. Does nothing outside the namespace.
+///
+/// Async-signal-safe: prctl only. Returns `false` on failure.
+pub fn drop_capabilities_in_child() -> bool {
+    if !ENTERED.load(Ordering::SeqCst) {
+        return true;
+    }
+    // SAFETY: prctl with integer arguments has no memory-safety
+    // preconditions
+    unsafe {
+        let securebits = libc::SECBIT_NOROOT | libc::SECBIT_NOROOT_LOCKED;
+        if libc::prctl(
+            libc::PR_SET_SECUREBITS,
+            securebits as libc::c_ulong,
+            0,
+            0,
+            0,
+        ) != 0
+            || libc::prctl(
+                libc::PR_CAP_AMBIENT,
+                libc::PR_CAP_AMBIENT_CLEAR_ALL as libc::c_ulong,
+                0,
+                0,
+                0,
+            ) != 0
+        {
+            return false;
+        }
+        // Capabilities are numbered from 0; the first invalid one ends the set
+        for cap in 0..64 {
+            if libc::prctl(libc::PR_CAPBSET_DROP, cap as libc::c_ulong, 0, 0, 0) != 0 {
+                return Errno::last() == Errno::EINVAL && cap > 0;
+            }
+        }
+    }
+    true
+}
+
+/// Thin wrapper around mount(2)
+pub fn mount(
+    source: Option<&Path>,
+    target: &Path,
+    fstype: Option<&str>,
+    flags: libc::c_ulong,
+    data: Option<&str>,
+) -> std::io::Result<()> {
+    let cstring = |bytes: &[u8]| {
+        CString::new(bytes).map_err(|_| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "argument contains NUL")
+        })
+    };
+    let source = source
+        .map(|s| cstring(s.as_os_str().as_bytes()))
+        .transpose()?;
+    let target = cstring(target.as_os_str().as_bytes())?;
+    let fstype = fstype.map(|s| cstring(s.as_bytes())).transpose()?;
+    let data = data.map(|s| cstring(s.as_bytes())).transpose()?;
+
+    let ptr = |s: &Option<CString>| s.as_ref().map_or(std::ptr::null(), |s| s.as_ptr());
+    // SAFETY: all pointers are null or valid NUL-terminated strings that
+    // outlive the call
+    let rc = unsafe {
+        libc::mount(
+            ptr(&source),
+            target.as_ptr(),
+            ptr(&fstype),
+            flags,
+            ptr(&data) as *const libc::c_void,
+        )
+    };
+    if rc == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+/// Look up the working directory again so it resolves through mounts made
+/// over it (or over one of its parents) since the process entered it
+pub fn refresh_cwd() {
+    if let Ok(cwd) = std::env::current_dir() {
+        if let Err(e) = std::env::set_current_dir(&cwd) {
+            warn!("Failed to re-enter {}: {}", cwd.display(), e);
+        }
+    }
+}
+
+/// Hide `paths` from the command.
+///
+/// A hidden directory is covered by an empty, read-only tmpfs that cannot be
+/// listed (mode 0000; the command holds no capability to override it, see
+/// [`drop_capabilities_in_child`]), a hidden file by a bind mount of
+/// `/dev/null` that refuses to open (`nodev`). The command cannot move a mask aside and recreate the path: a
+/// mount point cannot be renamed or unlinked, and Landlock never grants
+/// `RemoveDir`, which renaming one of its parent directories would need.
+///
+/// Entries a user config override reveals are bind-mounted back into the
+/// mask, read-only where the override only allows reading.
+pub fn hide(paths: &[HiddenPath]) -> Result<()> {
+    let Some(first) = paths.first() else {
+        return Ok(());
+    };
+    enter().map_err(|e| {
+        NonoError::SandboxInit(format!(
+            "cannot hide {} inside the granted directories: {}",
+            first.path.display(),
+            e
+        ))
+    })?;
+
+    for hidden in paths {
+        mask(&hidden.path, &hidden.reveal).map_err(|e| {
+            NonoError::SandboxInit(format!("failed to hide {}: {}", hidden.path.display(), e))
+        })?;
+        debug!("Hid {}", hidden.path.display());
+    }
+
+    refresh_cwd();
+    Ok(())
+}
+
+const MASK_FLAGS: libc::c_ulong =
+    libc::MS_RDONLY | libc::MS_NOSUID | libc::MS_NODEV | libc::MS_NOEXEC;
+
+fn mask(path: &Path, reveal: &[Reveal]) -> std::io::Result<()> {
+    if reveal.iter().any(|r| r.path == path) {
+        // Overridden as a whole for reading only
+        mount(Some(path), path, None, libc::MS_BIND, None)?;
+        return remount_read_only(path);
+    }
+
+    if !path.is_dir() {
+        mount(
+            Some(Path::new("/dev/null")),
+            path,
+            None,
+            libc::MS_BIND,
+            None,
+        )?;
+        return mount(
+            None,
+            path,
+            None,
+            libc::MS_BIND | libc::MS_REMOUNT | MASK_FLAGS,
+            None,
+        );
+    }
+
+    if reveal.is_empty() {
+        return mount(
+            Some(Path::new("tmpfs")),
+            path,
+            Some("tmpfs"),
+            MASK_FLAGS,
+            Some("mode=0000,size=4k"),
+        );
+    }
+
+    // Keep a handle on each revealed entry: the tmpfs covers them next
+    let handles = reveal
+        .iter()
+        .map(|r| {
+            let handle = fs::OpenOptions::new()
+                .read(true)
+                .custom_flags(libc::O_PATH | libc::O_CLOEXEC)
+                .open(&r.path)?;
+            Ok((r, handle, r.path.is_dir()))
+        })
+        .collect::<std::io::Result<Vec<_>>>()?;
+
+    mount(
+        Some(Path::new("tmpfs")),
+        path,
+        Some("tmpfs"),
+        MASK_FLAGS & !libc::MS_RDONLY,
+        Some("mode=0700,size=4k"),
+    )?;
+
+    // Recreate the way to each entry and bind the original over it
+    let mut dirs = vec![path.to_path_buf()];
+    for (r, handle, is_dir) in &handles {
+        for parent in r.path.ancestors().skip(1) {
+            if parent == path {
+                break;
+            }
+            if !dirs.iter().any(|d| d == parent) {
+                dirs.push(parent.to_path_buf());
+            }
+        }
+        if let Some(parent) = r.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if *is_dir {
+            fs::create_dir(&r.path)?;
+        } else {
+            fs::File::create(&r.path)?;
+        }
+        let source = PathBuf::from(format!("/proc/self/fd/{}", handle.as_raw_fd()));
+        mount(Some(&source), &r.path, None, libc::MS_BIND, None)?;
+        if r.read_only {
+            remount_read_only(&r.path)?;
+        }
+    }
+
+    // The mask itself can be traversed, but not listed or written
+    for dir in dirs.iter().rev() {
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o111))?;
+    }
+    mount(None, path, None, libc::MS_REMOUNT | MASK_FLAGS, None)
+}
+
+/// Remount the bind mount at `path` read-only.
+///
+/// Flags the mount inherited from the host are locked inside a user
+/// namespace and have to be repeated.
+fn remount_read_only(path: &Path) -> std::io::Result<()> {
+    use nix::sys::statvfs::{statvfs, FsFlags};
+
+    let current = statvfs(path).map_err(std::io::Error::from)?.flags();
+    let mut flags = libc::MS_BIND | libc::MS_REMOUNT | libc::MS_RDONLY;
+    for (st, ms) in [
+        (FsFlags::ST_NOSUID, libc::MS_NOSUID),
+        (FsFlags::ST_NODEV, libc::MS_NODEV),
+        (FsFlags::ST_NOEXEC, libc::MS_NOEXEC),
+        (FsFlags::ST_NOATIME, libc::MS_NOATIME),
+        (FsFlags::ST_NODIRATIME, libc::MS_NODIRATIME),
+        (FsFlags::ST_RELATIME, libc::MS_RELATIME),
+    ] {
+        if current.contains(st) {
+            flags |= ms;
+        }
+    }
+    mount(None, path, None, flags, None)
+}
+
+/// One entry of an isolated root (see [`IsolatedRoot`])
+#[derive(Debug, Clone, PartialEq)]
+pub enum RootMount {
+    /// Bind-mount `path` from the supervisor's view, with everything mounted
+    /// beneath it. A failed `required` bind is an error, any other is skipped.
+    Bind {
+        path: PathBuf,
+        read_only: bool,
+        required: bool,
+    },
+    /// Recreate the symlink at `path`, so it resolves inside the new root
+    Symlink { path: PathBuf, target: PathBuf },
+    /// Mount an empty, world-writable tmpfs at `path`
+    Tmpfs { path: PathBuf },
+}
+
+impl RootMount {
+    /// Where the entry appears in the new root
+    pub fn path(&self) -> &Path {
+        match self {
+            RootMount::Bind { path, .. }
+            | RootMount::Symlink { path, .. }
+            | RootMount::Tmpfs { path } => path,
+        }
+    }
+}
+
+/// A fresh root filesystem holding only selected paths, for `--isolate`.
+///
+/// The supervisor builds it on a tmpfs in its private mount namespace; the
+/// forked child moves into it with [`IsolatedRoot::enter_in_child`] right
+/// before the sandbox is applied. Everything not mounted into it does not
+/// exist for the command, not even for `stat`. The supervisor keeps the
+/// host's view, which it needs to clean up after the command.
+pub struct IsolatedRoot {
+    path: PathBuf,
+    path_c: CString,
+    cwd_c: CString,
+    /// The tmpfs mounts, as the supervisor sees them
+    private_dirs: Vec<PathBuf>,
+}
+
+impl IsolatedRoot {
+    /// Build the root from `mounts`, which must list parents before the
+    /// paths beneath them
+    pub fn build(mounts: &[RootMount]) -> Result<Self> {
+        enter().map_err(|e| NonoError::SandboxInit(format!("--isolate: {}", e)))?;
+
+        let path = std::env::temp_dir().join(format!("nono-root-{}", std::process::id()));
+        let err = |what: &str, path: &Path, e: std::io::Error| {
+            NonoError::SandboxInit(format!(
+                "--isolate: failed to {} {}: {}",
+                what,
+                path.display(),
+                e
+            ))
+        };
+        match fs::create_dir(&path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(err("create", &path, e)),
+        }
+        // Unbindable, so binding a parent of the staging directory (say a
+        // grant of the temp directory) does not pull the new root into itself
+        mount(
+            Some(Path::new("tmpfs")),
+            &path,
+            Some("tmpfs"),
+            libc::MS_NOSUID | libc::MS_NODEV,
+            Some("mode=0755"),
+        )
+        .and_then(|()| mount(None, &path, None, libc::MS_UNBINDABLE, None))
+        .map_err(|e| err("mount a tmpfs at", &path, e))?;
+
+        let c_path = |p: &Path| {
+            CString::new(p.as_os_str().as_bytes())
+                .map_err(|_| NonoError::SandboxInit(format!("path contains NUL: {}", p.display())))
+        };
+        let mut root = IsolatedRoot {
+            path_c: c_path(&path)?,
+            cwd_c: c_path(&std::env::current_dir().unwrap_or_else(|_| PathBuf::from("/")))?,
+            path,
+            private_dirs: Vec::new(),
+        };
+
+        for entry in mounts {
+            match root.add(entry) {
+                Ok(()) => {
+                    debug!("Isolated root: {:?}", entry);
+                    if let RootMount::Tmpfs { path } = entry {
+                        root.private_dirs.push(root.target(path));
+                    }
+                }
+                Err(e) => match entry {
+                    RootMount::Bind { required: true, .. } => {
+                        return Err(err("mount", entry.path(), e));
+                    }
+                    _ => debug!("Isolated root: skipping {}: {}", entry.path().display(), e),
+                },
+            }
+        }
+
+        // Nothing can be added next to the mounted paths
+        mount(
+            None,
+            &root.path,
+            None,
+            libc::MS_REMOUNT | libc::MS_RDONLY | libc::MS_NOSUID | libc::MS_NODEV,
+            None,
+        )
+        .map_err(|e| err("remount read-only", &root.path, e))?;
+        Ok(root)
+    }
+
+    /// Directories only the command can see, which it may use freely
+    pub fn private_dirs(&self) -> &[PathBuf] {
+        &self.private_dirs
+    }
+
+    /// Where `path` of the new root is in the supervisor's view
+    fn target(&self, path: &Path) -> PathBuf {
+        self.path.join(path.strip_prefix("/").unwrap_or(path))
+    }
+
+    fn add(&self, entry: &RootMount) -> std::io::Result<()> {
+        let target = self.target(entry.path());
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        match entry {
+            RootMount::Tmpfs { .. } => {
+                fs::create_dir_all(&target)?;
+                mount(
+                    Some(Path::new("tmpfs")),
+                    &target,
+                    Some("tmpfs"),
+                    libc::MS_NOSUID | libc::MS_NODEV,
+                    Some("mode=1777"),
+                )
+            }
+            RootMount::Symlink { target: link, .. } => {
+                // A parent may already provide it, e.g. through a bind
+                if fs::symlink_metadata(&target).is_ok() {
+                    return Ok(());
+                }
+                std::os::unix::fs::symlink(link, &target)
+            }
+            RootMount::Bind {
+                path, read_only, ..
+            } => {
+                if fs::symlink_metadata(&target).is_err() {
+                    if path.is_dir() {
+                        fs::create_dir(&target)?;
+                    } else {
+                        fs::File::create(&target)?;
+                    }
+                }
+                // Recursive, so masks and overlays on the path come along
+                mount(
+                    Some(path),
+                    &target,
+                    None,
+                    libc::MS_BIND | libc::MS_REC,
+                    None,
+                )?;
+                if *read_only {
+                    remount_read_only(&target)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Move the calling process into the root, in a mount namespace of its
+    /// own, and return to the working directory if it exists there.
+    ///
+    /// Async-signal-safe: raw syscalls on pre-built strings only. Returns
+    /// `false` on failure, in which case the caller must not exec.
+    pub fn enter_in_child(&self) -> bool {
+        const DOT: &[u8] = b".\0";
+        const SLASH: &[u8] = b"/\0";
+        let dot = DOT.as_ptr() as *const libc::c_char;
+        // SAFETY: all pointers are valid NUL-terminated strings owned by
+        // `self` or static. pivot_root(".", ".") stacks the old root on top
+        // of the new one, and unmounting "." then detaches the old root.
+        unsafe {
+            if libc::unshare(libc::CLONE_NEWNS) != 0
+                || libc::chdir(self.path_c.as_ptr()) != 0
+                || libc::syscall(libc::SYS_pivot_root, dot, dot) != 0
+                || libc::umount2(dot, libc::MNT_DETACH) != 0
+            {
+                return false;
+            }
+            libc::chdir(self.cwd_c.as_ptr()) == 0
+                || libc::chdir(SLASH.as_ptr() as *const libc::c_char) == 0
+        }
+    }
+}
+
+impl Drop for IsolatedRoot {
+    fn drop(&mut self) {
+        // SAFETY: path_c is a valid NUL-terminated string
+        unsafe {
+            libc::umount2(self.path_c.as_ptr(), libc::MNT_DETACH);
+        }
+        if let Err(e) = fs::remove_dir(&self.path) {
+            debug!("Failed to remove {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// Fork the command's process as PID 1 of a new PID namespace.
+///
+/// A raw `clone()` rather than `unshare(CLONE_NEWPID)` before `fork()`: the
+/// latter would keep the supervisor from starting threads afterwards. As
+/// after `fork()`, the child may only make async-signal-safe calls until it
+/// execs. Needs the private user namespace (see [`enter`]).
+pub fn fork_into_pid_namespace() -> nix::Result<ForkResult> {
+    // SAFETY: clone without CLONE_VM duplicates the process like fork(); the
+    // zero stack, TID and TLS arguments keep the parent's
+    let pid = unsafe {
+        libc::syscall(
+            libc::SYS_clone,
+            (libc::CLONE_NEWPID | libc::SIGCHLD) as libc::c_ulong,
+            0usize,
+            0usize,
+            0usize,
+            0usize,
+        )
+    };
+    match pid {
+        -1 => Err(Errno::last()),
+        0 => Ok(ForkResult::Child),
+        pid => Ok(ForkResult::Parent {
+            child: Pid::from_raw(pid as libc::pid_t),
+        }),
+    }
+}
+
+/// Mount a procfs of the caller's PID namespace over `/proc`, first moving
+/// into a mount namespace of its own if `unshare_mount_ns` is set.
+///
+/// Async-signal-safe. Returns `false` on failure.
+pub fn mount_proc_in_child(unshare_mount_ns: bool) -> bool {
+    const PROC: &[u8] = b"proc\0";
+    const TARGET: &[u8] = b"/proc\0";
+    // SAFETY: all pointers are static NUL-terminated strings or null
+    unsafe {
+        if unshare_mount_ns && libc::unshare(libc::CLONE_NEWNS) != 0 {
+            return false;
+        }
+        libc::mount(
+            PROC.as_ptr() as *const libc::c_char,
+            TARGET.as_ptr() as *const libc::c_char,
+            PROC.as_ptr() as *const libc::c_char,
+            libc::MS_NOSUID | libc::MS_NODEV | libc::MS_NOEXEC,
+            std::ptr::null(),
+        ) == 0
+    }
+}
+
+/// The command, as seen by the reaper
+static COMMAND_PID: AtomicI32 = AtomicI32::new(0);
+
+/// Fork the command off the calling process, which stays behind as PID 1 of
+/// its namespace and closes `close_fds`.
+///
+/// Async-signal-safe. Returns `true` in the command's process and `false` if
+/// the fork failed; the reaper never returns (see [`reap`]).
+pub fn fork_reaper_in_child(close_fds: &[libc::c_int]) -> bool {
+    // SAFETY: as in fork_into_pid_namespace
+    let pid = unsafe {
+        libc::syscall(
+            libc::SYS_clone,
+            libc::SIGCHLD as libc::c_ulong,
+            0usize,
+            0usize,
+            0usize,
+            0usize,
+        )
+    };
+    match pid {
+        -1 => false,
+        0 => true,
+        pid => {
+            for &fd in close_fds {
+                // SAFETY: closing an FD has no memory-safety preconditions
+                unsafe { libc::close(fd) };
+            }
+            reap(pid as libc::pid_t)
+        }
+    }
+}
+
+/// Run as PID 1 until `command` exits, then exit with its status.
+///
+/// The kernel reparents every orphan in the namespace to PID 1, so the reaper
+/// collects them, and kills whatever is left when PID 1 exits. Signals from
+/// the supervisor are forwarded to the command; those the terminal sends
+/// already reach it through its process group.
+fn reap(command: libc::pid_t) -> ! {
+    COMMAND_PID.store(command, Ordering::SeqCst);
+
+    extern "C" fn forward(sig: libc::c_int, info: *mut libc::siginfo_t, _: *mut libc::c_void) {
+        // SAFETY: the kernel passes a valid siginfo to SA_SIGINFO handlers
+        if unsafe { (*info).si_code } == libc::SI_KERNEL {
+            return;
+        }
+        let command = COMMAND_PID.load(Ordering::SeqCst);
+        // SAFETY: kill() is async-signal-safe
+        unsafe { libc::kill(command, sig) };
+    }
+
+    // SAFETY: sigaction, waitpid and _exit are async-signal-safe, and the
+    // sigaction struct is fully initialised before use
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = forward as *const () as usize;
+        action.sa_flags = libc::SA_SIGINFO | libc::SA_RESTART;
+        libc::sigemptyset(&mut action.sa_mask);
+        for sig in [libc::SIGINT, libc::SIGTERM, libc::SIGHUP, libc::SIGQUIT] {
+            libc::sigaction(sig, &action, std::ptr::null_mut());
+        }
+
+        let mut status = 0;
+        loop {
+            let pid = libc::waitpid(-1, &mut status, 0);
+            if pid == command {
+                break;
+            }
+            if pid < 0 && Errno::last() != Errno::EINTR {
+                libc::_exit(1);
+            }
+        }
+        if libc::WIFEXITED(status) {
+            libc::_exit(libc::WEXITSTATUS(status));
+        }
+        if libc::WIFSIGNALED(status) {
+            // PID 1 cannot be killed by its own signal: report it shell-style
+            libc::_exit(128 + libc::WTERMSIG(status));
+        }
+        libc::_exit(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_userns_hint_names_sysctl() {
+        let hint = userns_hint(|path| {
+            (path == "/proc/sys/user/max_user_namespaces").then(|| "0\n".to_string())
+        });
+        assert!(hint.contains("user.max_user_namespaces=0"));
+        assert!(hint.contains("sysctl -w user.max_user_namespaces=1"));
+
+        let hint = userns_hint(|path| {
+            path.ends_with("apparmor_restrict_unprivileged_userns")
+                .then(|| "1".to_string())
+        });
+        assert!(hint.contains("sysctl -w kernel.apparmor_restrict_unprivileged_userns=0"));
+
+        // Nothing set: list what to check
+        let hint = userns_hint(|_| None);
+        assert!(hint.contains("kernel.unprivileged_userns_clone"));
+        assert!(hint.contains("user.max_user_namespaces"));
+    }
+}
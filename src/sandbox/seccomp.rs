@@ -0,0 +1,623 @@
+//! Seccomp filters for the supervised child (Linux only).
+//!
+//! Landlock decides *whether* an operation is allowed, but a denial is final
+//! and invisible to nono. Seccomp user notification (`SECCOMP_RET_USER_NOTIF`,
+//! Linux 5.0+, fd injection 5.14+) lets the child stop on selected syscalls
+//! and hand them to the unsandboxed supervisor, which can perform the
+//! operation on the child's behalf or fail it with an errno.
+//!
+//! The filter is built in the parent (it allocates), installed in the forked
+//! child right before `execve()`, and the resulting listener FD is passed back
+//! to the supervisor over a Unix socket with `SCM_RIGHTS`.
+
+use nix::libc;
+use std::io;
+use std::os::fd::{AsRawFd, BorrowedFd, FromRawFd, OwnedFd};
+use tracing::debug;
+
+/// Audit architecture of the running kernel ABI, checked by every filter
+#[cfg(target_arch = "x86_64")]
+const AUDIT_ARCH: u32 = 0xc000_003e; // AUDIT_ARCH_X86_64
+#[cfg(target_arch = "aarch64")]
+const AUDIT_ARCH: u32 = 0xc000_00b7; // AUDIT_ARCH_AARCH64
+
+// ioctl request numbers from linux/seccomp.h (not exported by libc)
+const SECCOMP_IOCTL_NOTIF_RECV: libc::c_ulong = 0xc050_2100;
+const SECCOMP_IOCTL_NOTIF_SEND: libc::c_ulong = 0xc018_2101;
+const SECCOMP_IOCTL_NOTIF_ID_VALID: libc::c_ulong = 0x4008_2102;
+const SECCOMP_IOCTL_NOTIF_ADDFD: libc::c_ulong = 0x4018_2103;
+
+/// Syscall numbers of the x32 ABI, which shares the x86_64 audit arch
+#[cfg(target_arch = "x86_64")]
+const X32_SYSCALL_BIT: u32 = 0x4000_0000;
+
+/// Offsets into `struct seccomp_data`
+const DATA_NR_OFFSET: u32 = 0;
+const DATA_ARCH_OFFSET: u32 = 4;
+const DATA_ARGS_OFFSET: u32 = 16;
+
+/// Syscalls that open files by path on this architecture
+pub fn open_syscalls() -> Vec<libc::c_long> {
+    #[allow(unused_mut)]
+    let mut syscalls = vec![libc::SYS_openat, libc::SYS_openat2];
+    #[cfg(target_arch = "x86_64")]
+    syscalls.extend([libc::SYS_open, libc::SYS_creat]);
+    syscalls
+}
+
+/// Syscalls of io_uring, whose operations (opens included) the kernel
+/// performs without another syscall a filter could stop
+pub fn io_uring_syscalls() -> Vec<libc::c_long> {
+    vec![
+        libc::SYS_io_uring_setup,
+        libc::SYS_io_uring_enter,
+        libc::SYS_io_uring_register,
+    ]
+}
+
+/// Syscalls that `[syscalls]` in a profile or the security lists may name
+const SYSCALL_NAMES: &[(&str, libc::c_long)] = &[
+    ("acct", libc::SYS_acct),
+    ("add_key", libc::SYS_add_key),
+    ("adjtimex", libc::SYS_adjtimex),
+    ("bpf", libc::SYS_bpf),
+    ("chroot", libc::SYS_chroot),
+    ("clock_adjtime", libc::SYS_clock_adjtime),
+    ("clock_settime", libc::SYS_clock_settime),
+    ("delete_module", libc::SYS_delete_module),
+    ("fanotify_init", libc::SYS_fanotify_init),
+    ("finit_module", libc::SYS_finit_module),
+    ("fsconfig", libc::SYS_fsconfig),
+    ("fsmount", libc::SYS_fsmount),
+    ("fsopen", libc::SYS_fsopen),
+    ("fspick", libc::SYS_fspick),
+    ("init_module", libc::SYS_init_module),
+    ("io_uring_enter", libc::SYS_io_uring_enter),
+    ("io_uring_register", libc::SYS_io_uring_register),
+    ("io_uring_setup", libc::SYS_io_uring_setup),
+    ("kcmp", libc::SYS_kcmp),
+    ("kexec_file_load", libc::SYS_kexec_file_load),
+    ("kexec_load", libc::SYS_kexec_load),
+    ("keyctl", libc::SYS_keyctl),
+    ("memfd_create", libc::SYS_memfd_create),
+    ("mount", libc::SYS_mount),
+    ("mount_setattr", libc::SYS_mount_setattr),
+    ("move_mount", libc::SYS_move_mount),
+    ("name_to_handle_at", libc::SYS_name_to_handle_at),
+    ("open_by_handle_at", libc::SYS_open_by_handle_at),
+    ("open_tree", libc::SYS_open_tree),
+    ("perf_event_open", libc::SYS_perf_event_open),
+    ("personality", libc::SYS_personality),
+    ("pidfd_getfd", libc::SYS_pidfd_getfd),
+    ("pivot_root", libc::SYS_pivot_root),
+    ("process_vm_readv", libc::SYS_process_vm_readv),
+    ("process_vm_writev", libc::SYS_process_vm_writev),
+    ("ptrace", libc::SYS_ptrace),
+    ("quotactl", libc::SYS_quotactl),
+    ("reboot", libc::SYS_reboot),
+    ("request_key", libc::SYS_request_key),
+    ("setdomainname", libc::SYS_setdomainname),
+    ("sethostname", libc::SYS_sethostname),
+    ("setns", libc::SYS_setns),
+    ("settimeofday", libc::SYS_settimeofday),
+    ("swapoff", libc::SYS_swapoff),
+    ("swapon", libc::SYS_swapon),
+    ("syslog", libc::SYS_syslog),
+    ("umount2", libc::SYS_umount2),
+    ("unshare", libc::SYS_unshare),
+    ("userfaultfd", libc::SYS_userfaultfd),
+    ("vhangup", libc::SYS_vhangup),
+];
+
+/// The number of the syscall called `name` on this architecture, if it is
+/// one nono can filter
+pub fn syscall_number(name: &str) -> Option<libc::c_long> {
+    SYSCALL_NAMES
+        .iter()
+        .find(|(known, _)| *known == name)
+        .map(|&(_, nr)| nr)
+}
+
+/// `clone()` flags that create namespaces
+const CLONE_NAMESPACE_FLAGS: u32 = (libc::CLONE_NEWNS
+    | libc::CLONE_NEWCGROUP
+    | libc::CLONE_NEWUTS
+    | libc::CLONE_NEWIPC
+    | libc::CLONE_NEWUSER
+    | libc::CLONE_NEWPID
+    | libc::CLONE_NEWNET) as u32;
+
+/// A compiled seccomp BPF program.
+pub struct SeccompFilter {
+    program: Vec<libc::sock_filter>,
+    flags: libc::c_ulong,
+}
+
+impl SeccompFilter {
+    /// Build a filter that forwards `syscalls` to a user-space listener and
+    /// allows everything else.
+    pub fn notify(syscalls: &[libc::c_long]) -> Self {
+        let rules: Vec<(libc::c_long, u32)> = syscalls
+            .iter()
+            .map(|&nr| (nr, libc::SECCOMP_RET_USER_NOTIF))
+            .collect();
+        Self {
+            program: build_program(&rules, false),
+            flags: libc::SECCOMP_FILTER_FLAG_NEW_LISTENER,
+        }
+    }
+
+    /// Like [`SeccompFilter::notify`], for filters that enforce policy of
+    /// their own: syscalls from foreign ABIs (32-bit compat, x32), whose
+    /// numbers the rules cannot match, fail with `ENOSYS` instead of being
+    /// allowed, and so do `unavailable`, syscalls that would get around the
+    /// listener.
+    pub fn notify_enforcing(syscalls: &[libc::c_long], unavailable: &[libc::c_long]) -> Self {
+        let enosys = libc::SECCOMP_RET_ERRNO | (libc::ENOSYS as u32 & libc::SECCOMP_RET_DATA);
+        let rules: Vec<(libc::c_long, u32)> = syscalls
+            .iter()
+            .map(|&nr| (nr, libc::SECCOMP_RET_USER_NOTIF))
+            .chain(unavailable.iter().map(|&nr| (nr, enosys)))
+            .collect();
+        Self {
+            program: build_program(&rules, true),
+            flags: libc::SECCOMP_FILTER_FLAG_NEW_LISTENER,
+        }
+    }
+
+    /// Build a filter that fails `syscalls` with `EPERM` and allows
+    /// everything else. Foreign ABIs fail as in
+    /// [`SeccompFilter::notify_enforcing`].
+    ///
+    /// If `unshare` is among them, `clone()` may not create namespaces
+    /// either, and `clone3()`, whose flags a filter cannot read, fails with
+    /// `ENOSYS` so that libc falls back to `clone()`.
+    pub fn deny(syscalls: &[libc::c_long]) -> Self {
+        let eperm = libc::SECCOMP_RET_ERRNO | (libc::EPERM as u32 & libc::SECCOMP_RET_DATA);
+        let mut rules: Vec<(libc::c_long, u32)> = syscalls.iter().map(|&nr| (nr, eperm)).collect();
+        let namespaces = syscalls.contains(&libc::SYS_unshare);
+        if namespaces {
+            let enosys = libc::SECCOMP_RET_ERRNO | (libc::ENOSYS as u32 & libc::SECCOMP_RET_DATA);
+            rules.push((libc::SYS_clone3, enosys));
+        }
+        let mut program = build_program(&rules, true);
+        if namespaces {
+            // Replace the final allow with a check of clone()'s flags
+            program.pop();
+            program.extend(clone_flags_check(eperm));
+        }
+        Self { program, flags: 0 }
+    }
+
+    /// Install the filter on the calling thread from a forked child.
+    ///
+    /// Async-signal-safe: a single `seccomp()` syscall on pre-built data.
+    /// Requires `PR_SET_NO_NEW_PRIVS`, which the Landlock sandbox has already
+    /// set. Returns the listener FD for notify filters, `0` for others, or
+    /// `-1` on failure.
+    pub fn install_in_child(&self) -> libc::c_int {
+        let prog = libc::sock_fprog {
+            len: self.program.len() as libc::c_ushort,
+            filter: self.program.as_ptr() as *mut libc::sock_filter,
+        };
+        unsafe {
+            libc::syscall(
+                libc::SYS_seccomp,
+                libc::SECCOMP_SET_MODE_FILTER,
+                self.flags,
+                &prog as *const libc::sock_fprog,
+            ) as libc::c_int
+        }
+    }
+}
+
+/// Assemble `arch check; (nr == N ? action)...; allow`.
+///
+/// Each rule is a compare followed by its return, so jumps never exceed one
+/// instruction no matter how many syscalls are listed.
+fn build_program(rules: &[(libc::c_long, u32)], enforcing: bool) -> Vec<libc::sock_filter> {
+    let ld = (libc::BPF_LD | libc::BPF_W | libc::BPF_ABS) as u16;
+    let jeq = (libc::BPF_JMP | libc::BPF_JEQ | libc::BPF_K) as u16;
+    let ret = (libc::BPF_RET | libc::BPF_K) as u16;
+
+    // Syscall numbers of a foreign ABI mean nothing to the rules below. Letting
+    // them through is safe when the filter only hands operations to the
+    // supervisor and Landlock enforces the policy either way; an enforcing
+    // filter must refuse them.
+    let foreign = if enforcing {
+        libc::SECCOMP_RET_ERRNO | (libc::ENOSYS as u32 & libc::SECCOMP_RET_DATA)
+    } else {
+        libc::SECCOMP_RET_ALLOW
+    };
+    let mut program = vec![
+        stmt(ld, DATA_ARCH_OFFSET),
+        jump(jeq, AUDIT_ARCH, 1, 0),
+        stmt(ret, foreign),
+        stmt(ld, DATA_NR_OFFSET),
+    ];
+    #[cfg(target_arch = "x86_64")]
+    if enforcing {
+        let jge = (libc::BPF_JMP | libc::BPF_JGE | libc::BPF_K) as u16;
+        program.push(jump(jge, X32_SYSCALL_BIT, 0, 1));
+        program.push(stmt(ret, foreign));
+    }
+    for &(nr, action) in rules {
+        program.push(jump(jeq, nr as u32, 0, 1));
+        program.push(stmt(ret, action));
+    }
+    program.push(stmt(ret, libc::SECCOMP_RET_ALLOW));
+    program
+}
+
+/// `nr == clone ? (flags & CLONE_NEW* ? action : allow) : allow`, with the
+/// syscall number loaded
+fn clone_flags_check(action: u32) -> Vec<libc::sock_filter> {
+    let ld = (libc::BPF_LD | libc::BPF_W | libc::BPF_ABS) as u16;
+    let jeq = (libc::BPF_JMP | libc::BPF_JEQ | libc::BPF_K) as u16;
+    let jset = (libc::BPF_JMP | libc::BPF_JSET | libc::BPF_K) as u16;
+    let ret = (libc::BPF_RET | libc::BPF_K) as u16;
+
+    vec![
+        jump(jeq, libc::SYS_clone as u32, 0, 3),
+        // The flags are the first argument on x86_64 and aarch64; the
+        // namespace bits are all in its low half
+        stmt(ld, DATA_ARGS_OFFSET),
+        jump(jset, CLONE_NAMESPACE_FLAGS, 0, 1),
+        stmt(ret, action),
+        stmt(ret, libc::SECCOMP_RET_ALLOW),
+    ]
+}
+
+fn stmt(code: u16, k: u32) -> libc::sock_filter {
+    jump(code, k, 0, 0)
+}
+
+fn jump(code: u16, k: u32, jt: u8, jf: u8) -> libc::sock_filter {
+    libc::sock_filter { code, jt, jf, k }
+}
+
+/// A syscall stopped in the child, waiting for the supervisor's answer
+#[derive(Debug, Clone, Copy)]
+pub struct Notification {
+    /// Cookie identifying this notification
+    pub id: u64,
+    /// Thread ID of the caller (in the supervisor's PID namespace)
+    pub pid: u32,
+    /// Syscall number
+    pub nr: libc::c_long,
+    /// Raw syscall arguments
+    pub args: [u64; 6],
+}
+
+/// The supervisor's end of a seccomp notify filter
+pub struct NotifyListener {
+    fd: OwnedFd,
+}
+
+impl NotifyListener {
+    pub fn new(fd: OwnedFd) -> Self {
+        Self { fd }
+    }
+
+    /// Block until the next notification arrives
+    pub fn receive(&self) -> io::Result<Notification> {
+        loop {
+            let mut notif: libc::seccomp_notif = unsafe { std::mem::zeroed() };
+            let ret = unsafe {
+                libc::ioctl(
+                    self.fd.as_raw_fd(),
+                    SECCOMP_IOCTL_NOTIF_RECV as _,
+                    &mut notif as *mut libc::seccomp_notif,
+                )
+            };
+            if ret == 0 {
+                return Ok(Notification {
+                    id: notif.id,
+                    pid: notif.pid,
+                    nr: notif.data.nr as libc::c_long,
+                    args: notif.data.args,
+                });
+            }
+            let err = io::Error::last_os_error();
+            // EINTR: signal; ENOENT: the caller was killed before we received it
+            match err.raw_os_error() {
+                Some(libc::EINTR) | Some(libc::ENOENT) => continue,
+                _ => return Err(err),
+            }
+        }
+    }
+
+    /// Whether the notification is still pending (its caller is alive).
+    ///
+    /// Must be checked after reading the caller's memory: if the caller died
+    /// in between, its PID may already belong to another process.
+    pub fn is_valid(&self, id: u64) -> bool {
+        let mut id = id;
+        unsafe {
+            libc::ioctl(
+                self.fd.as_raw_fd(),
+                SECCOMP_IOCTL_NOTIF_ID_VALID as _,
+                &mut id as *mut u64,
+            ) == 0
+        }
+    }
+
+    /// Let the kernel run the syscall as if it had not been intercepted
+    pub fn respond_continue(&self, id: u64) -> io::Result<()> {
+        self.send(id, 0, 0, libc::SECCOMP_USER_NOTIF_FLAG_CONTINUE as u32)
+    }
+
+    /// Fail the syscall with `errno`
+    pub fn respond_error(&self, id: u64, errno: i32) -> io::Result<()> {
+        self.send(id, 0, -errno, 0)
+    }
+
+    /// Complete the syscall with return value `val`
+    pub fn respond_value(&self, id: u64, val: i64) -> io::Result<()> {
+        self.send(id, val, 0, 0)
+    }
+
+    /// Install `fd` in the caller and return its number as the syscall result
+    pub fn respond_fd(&self, id: u64, fd: BorrowedFd<'_>, cloexec: bool) -> io::Result<()> {
+        let addfd = libc::seccomp_notif_addfd {
+            id,
+            flags: libc::SECCOMP_ADDFD_FLAG_SEND as u32,
+            srcfd: fd.as_raw_fd() as u32,
+            newfd: 0,
+            newfd_flags: if cloexec { libc::O_CLOEXEC as u32 } else { 0 },
+        };
+        let ret = unsafe {
+            libc::ioctl(
+                self.fd.as_raw_fd(),
+                SECCOMP_IOCTL_NOTIF_ADDFD as _,
+                &addfd as *const libc::seccomp_notif_addfd,
+            )
+        };
+        if ret < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    fn send(&self, id: u64, val: i64, error: i32, flags: u32) -> io::Result<()> {
+        let mut resp = libc::seccomp_notif_resp {
+            id,
+            val,
+            error,
+            flags,
+        };
+        let ret = unsafe {
+            libc::ioctl(
+                self.fd.as_raw_fd(),
+                SECCOMP_IOCTL_NOTIF_SEND as _,
+                &mut resp as *mut libc::seccomp_notif_resp,
+            )
+        };
+        if ret < 0 {
+            let err = io::Error::last_os_error();
+            // ENOENT: the caller went away while we were deciding
+            debug!("Failed to answer seccomp notification {}: {}", id, err);
+            return Err(err);
+        }
+        Ok(())
+    }
+}
+
+/// Send `fd` over the Unix socket `sock` from a forked child.
+///
+/// Async-signal-safe: uses only stack buffers and `sendmsg()`.
+pub fn send_fd_in_child(sock: libc::c_int, fd: libc::c_int) -> bool {
+    // u64 array keeps the control buffer aligned for cmsghdr
+    let mut control = [0u64; 4];
+    let mut byte = [0u8; 1];
+    let mut iov = libc::iovec {
+        iov_base: byte.as_mut_ptr() as *mut libc::c_void,
+        iov_len: byte.len(),
+    };
+
+    unsafe {
+        let space = libc::CMSG_SPACE(std::mem::size_of::<libc::c_int>() as u32) as usize;
+        let mut msg: libc::msghdr = std::mem::zeroed();
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+        msg.msg_controllen = space as _;
+
+        let cmsg = libc::CMSG_FIRSTHDR(&msg);
+        if cmsg.is_null() {
+            return false;
+        }
+        (*cmsg).cmsg_level = libc::SOL_SOCKET;
+        (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+        (*cmsg).cmsg_len = libc::CMSG_LEN(std::mem::size_of::<libc::c_int>() as u32) as _;
+        std::ptr::write_unaligned(libc::CMSG_DATA(cmsg) as *mut libc::c_int, fd);
+
+        libc::sendmsg(sock, &msg, 0) == 1
+    }
+}
+
+/// Receive an FD sent with [`send_fd_in_child`].
+///
+/// Returns `Ok(None)` if the peer closed the socket without sending one
+/// (e.g. the child failed before installing its filter).
+pub fn recv_fd(sock: BorrowedFd<'_>) -> io::Result<Option<OwnedFd>> {
+    let mut control = [0u64; 4];
+    let mut byte = [0u8; 1];
+    let mut iov = libc::iovec {
+        iov_base: byte.as_mut_ptr() as *mut libc::c_void,
+        iov_len: byte.len(),
+    };
+
+    unsafe {
+        let mut msg: libc::msghdr = std::mem::zeroed();
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+        msg.msg_controllen = std::mem::size_of_val(&control) as _;
+
+        let n = loop {
+            let n = libc::recvmsg(sock.as_raw_fd(), &mut msg, libc::MSG_CMSG_CLOEXEC);
+            if n >= 0 {
+                break n;
+            }
+            let err = io::Error::last_os_error();
+            if err.raw_os_error() != Some(libc::EINTR) {
+                return Err(err);
+            }
+        };
+        if n == 0 {
+            return Ok(None);
+        }
+
+        let cmsg = libc::CMSG_FIRSTHDR(&msg);
+        if cmsg.is_null()
+            || (*cmsg).cmsg_level != libc::SOL_SOCKET
+            || (*cmsg).cmsg_type != libc::SCM_RIGHTS
+        {
+            return Ok(None);
+        }
+        let fd = std::ptr::read_unaligned(libc::CMSG_DATA(cmsg) as *const libc::c_int);
+        Ok(Some(OwnedFd::from_raw_fd(fd)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::fd::AsFd;
+    use std::os::unix::net::UnixStream;
+
+    #[test]
+    fn test_notify_program_layout() {
+        let filter = SeccompFilter::notify(&[libc::SYS_openat, libc::SYS_connect]);
+        assert_eq!(filter.flags, libc::SECCOMP_FILTER_FLAG_NEW_LISTENER);
+
+        // arch check (3) + load nr (1) + 2 per rule + final allow
+        assert_eq!(filter.program.len(), 4 + 2 * 2 + 1);
+
+        let rule = &filter.program[4];
+        assert_eq!(rule.k, libc::SYS_openat as u32);
+        assert_eq!((rule.jt, rule.jf), (0, 1));
+        assert_eq!(filter.program[5].k, libc::SECCOMP_RET_USER_NOTIF);
+
+        let last = filter.program.last().unwrap();
+        assert_eq!(last.k, libc::SECCOMP_RET_ALLOW);
+    }
+
+    #[test]
+    fn test_notify_enforcing_refuses_foreign_abis() {
+        let filter = SeccompFilter::notify_enforcing(&[libc::SYS_openat], &[]);
+        let enosys = libc::SECCOMP_RET_ERRNO | libc::ENOSYS as u32;
+
+        // Foreign audit arch fails instead of being allowed
+        assert_eq!(filter.program[2].k, enosys);
+
+        #[cfg(target_arch = "x86_64")]
+        {
+            // x32 syscall numbers fail too
+            assert_eq!(filter.program[4].k, X32_SYSCALL_BIT);
+            assert_eq!(filter.program[5].k, enosys);
+            assert_eq!(filter.program[6].k, libc::SYS_openat as u32);
+        }
+
+        let last = filter.program.last().unwrap();
+        assert_eq!(last.k, libc::SECCOMP_RET_ALLOW);
+    }
+
+    #[test]
+    fn test_notify_enforcing_unavailable_syscalls() {
+        let filter =
+            SeccompFilter::notify_enforcing(&[libc::SYS_openat], &[libc::SYS_io_uring_setup]);
+        let enosys = libc::SECCOMP_RET_ERRNO | libc::ENOSYS as u32;
+
+        let rule = filter
+            .program
+            .iter()
+            .position(|insn| insn.k == libc::SYS_io_uring_setup as u32)
+            .expect("io_uring_setup rule");
+        assert_eq!(filter.program[rule + 1].k, enosys);
+        let rule = filter
+            .program
+            .iter()
+            .position(|insn| insn.k == libc::SYS_openat as u32)
+            .expect("openat rule");
+        assert_eq!(filter.program[rule + 1].k, libc::SECCOMP_RET_USER_NOTIF);
+    }
+
+    #[test]
+    fn test_deny_program_layout() {
+        let filter = SeccompFilter::deny(&[libc::SYS_ptrace]);
+        let eperm = libc::SECCOMP_RET_ERRNO | libc::EPERM as u32;
+        assert_eq!(filter.flags, 0);
+
+        let rule = filter
+            .program
+            .iter()
+            .position(|insn| insn.k == libc::SYS_ptrace as u32)
+            .expect("ptrace rule");
+        assert_eq!(filter.program[rule + 1].k, eperm);
+        assert_eq!(filter.program.last().unwrap().k, libc::SECCOMP_RET_ALLOW);
+        // No clone handling without unshare
+        assert!(!filter
+            .program
+            .iter()
+            .any(|insn| insn.k == libc::SYS_clone3 as u32));
+    }
+
+    #[test]
+    fn test_deny_unshare_covers_clone() {
+        let filter = SeccompFilter::deny(&[libc::SYS_unshare]);
+        let eperm = libc::SECCOMP_RET_ERRNO | libc::EPERM as u32;
+        let enosys = libc::SECCOMP_RET_ERRNO | libc::ENOSYS as u32;
+
+        let clone3 = filter
+            .program
+            .iter()
+            .position(|insn| insn.k == libc::SYS_clone3 as u32)
+            .expect("clone3 rule");
+        assert_eq!(filter.program[clone3 + 1].k, enosys);
+
+        // clone: load flags, test the namespace bits, deny or allow
+        let tail = &filter.program[filter.program.len() - 5..];
+        assert_eq!(tail[0].k, libc::SYS_clone as u32);
+        assert_eq!(tail[1].k, DATA_ARGS_OFFSET);
+        assert_eq!(tail[2].k, CLONE_NAMESPACE_FLAGS);
+        assert_eq!(tail[3].k, eperm);
+        assert_eq!(tail[4].k, libc::SECCOMP_RET_ALLOW);
+    }
+
+    #[test]
+    fn test_syscall_number() {
+        assert_eq!(syscall_number("ptrace"), Some(libc::SYS_ptrace));
+        assert_eq!(syscall_number("unshare"), Some(libc::SYS_unshare));
+        assert_eq!(syscall_number("no_such_syscall"), None);
+    }
+
+    #[test]
+    fn test_dangerous_syscalls_are_known() {
+        // Every syscall of the embedded lists can be filtered
+        for name in crate::config::denied_syscalls(&[], &[]) {
+            assert!(syscall_number(&name).is_some(), "unknown syscall {}", name);
+        }
+    }
+
+    #[test]
+    fn test_open_syscalls_include_openat() {
+        let syscalls = open_syscalls();
+        assert!(syscalls.contains(&libc::SYS_openat));
+        assert!(syscalls.contains(&libc::SYS_openat2));
+    }
+
+    #[test]
+    fn test_fd_passing_roundtrip() {
+        let (a, b) = UnixStream::pair().unwrap();
+        let file = tempfile::tempfile().unwrap();
+
+        assert!(send_fd_in_child(a.as_raw_fd(), file.as_raw_fd()));
+        let received = recv_fd(b.as_fd()).unwrap().expect("fd should be received");
+        assert_ne!(received.as_raw_fd(), file.as_raw_fd());
+
+        drop(a);
+        assert!(recv_fd(b.as_fd()).unwrap().is_none());
+    }
+}
@@ -13,20 +13,35 @@ use tracing::debug;
 #[cfg(unix)]
 use std::os::unix::fs::OpenOptionsExt;
 
-use crate::capability::{CapabilitySet, FsAccess, FsCapability};
+use crate::capability::{format_timestamp, CapabilitySet, FsAccess, FsCapability};
 use crate::error::{NonoError, Result};
 
 /// Sandbox state stored for `nono why --self`
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct SandboxState {
     /// Filesystem capabilities
     pub fs: Vec<FsCapState>,
+    /// Paths hidden from the sandbox
+    #[serde(default)]
+    pub deny: Vec<String>,
     /// Whether network is blocked
     pub net_blocked: bool,
+    /// TCP ports allowed for connect when port filtering is active
+    #[serde(default)]
+    pub tcp_connect_ports: Vec<u16>,
+    /// TCP ports allowed for bind when port filtering is active
+    #[serde(default)]
+    pub tcp_bind_ports: Vec<u16>,
+    /// Hosts reachable through the filtering proxy
+    #[serde(default)]
+    pub allow_hosts: Vec<String>,
     /// Commands explicitly allowed
     pub allowed_commands: Vec<String>,
     /// Commands explicitly blocked
     pub blocked_commands: Vec<String>,
+    /// Whether the command runs in a PID namespace of its own
+    #[serde(default)]
+    pub pid_namespace: bool,
 }
 
 /// Serializable filesystem capability state
@@ -36,10 +51,13 @@ pub struct FsCapState {
     pub original: String,
     /// Resolved absolute path
     pub path: String,
-    /// Access level: "read", "write", or "readwrite"
+    /// Access level: "read", "write", "readwrite", "read-noexec" or "exec"
     pub access: String,
     /// Whether this is a single file (vs directory)
     pub is_file: bool,
+    /// When a time-limited capability expires (RFC 3339)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
 }
 
 impl SandboxState {
@@ -56,13 +74,21 @@ impl SandboxState {
                         FsAccess::Read => "read".to_string(),
                         FsAccess::Write => "write".to_string(),
                         FsAccess::ReadWrite => "readwrite".to_string(),
+                        FsAccess::ReadNoExec => "read-noexec".to_string(),
+                        FsAccess::Execute => "exec".to_string(),
                     },
                     is_file: c.is_file,
+                    expires_at: c.expires_at.map(format_timestamp),
                 })
                 .collect(),
+            deny: caps.deny.iter().map(|p| p.display().to_string()).collect(),
             net_blocked: caps.net_block,
+            tcp_connect_ports: caps.tcp_connect_ports.clone(),
+            tcp_bind_ports: caps.tcp_bind_ports.clone(),
+            allow_hosts: caps.allow_hosts.clone(),
             allowed_commands: caps.allowed_commands.clone(),
             blocked_commands: caps.blocked_commands.clone(),
+            pid_namespace: caps.pid_namespace,
         }
     }
 
@@ -78,6 +104,8 @@ impl SandboxState {
                 "read" => FsAccess::Read,
                 "write" => FsAccess::Write,
                 "readwrite" => FsAccess::ReadWrite,
+                "read-noexec" => FsAccess::ReadNoExec,
+                "exec" => FsAccess::Execute,
                 _ => FsAccess::Read, // Default to read for unknown
             };
 
@@ -87,13 +115,24 @@ impl SandboxState {
                 resolved: PathBuf::from(&fs_cap.path),
                 access,
                 is_file: fs_cap.is_file,
+                ttl: None,
+                expires_at: fs_cap
+                    .expires_at
+                    .as_deref()
+                    .and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok())
+                    .map(std::time::SystemTime::from),
             };
             caps.fs.push(cap);
         }
 
+        caps.deny = self.deny.iter().map(PathBuf::from).collect();
         caps.net_block = self.net_blocked;
+        caps.tcp_connect_ports = self.tcp_connect_ports.clone();
+        caps.tcp_bind_ports = self.tcp_bind_ports.clone();
+        caps.allow_hosts = self.allow_hosts.clone();
         caps.allowed_commands = self.allowed_commands.clone();
         caps.blocked_commands = self.blocked_commands.clone();
+        caps.pid_namespace = self.pid_namespace;
 
         caps
     }
@@ -443,6 +482,35 @@ mod tests {
         assert_eq!(restored.allowed_commands, vec!["pip"]);
     }
 
+    #[test]
+    fn test_pid_namespace_roundtrip() {
+        let caps = CapabilitySet {
+            pid_namespace: true,
+            ..Default::default()
+        };
+
+        let json = serde_json::to_string(&SandboxState::from_caps(&caps)).unwrap();
+        let loaded: SandboxState = serde_json::from_str(&json).unwrap();
+        assert!(loaded.pid_namespace);
+        assert!(loaded.to_caps().pid_namespace);
+    }
+
+    #[test]
+    fn test_sandbox_state_roundtrip_port_rules() {
+        let caps = CapabilitySet {
+            tcp_connect_ports: vec![443],
+            tcp_bind_ports: vec![3000],
+            allow_hosts: vec!["*.github.com".to_string()],
+            ..Default::default()
+        };
+
+        let restored = SandboxState::from_caps(&caps).to_caps();
+        assert_eq!(restored.tcp_connect_ports, vec![443]);
+        assert_eq!(restored.tcp_bind_ports, vec![3000]);
+        assert_eq!(restored.allow_hosts, vec!["*.github.com"]);
+        assert!(restored.has_port_rules());
+    }
+
     #[test]
     fn test_sandbox_state_write_and_read() {
         let dir = tempdir().expect("Failed to create temp dir");
@@ -472,23 +540,27 @@ mod tests {
                     path: "/home/user/src".to_string(),
                     access: "read".to_string(),
                     is_file: false,
+                    expires_at: None,
                 },
                 FsCapState {
                     original: "./out".to_string(),
                     path: "/home/user/out".to_string(),
                     access: "write".to_string(),
                     is_file: false,
+                    expires_at: None,
                 },
                 FsCapState {
                     original: "./data".to_string(),
                     path: "/home/user/data".to_string(),
                     access: "readwrite".to_string(),
                     is_file: false,
+                    expires_at: None,
                 },
             ],
             net_blocked: false,
             allowed_commands: vec![],
             blocked_commands: vec![],
+            ..Default::default()
         };
 
         let caps = state.to_caps();
@@ -498,6 +570,53 @@ mod tests {
         assert_eq!(caps.fs[2].access, FsAccess::ReadWrite);
     }
 
+    #[test]
+    fn test_fs_cap_state_expiry_roundtrip() {
+        let dir = tempdir().unwrap();
+        let mut caps = CapabilitySet::new();
+        caps.add_fs(
+            FsCapability::new_dir(dir.path().to_path_buf(), FsAccess::Read)
+                .unwrap()
+                .with_ttl(Some(std::time::Duration::from_secs(300))),
+        );
+        let start = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000);
+        caps.arm_expiry(start);
+
+        let state = SandboxState::from_caps(&caps);
+        assert_eq!(
+            state.fs[0].expires_at.as_deref(),
+            Some("1970-01-12T13:51:40Z")
+        );
+
+        let json = serde_json::to_string(&state).unwrap();
+        let loaded: SandboxState = serde_json::from_str(&json).unwrap();
+        let caps = loaded.to_caps();
+        assert_eq!(
+            caps.fs[0].expires_at,
+            Some(start + std::time::Duration::from_secs(300))
+        );
+    }
+
+    #[test]
+    fn test_deny_roundtrip() {
+        let mut caps = CapabilitySet::new();
+        caps.add_deny(PathBuf::from("/work/.git/hooks"));
+
+        let json = serde_json::to_string(&SandboxState::from_caps(&caps)).unwrap();
+        let loaded: SandboxState = serde_json::from_str(&json).unwrap();
+        assert!(loaded
+            .to_caps()
+            .is_denied(std::path::Path::new("/work/.git/hooks/pre-commit")));
+
+        // State files written before deny paths existed still load
+        let legacy: SandboxState = serde_json::from_str(
+            r#"{"fs":[],"net_blocked":false,"allowed_commands":[],"blocked_commands":[]}"#,
+        )
+        .unwrap();
+        assert!(legacy.deny.is_empty());
+        assert!(!legacy.pid_namespace);
+    }
+
     // Security tests for validate_cap_file_path
 
     #[test]
@@ -582,6 +701,7 @@ mod tests {
             net_blocked: true,
             allowed_commands: vec![],
             blocked_commands: vec![],
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&test_state).expect("Failed to serialize");
@@ -630,6 +750,7 @@ mod tests {
             net_blocked: true,
             allowed_commands: vec![],
             blocked_commands: vec![],
+            ..Default::default()
         };
 
         state
@@ -660,6 +781,7 @@ mod tests {
             net_blocked: true,
             allowed_commands: vec![],
             blocked_commands: vec![],
+            ..Default::default()
         };
 
         // Should fail due to create_new(true)
@@ -684,6 +806,7 @@ mod tests {
             net_blocked: true,
             allowed_commands: vec![],
             blocked_commands: vec![],
+            ..Default::default()
         };
 
         // First write succeeds
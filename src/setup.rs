@@ -1,3 +1,5 @@
+#[cfg(target_os = "linux")]
+use crate::capability::CapabilitySet;
 use crate::cli::SetupArgs;
 use crate::config;
 use crate::error::{NonoError, Result};
@@ -36,6 +38,10 @@ impl SetupRunner {
         // Sandbox support testing
         self.test_sandbox_support()?;
 
+        // Load the config, which checks the list versions (and records them,
+        // unless only checking)
+        config::init_effective_config(!self.check_only)?;
+
         // Show what nono protects
         self.show_protection_summary();
 
@@ -204,12 +210,11 @@ impl SetupRunner {
             println!("  ✓ Kernel version: {}", version);
         }
 
-        // Check LSM list
-        let lsm_list = std::fs::read_to_string("/sys/kernel/security/lsm").unwrap_or_default();
-
-        if !lsm_list.contains("landlock") {
+        // Ask the kernel for its Landlock ABI; this fails with ENOSYS or
+        // EOPNOTSUPP when Landlock is not built in or not in the LSM list
+        if crate::sandbox::landlock_abi().is_none() {
             return Err(NonoError::Setup(
-                "Landlock is not enabled in kernel LSM list.\n\n\
+                "Landlock is not enabled in this kernel.\n\n\
                 To enable Landlock:\n\
                   1. Check your kernel config: CONFIG_SECURITY_LANDLOCK=y\n\
                   2. Add to boot params: lsm=landlock,lockdown,yama,integrity,apparmor\n\
@@ -219,46 +224,27 @@ impl SetupRunner {
             ));
         }
 
-        println!("  ✓ Landlock enabled in LSM list");
-
-        // We target the highest ABI and report its features
-        // The actual sandbox will use Compatible trait to handle older kernels
-        let abi = ABI::V5;
+        println!("  ✓ Landlock enabled");
 
-        println!("  ✓ Landlock ABI: {:?}", abi);
+        // Report the ABI the kernel provides; the sandbox targets the highest
+        // ABI and the Compatible trait drops what older kernels lack
+        let report = crate::sandbox::enforcement_report(&CapabilitySet::new());
+        println!("  ✓ {}", report.backend);
         println!("  ✓ Available features:");
-
-        match abi {
-            ABI::V1 => {
-                println!("      - Basic filesystem access control");
-            }
-            ABI::V2 => {
-                println!("      - Basic filesystem access control");
-                println!("      - File rename across directories");
-            }
-            ABI::V3 => {
-                println!("      - Basic filesystem access control");
-                println!("      - File rename across directories");
-                println!("      - File truncation");
-            }
-            ABI::V4 => {
-                println!("      - Basic filesystem access control");
-                println!("      - File rename across directories");
-                println!("      - File truncation");
-                println!("      - TCP network filtering");
-            }
-            ABI::V5 => {
-                println!("      - Basic filesystem access control");
-                println!("      - File rename across directories");
-                println!("      - File truncation");
-                println!("      - TCP network filtering");
-                println!("      - Advanced socket and signal scoping");
+        println!("      - filesystem: basic filesystem access control");
+        for protection in report.protections.iter().filter(|p| p.available) {
+            println!("      - {}: {}", protection.name, protection.description);
+        }
+        let missing: Vec<_> = report.missing().collect();
+        if !missing.is_empty() {
+            println!("  * Missing on this kernel (not enforced):");
+            for protection in missing {
+                println!("      - {}: {}", protection.name, protection.description);
             }
-            _ => {}
         }
 
         // Try creating a test ruleset
-        let handled = AccessFs::from_all(abi);
+        let handled = AccessFs::from_all(ABI::V1);
         Ruleset::default()
             .handle_access(handled)
             .and_then(|r| r.create())
@@ -295,9 +281,37 @@ impl SetupRunner {
         println!("      {}, ...", sample.join(", "));
 
         println!("  ✓ Network access: allowed by default (use --net-block to disable)");
+        self.show_tracked_versions();
         println!();
     }
 
+    fn show_tracked_versions(&self) {
+        let tracker = match config::version::VersionTracker::load() {
+            Ok(tracker) => tracker,
+            Err(e) => {
+                println!("  * Security list versions: <warning: {}>", e);
+                return;
+            }
+        };
+        let state_file = config::version::VersionTracker::state_file_path()
+            .map(|p| p.display().to_string())
+            .unwrap_or_default();
+
+        println!(
+            "  ✓ Security list versions (downgrade protected, {}):",
+            state_file
+        );
+        for (name, state) in tracker.sorted() {
+            println!(
+                "      {} v{} ({}, last seen {})",
+                name,
+                state.version,
+                state.source,
+                state.last_seen.format("%Y-%m-%d %H:%M UTC")
+            );
+        }
+    }
+
     fn show_builtin_profiles(&self) {
         println!("[4/{}] Built-in profiles...", self.total_phases());
 
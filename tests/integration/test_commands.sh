@@ -97,6 +97,114 @@ expect_failure "ls blocked with --block-command ls" \
 expect_failure "explicitly blocked command overrides default allow" \
     "$NONO_BIN" run --allow "$TMPDIR" --block-command echo -- echo "should fail"
 
+# =============================================================================
+# Profile Command Lists
+# =============================================================================
+
+echo ""
+echo "--- Profile [commands] Section ---"
+
+PROFILE_HOME="$TMPDIR/profile-home"
+mkdir -p "$PROFILE_HOME/.config/nono/profiles"
+cat > "$PROFILE_HOME/.config/nono/profiles/cmd-test.toml" <<'TOML'
+[meta]
+name = "cmd-test"
+
+[commands]
+allow = ["rm"]
+block = ["cat"]
+TOML
+
+echo "profile" > "$TMPDIR/profile-delete.txt"
+expect_success "rm allowed by profile [commands] allow" \
+    env HOME="$PROFILE_HOME" XDG_CONFIG_HOME="$PROFILE_HOME/.config" "$NONO_BIN" run \
+    --profile cmd-test --trust-unsigned --allow "$TMPDIR" -- rm "$TMPDIR/profile-delete.txt"
+
+expect_failure "cat blocked by profile [commands] block" \
+    env HOME="$PROFILE_HOME" XDG_CONFIG_HOME="$PROFILE_HOME/.config" "$NONO_BIN" run \
+    --profile cmd-test --trust-unsigned --allow "$TMPDIR" -- cat "$TMPDIR/protected.txt"
+
+expect_success "--allow-command overrides profile block" \
+    env HOME="$PROFILE_HOME" XDG_CONFIG_HOME="$PROFILE_HOME/.config" "$NONO_BIN" run \
+    --profile cmd-test --trust-unsigned --allow "$TMPDIR" --allow-command cat -- \
+    cat "$TMPDIR/protected.txt"
+
+# =============================================================================
+# Child Processes
+# =============================================================================
+
+echo ""
+echo "--- Blocked Commands in Child Processes ---"
+
+echo "child" > "$TMPDIR/child-delete.txt"
+expect_failure "rm blocked when run by a shell" \
+    "$NONO_BIN" run --allow "$TMPDIR" -- sh -c "rm '$TMPDIR/child-delete.txt'"
+
+run_test "file was not deleted (child rm was blocked)" 0 test -f "$TMPDIR/child-delete.txt"
+
+expect_output_contains "blocked child exec names the command" "Blocked execution of 'rm'" \
+    "$NONO_BIN" run --allow "$TMPDIR" -- sh -c "rm '$TMPDIR/child-delete.txt'"
+
+ln -sf "$(command -v rm)" "$TMPDIR/remove"
+expect_failure "rm blocked under another name" \
+    "$NONO_BIN" run --allow "$TMPDIR" -- sh -c "'$TMPDIR/remove' '$TMPDIR/child-delete.txt'"
+
+expect_success "--allow-command applies to child processes" \
+    "$NONO_BIN" run --allow "$TMPDIR" --allow-command rm -- sh -c "rm '$TMPDIR/child-delete.txt'"
+
+expect_failure "--block-command applies to child processes" \
+    "$NONO_BIN" run --allow "$TMPDIR" --block-command cat -- sh -c "cat '$TMPDIR/protected.txt'"
+
+# =============================================================================
+# Command Rules
+# =============================================================================
+
+echo ""
+echo "--- Command Rules ---"
+
+RULES_WORK="$TMPDIR/rules-work"
+mkdir -p "$RULES_WORK/dir"
+echo "outside" > "$TMPDIR/outside.txt"
+cat > "$PROFILE_HOME/.config/nono/profiles/rules-test.toml" <<'TOML'
+[meta]
+name = "rules-test"
+
+[commands]
+allow = ["rm"]
+
+[commands.rules]
+rm = { deny_args = ["-r"], allow_paths = ["$WORKDIR"] }
+TOML
+
+# Runs from $RULES_WORK (the profile's $WORKDIR), so the binary path must be absolute
+NONO_ABS="$(cd "$(dirname "$NONO_BIN")" && pwd)/$(basename "$NONO_BIN")"
+run_rules_profile() {
+    (cd "$RULES_WORK" && env HOME="$PROFILE_HOME" XDG_CONFIG_HOME="$PROFILE_HOME/.config" \
+        "$NONO_ABS" run --profile rules-test --trust-unsigned --allow "$TMPDIR" "$@")
+}
+
+expect_failure "rm -fr refused by deny_args" \
+    run_rules_profile -- rm -fr "$RULES_WORK/dir"
+
+run_test "directory was not removed (deny_args)" 0 test -d "$RULES_WORK/dir"
+
+expect_failure "rm outside allow_paths refused" \
+    run_rules_profile -- rm "$TMPDIR/outside.txt"
+
+run_test "file outside allow_paths was not deleted" 0 test -f "$TMPDIR/outside.txt"
+
+echo "inside" > "$RULES_WORK/inside.txt"
+expect_success "rm inside allow_paths allowed" \
+    run_rules_profile -- rm inside.txt
+
+expect_failure "rules apply to child processes" \
+    run_rules_profile -- sh -c "rm -r '$RULES_WORK/dir'"
+
+expect_output_contains "refused child exec names the rule" "not allowed for rm" \
+    run_rules_profile -- sh -c "rm -r '$RULES_WORK/dir'"
+
+run_test "directory was not removed (child rm)" 0 test -d "$RULES_WORK/dir"
+
 # =============================================================================
 # Package Managers
 # =============================================================================
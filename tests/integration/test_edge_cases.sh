@@ -205,6 +205,316 @@ else
     run_test "write to write-only directory succeeded" 0 test -f "$TMPDIR/mixed_write/output.txt"
 fi
 
+# =============================================================================
+# Supervised Mode
+# =============================================================================
+
+echo ""
+echo "--- Supervised Mode ---"
+
+mkdir -p "$TMPDIR/supervised_allowed" "$TMPDIR/supervised_denied"
+echo "supervised content" > "$TMPDIR/supervised_allowed/file.txt"
+echo "supervised secret" > "$TMPDIR/supervised_denied/secret.txt"
+
+expect_success "supervised: read file in granted directory" \
+    "$NONO_BIN" run --supervised --allow "$TMPDIR/supervised_allowed" -- cat "$TMPDIR/supervised_allowed/file.txt"
+
+expect_failure "supervised: child is still sandboxed" \
+    "$NONO_BIN" run --supervised --allow "$TMPDIR/supervised_allowed" -- cat "$TMPDIR/supervised_denied/secret.txt"
+
+run_test "supervised: exit code is propagated" 3 \
+    "$NONO_BIN" run --supervised --allow "$TMPDIR/supervised_allowed" -- sh -c "exit 3"
+
+# =============================================================================
+# Audit Log
+# =============================================================================
+
+echo ""
+echo "--- Audit Log ---"
+
+# The temp directory is writable by every command on macOS, which a log must
+# not be in
+if is_macos; then
+    AUDIT_DIR=$(mktemp -d "$HOME/.nono-audit-test.XXXXXX")
+else
+    AUDIT_DIR="$TMPDIR"
+fi
+AUDIT_LOG="$AUDIT_DIR/audit.jsonl"
+
+run_test "audit-log: exit code is propagated" 3 \
+    "$NONO_BIN" run --audit-log "$AUDIT_LOG" --allow "$TMPDIR/supervised_allowed" -- sh -c "exit 3"
+
+expect_output_contains "audit-log: session start recorded" '"event":"session_start"' \
+    cat "$AUDIT_LOG"
+
+expect_output_contains "audit-log: exit status recorded" '"exit_code":3' \
+    cat "$AUDIT_LOG"
+
+expect_failure "audit-log: blocked command rejected" \
+    "$NONO_BIN" run --audit-log "$AUDIT_LOG" --allow "$TMPDIR/supervised_allowed" -- rm -rf "$TMPDIR/supervised_allowed"
+
+expect_output_contains "audit-log: blocked command recorded" '"event":"command_blocked"' \
+    cat "$AUDIT_LOG"
+
+expect_failure "audit-log: log inside a write grant refused" \
+    "$NONO_BIN" run --audit-log "$TMPDIR/supervised_allowed/audit.jsonl" \
+    --allow "$TMPDIR/supervised_allowed" -- true
+
+if is_macos; then
+    rm -rf "$AUDIT_DIR"
+fi
+
+# =============================================================================
+# Permission Prompts (Linux)
+# =============================================================================
+
+echo ""
+echo "--- Permission Prompts ---"
+
+mkdir -p "$TMPDIR/prompt_outside"
+echo "prompted content" > "$TMPDIR/prompt_outside/file.txt"
+
+if ! is_linux; then
+    skip_test "prompt: approved read succeeds" "Linux only"
+    skip_test "prompt: denied read fails" "Linux only"
+elif ! command_exists script; then
+    skip_test "prompt: approved read succeeds" "script not installed"
+    skip_test "prompt: denied read fails" "script not installed"
+else
+    # script(1) gives nono a terminal to prompt on and feeds it the answer
+    expect_output_contains "prompt: approved read succeeds" "prompted content" \
+        sh -c "printf 's\\n' | script -qec '\"$NONO_BIN\" run --prompt --allow-cwd --allow \"$TMPDIR/supervised_allowed\" -- cat \"$TMPDIR/prompt_outside/file.txt\"' /dev/null"
+
+    expect_output_not_contains "prompt: denied read fails" "prompted content" \
+        sh -c "printf 'n\\n' | script -qec '\"$NONO_BIN\" run --prompt --allow-cwd --allow \"$TMPDIR/supervised_allowed\" -- cat \"$TMPDIR/prompt_outside/file.txt\"' /dev/null"
+fi
+
+# =============================================================================
+# Ephemeral Workspace (Linux)
+# =============================================================================
+
+echo ""
+echo "--- Ephemeral Workspace ---"
+
+EPHEMERAL_DIR="$TMPDIR/ephemeral"
+mkdir -p "$EPHEMERAL_DIR"
+echo "original" > "$EPHEMERAL_DIR/file.txt"
+
+if ! is_linux; then
+    skip_test "ephemeral: command sees its own writes" "Linux only"
+elif ! command_exists unshare || ! unshare -Urm true 2>/dev/null; then
+    skip_test "ephemeral: command sees its own writes" "user namespaces unavailable"
+else
+    expect_output_contains "ephemeral: command sees its own writes" "changed" \
+        "$NONO_BIN" run --silent --ephemeral --allow-cwd --allow "$EPHEMERAL_DIR" -- \
+        sh -c "echo changed > '$EPHEMERAL_DIR/file.txt' && cat '$EPHEMERAL_DIR/file.txt'"
+
+    expect_output_contains "ephemeral: real file untouched without a terminal" "original" \
+        cat "$EPHEMERAL_DIR/file.txt"
+
+    if command_exists script; then
+        sh -c "printf 'e\\n$TMPDIR/ephemeral.patch\\n' | script -qec '\"$NONO_BIN\" run --ephemeral --allow-cwd --allow \"$EPHEMERAL_DIR\" -- sh -c \"echo changed > $EPHEMERAL_DIR/file.txt\"' /dev/null" >/dev/null 2>&1
+        expect_output_contains "ephemeral: export writes a patch" "+changed" \
+            cat "$TMPDIR/ephemeral.patch"
+
+        sh -c "printf 'c\\n' | script -qec '\"$NONO_BIN\" run --ephemeral --allow-cwd --allow \"$EPHEMERAL_DIR\" -- sh -c \"echo changed > $EPHEMERAL_DIR/file.txt\"' /dev/null" >/dev/null 2>&1
+        expect_output_contains "ephemeral: commit applies changes" "changed" \
+            cat "$EPHEMERAL_DIR/file.txt"
+    else
+        skip_test "ephemeral: export and commit" "script not installed"
+    fi
+fi
+
+# =============================================================================
+# Time-Limited Capabilities (Linux)
+# =============================================================================
+
+echo ""
+echo "--- Time-Limited Capabilities ---"
+
+TTL_DIR="$TMPDIR/ttl"
+mkdir -p "$TTL_DIR"
+
+if ! is_linux; then
+    skip_test "ttl: writes refused after expiry" "Linux only"
+else
+    expect_success "ttl: writes allowed before expiry" \
+        "$NONO_BIN" run --silent --allow-cwd --allow "$TTL_DIR:1m" -- \
+        sh -c "echo before > '$TTL_DIR/before.txt'"
+
+    expect_output_contains "ttl: writes refused after expiry" "expired" \
+        "$NONO_BIN" run --silent --allow-cwd --allow "$TTL_DIR:2s" -- \
+        sh -c "sleep 3; echo after > '$TTL_DIR/after.txt' || echo expired"
+
+    # io_uring_setup is 425 on every architecture; it would open files
+    # without the supervisor seeing them
+    if command_exists perl; then
+        expect_output_contains "ttl: io_uring unavailable" "Function not implemented" \
+            "$NONO_BIN" run --silent --allow-cwd --allow "$TTL_DIR:1m" -- \
+            perl -e 'syscall(425, 8, 0) == -1 and print "$!\n"'
+    else
+        skip_test "ttl: io_uring unavailable" "perl not installed"
+    fi
+
+    expect_output_contains "ttl: invalid duration is not split off" "does not exist" \
+        "$NONO_BIN" run --silent --allow-cwd --allow "$TTL_DIR:5x" -- true
+fi
+
+# =============================================================================
+# Security List Versions
+# =============================================================================
+
+echo ""
+echo "--- Security List Versions ---"
+
+VERSIONS_HOME="$TMPDIR/versions-home"
+mkdir -p "$VERSIONS_HOME"
+VERSIONS_FILE="$VERSIONS_HOME/.local/state/nono/versions.json"
+
+expect_success "versions: setup --check-only succeeds" \
+    env HOME="$VERSIONS_HOME" XDG_CONFIG_HOME="$VERSIONS_HOME/.config" \
+    XDG_STATE_HOME="$VERSIONS_HOME/.local/state" "$NONO_BIN" setup --check-only
+
+run_test "versions: setup --check-only does not record versions" 1 test -e "$VERSIONS_FILE"
+
+# NONO_CAP_FILE can be set by anyone, so it must not stop versions from being recorded
+expect_success "versions: run with NONO_CAP_FILE set succeeds" \
+    env HOME="$VERSIONS_HOME" XDG_CONFIG_HOME="$VERSIONS_HOME/.config" \
+    XDG_STATE_HOME="$VERSIONS_HOME/.local/state" NONO_CAP_FILE=/nonexistent \
+    "$NONO_BIN" run --allow-cwd --allow "$TMPDIR" -- true
+
+run_test "versions: run records versions despite NONO_CAP_FILE" 0 \
+    grep -q '"security_lists"' "$VERSIONS_FILE"
+
+# =============================================================================
+# PID Namespace
+# =============================================================================
+
+echo ""
+echo "--- PID Namespace ---"
+
+if ! is_linux; then
+    expect_failure "isolate-pids: refused outside Linux" \
+        "$NONO_BIN" run --silent --allow-cwd --isolate-pids --allow "$TMPDIR" -- true
+elif ! command_exists unshare || ! unshare -Urpf --mount-proc true 2>/dev/null; then
+    skip_test "isolate-pids: other processes are not visible" "PID namespaces unavailable"
+else
+    expect_output_contains "isolate-pids: command runs under nono as PID 1" "pid=2" \
+        "$NONO_BIN" run --silent --allow-cwd --isolate-pids --allow "$TMPDIR" -- \
+        sh -c 'echo pid=$$'
+
+    expect_output_contains "isolate-pids: host processes are not visible" "hidden" \
+        "$NONO_BIN" run --silent --allow-cwd --isolate-pids --allow "$TMPDIR" -- \
+        sh -c "test -e /proc/$$ || echo hidden"
+
+    run_test "isolate-pids: exit code is passed through" 7 \
+        "$NONO_BIN" run --silent --allow-cwd --isolate-pids --allow "$TMPDIR" -- \
+        sh -c 'exit 7' || true
+
+    # The state file is written to $TMPDIR, which the command must be able to read
+    expect_output_contains "why --self reports the PID namespace" '"pid_namespace": true' \
+        env TMPDIR="$TMPDIR" "$NONO_BIN" run --silent --allow-cwd --isolate-pids --allow "$TMPDIR" -- \
+        "$NONO_BIN" why --self --json
+fi
+
+# =============================================================================
+# Syscall Filter
+# =============================================================================
+
+echo ""
+echo "--- Syscall Filter ---"
+
+if ! is_linux; then
+    skip_test "syscalls: dangerous syscalls denied" "Linux only"
+else
+    expect_output_contains "syscalls: dry run lists the filter" "Syscall filter: deny" \
+        "$NONO_BIN" run --allow-cwd --allow "$TMPDIR" --dry-run -- true
+
+    if ! command_exists unshare || ! unshare -U true 2>/dev/null; then
+        skip_test "syscalls: unshare denied" "user namespaces unavailable"
+    else
+        expect_output_contains "syscalls: unshare denied" "denied" \
+            "$NONO_BIN" run --silent --allow-cwd --allow "$TMPDIR" -- \
+            sh -c 'unshare -U true 2>/dev/null || echo denied'
+
+        SYSCALL_HOME="$TMPDIR/syscall-home"
+        mkdir -p "$SYSCALL_HOME/.config/nono/profiles"
+        cat > "$SYSCALL_HOME/.config/nono/profiles/syscall-test.toml" <<'TOML'
+[meta]
+name = "syscall-test"
+
+[syscalls]
+allow = ["unshare"]
+TOML
+
+        expect_success "syscalls: profile [syscalls] allow lifts the filter" \
+            env HOME="$SYSCALL_HOME" XDG_CONFIG_HOME="$SYSCALL_HOME/.config" "$NONO_BIN" run \
+            --silent --profile syscall-test --trust-unsigned --allow-cwd --allow "$TMPDIR" -- \
+            unshare -U true
+    fi
+fi
+
+# =============================================================================
+# Resource Limits
+# =============================================================================
+
+echo ""
+echo "--- Resource Limits ---"
+
+expect_output_contains "limits: --max-open-files applied" "^64$" \
+    "$NONO_BIN" run --silent --allow-cwd --allow "$TMPDIR" --max-open-files 64 -- \
+    sh -c 'ulimit -n'
+
+expect_output_contains "limits: --max-cpu-time explained in footer" "killed by SIGXCPU" \
+    "$NONO_BIN" run --allow-cwd --allow "$TMPDIR" --max-cpu-time 1s -- \
+    sh -c 'while :; do :; done'
+
+# Each strategy sets the limits on its own path: Monitor (the default without
+# a terminal) and Supervised in the forked command
+expect_output_contains "limits: --max-file-size explained in footer" "killed by SIGXFSZ" \
+    "$NONO_BIN" run --allow-cwd --allow "$TMPDIR" --max-file-size 1M -- \
+    sh -c "head -c 2000000 /dev/zero > '$TMPDIR/limits-big'"
+
+expect_output_contains "limits: --max-file-size explained in footer (supervised)" "killed by SIGXFSZ" \
+    "$NONO_BIN" run --supervised --allow-cwd --allow "$TMPDIR" --max-file-size 1M -- \
+    sh -c "head -c 2000000 /dev/zero > '$TMPDIR/limits-big'"
+
+LIMITS_HOME="$TMPDIR/limits-home"
+mkdir -p "$LIMITS_HOME/.config/nono/profiles"
+cat > "$LIMITS_HOME/.config/nono/profiles/limits-test.toml" <<'TOML'
+[meta]
+name = "limits-test"
+
+[limits]
+open_files = 32
+cpu_time = "10m"
+TOML
+
+expect_output_contains "limits: profile [limits] applied, CLI wins" "^48$" \
+    env HOME="$LIMITS_HOME" XDG_CONFIG_HOME="$LIMITS_HOME/.config" "$NONO_BIN" run \
+    --silent --profile limits-test --trust-unsigned --allow-cwd --allow "$TMPDIR" \
+    --max-open-files 48 -- sh -c 'ulimit -n'
+
+expect_output_contains "limits: profile [limits] shown in capabilities" "CPU time 10m" \
+    env HOME="$LIMITS_HOME" XDG_CONFIG_HOME="$LIMITS_HOME/.config" "$NONO_BIN" run \
+    --profile limits-test --trust-unsigned --allow-cwd --allow "$TMPDIR" --dry-run -- true
+
+# Direct mode (interactive profiles) sets them in nono before it becomes the
+# command, so there is no footer: the write stops at the limit instead
+cat > "$LIMITS_HOME/.config/nono/profiles/limits-direct.toml" <<'TOML'
+interactive = true
+
+[meta]
+name = "limits-direct"
+TOML
+
+rm -f "$TMPDIR/limits-big"
+expect_output_contains "limits: --max-file-size applied (direct)" "1048576$" \
+    env HOME="$LIMITS_HOME" XDG_CONFIG_HOME="$LIMITS_HOME/.config" "$NONO_BIN" run \
+    --silent --profile limits-direct --trust-unsigned --allow-cwd --allow "$TMPDIR" \
+    --max-file-size 1M -- \
+    sh -c "head -c 2000000 /dev/zero > '$TMPDIR/limits-big'; wc -c < '$TMPDIR/limits-big'"
+
 # =============================================================================
 # Summary
 # =============================================================================
@@ -119,6 +119,57 @@ else
         "$NONO_BIN" run --write-file "$TMPDIR/allowed/writeable.txt" --allow /tmp -- sh -c "echo 'updated' > '$TMPDIR/allowed/writeable.txt'"
 fi
 
+# =============================================================================
+# Exec Modes
+# =============================================================================
+
+echo ""
+echo "--- Read-noexec / Exec-only Access ---"
+
+mkdir -p "$TMPDIR/noexec" "$TMPDIR/execonly"
+echo "downloaded" > "$TMPDIR/noexec/file.txt"
+cp /bin/echo "$TMPDIR/noexec/prog"
+cp /bin/echo "$TMPDIR/execonly/prog"
+
+expect_success "read file with --read-noexec" \
+    "$NONO_BIN" run --read-noexec "$TMPDIR/noexec" -- cat "$TMPDIR/noexec/file.txt"
+
+expect_failure "cannot execute from --read-noexec directory" \
+    "$NONO_BIN" run --read-noexec "$TMPDIR/noexec" -- sh -c "'$TMPDIR/noexec/prog' hi"
+
+expect_output_contains "execute binary with --exec-only" "exec-ok" \
+    "$NONO_BIN" run --exec-only "$TMPDIR/execonly" -- "$TMPDIR/execonly/prog" exec-ok
+
+# macOS: the temp directory is readable as a system path, so listing is not denied there
+if is_linux; then
+    expect_failure "cannot list --exec-only directory" \
+        "$NONO_BIN" run --exec-only "$TMPDIR/execonly" -- ls "$TMPDIR/execonly"
+    # Landlock needs ReadFile for execve, so the grant must not claim exec-only
+    expect_output_contains "dry run reports --exec-only files readable" "exec, files readable" \
+        "$NONO_BIN" run --dry-run --exec-only "$TMPDIR/execonly" -- true
+else
+    skip_test "cannot list --exec-only directory" "temp directory is a system path on macOS"
+fi
+
+# =============================================================================
+# Device ioctls
+# =============================================================================
+
+echo ""
+echo "--- Device ioctl Allowlist ---"
+
+# stty on /dev/null fails either way; the error tells a Landlock denial
+# (EACCES) from the device rejecting the ioctl (ENOTTY)
+if is_linux && "$NONO_BIN" setup --check-only 2>/dev/null | sed '/Missing on this kernel/,$d' | grep -q -- "- ioctl:"; then
+    expect_output_contains "device ioctl denied by default" "Permission denied" \
+        "$NONO_BIN" run --allow "$TMPDIR" -- sh -c "stty < /dev/null"
+
+    expect_output_contains "device ioctl allowed with --allow-ioctl" "Inappropriate ioctl" \
+        "$NONO_BIN" run --allow "$TMPDIR" --allow-ioctl /dev/null -- sh -c "stty < /dev/null"
+else
+    skip_test "device ioctl allowlist" "requires Linux with Landlock ABI v5"
+fi
+
 # =============================================================================
 # Multiple Grants
 # =============================================================================
@@ -134,6 +185,66 @@ expect_success "overlapping grants (parent and child)" \
     "$NONO_BIN" run --allow "$TMPDIR/allowed" --allow "$TMPDIR/allowed/nested" -- \
     cat "$TMPDIR/allowed/nested/deep/file.txt"
 
+# =============================================================================
+# Deny Paths
+# =============================================================================
+
+echo ""
+echo "--- Deny Paths ---"
+
+DENY_DIR="$TMPDIR/deny"
+mkdir -p "$DENY_DIR/.git/hooks"
+echo "SECRET=1" > "$DENY_DIR/.env"
+echo "[core]" > "$DENY_DIR/.git/config"
+
+if is_linux && { ! command_exists unshare || ! unshare -Urm true 2>/dev/null; }; then
+    skip_test "deny: paths inside an allowed directory" "user namespaces unavailable"
+else
+    expect_output_contains "deny: cannot plant a git hook" "denied" \
+        "$NONO_BIN" run --silent --allow-cwd --allow "$DENY_DIR" --deny "$DENY_DIR/.git/hooks" -- \
+        sh -c "echo x > '$DENY_DIR/.git/hooks/pre-commit' || echo denied"
+
+    expect_output_not_contains "deny: cannot read a denied file" "SECRET" \
+        "$NONO_BIN" run --silent --allow-cwd --allow "$DENY_DIR" --deny "$DENY_DIR/.env" -- \
+        cat "$DENY_DIR/.env"
+
+    expect_success "deny: rest of the directory still writable" \
+        "$NONO_BIN" run --silent --allow-cwd --allow "$DENY_DIR" --deny "$DENY_DIR/.git/hooks" -- \
+        sh -c "echo ok > '$DENY_DIR/.git/config'"
+fi
+
+expect_failure "deny: missing path is rejected" \
+    "$NONO_BIN" run --silent --allow-cwd --allow "$DENY_DIR" --deny "$DENY_DIR/missing" -- true
+
+# =============================================================================
+# Isolated Root
+# =============================================================================
+
+echo ""
+echo "--- Isolated Root ---"
+
+if ! is_linux; then
+    expect_failure "isolate: refused outside Linux" \
+        "$NONO_BIN" run --silent --allow-cwd --isolate --allow "$TMPDIR/allowed" -- true
+elif ! command_exists unshare || ! unshare -Urm true 2>/dev/null; then
+    skip_test "isolate: only granted paths exist" "user namespaces unavailable"
+else
+    expect_success "isolate: granted directory is readable" \
+        "$NONO_BIN" run --silent --allow-cwd --isolate --allow "$TMPDIR/allowed" -- \
+        cat "$TMPDIR/allowed/test.txt"
+
+    expect_output_contains "isolate: other paths do not exist" "absent" \
+        "$NONO_BIN" run --silent --allow-cwd --isolate --allow "$TMPDIR/allowed" -- \
+        sh -c "test -e '$TMPDIR/readonly' || echo absent"
+
+    expect_output_contains "isolate: /tmp is private and writable" "private" \
+        "$NONO_BIN" run --silent --allow-cwd --isolate --allow "$TMPDIR/allowed" -- \
+        sh -c "echo private > /tmp/nono-isolate-$$ && cat /tmp/nono-isolate-$$"
+
+    expect_failure "isolate: private /tmp is not written to the host" \
+        test -e "/tmp/nono-isolate-$$"
+fi
+
 # =============================================================================
 # Summary
 # =============================================================================
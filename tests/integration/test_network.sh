@@ -95,6 +95,46 @@ else
     skip_test "DNS resolution" "no DNS tools installed"
 fi
 
+# =============================================================================
+# TCP Port Filtering (--allow-port / --allow-bind)
+# =============================================================================
+
+echo ""
+echo "--- TCP Port Filtering ---"
+
+if command_exists curl; then
+    expect_success "curl to allowed port works" \
+        "$NONO_BIN" run --allow-port 443 --allow "$TMPDIR" -- curl -s --max-time 10 https://example.com >/dev/null
+
+    expect_failure "curl to port not in --allow-port list blocked" \
+        "$NONO_BIN" run --allow-port 443 --allow "$TMPDIR" -- curl -s --max-time 5 http://example.com >/dev/null
+else
+    skip_test "curl to allowed port" "curl not installed"
+    skip_test "curl to disallowed port" "curl not installed"
+fi
+
+# =============================================================================
+# Host Allowlist (--allow-host)
+# =============================================================================
+
+echo ""
+echo "--- Host Allowlist ---"
+
+if command_exists curl; then
+    expect_success "curl to allowed host works through proxy" \
+        "$NONO_BIN" run --allow-host example.com --allow "$TMPDIR" -- curl -s --max-time 10 https://example.com >/dev/null
+
+    expect_failure "curl to host not in --allow-host list blocked" \
+        "$NONO_BIN" run --allow-host example.com --allow "$TMPDIR" -- curl -s --max-time 5 https://example.org >/dev/null
+
+    expect_failure "curl bypassing the proxy blocked" \
+        "$NONO_BIN" run --allow-host example.com --allow "$TMPDIR" -- curl -s --noproxy '*' --max-time 5 https://example.com >/dev/null
+else
+    skip_test "curl to allowed host" "curl not installed"
+    skip_test "curl to disallowed host" "curl not installed"
+    skip_test "curl bypassing proxy" "curl not installed"
+fi
+
 # =============================================================================
 # Network with Language Runtimes
 # =============================================================================
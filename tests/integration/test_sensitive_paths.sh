@@ -302,6 +302,92 @@ fi
 expect_success "collision directory itself is readable when granted" \
     "$NONO_BIN" run --read "$COLLISION_DIR_SSH" --allow "$TMPDIR" -- cat "$COLLISION_DIR_SSH/fake"
 
+# =============================================================================
+# Broad Grants on Linux (fixture home)
+# =============================================================================
+
+echo ""
+echo "--- Broad Grants on Linux ---"
+echo "(Landlock covers whole hierarchies; nono hides sensitive subtrees instead)"
+echo ""
+
+FAKE_HOME="$TMPDIR/home"
+mkdir -p "$FAKE_HOME/.ssh" "$FAKE_HOME/.sshfoo" "$FAKE_HOME/project"
+echo "FIXTURE-PRIVATE-KEY" > "$FAKE_HOME/.ssh/id_ed25519"
+echo "export FIXTURE_TOKEN=1" > "$FAKE_HOME/.bashrc"
+echo "not-a-key" > "$FAKE_HOME/.sshfoo/fake"
+echo "notes" > "$FAKE_HOME/project/notes.txt"
+
+if ! is_linux; then
+    skip_test "broad grant hides ~/.ssh" "Linux only"
+elif ! command_exists unshare || ! unshare -Urm true 2>/dev/null; then
+    expect_failure "broad grant refused without user namespaces" \
+        env HOME="$FAKE_HOME" "$NONO_BIN" run --read "$FAKE_HOME" -- true
+else
+    expect_output_not_contains "broad grant hides ~/.ssh key contents" "FIXTURE-PRIVATE-KEY" \
+        env HOME="$FAKE_HOME" "$NONO_BIN" run --read "$FAKE_HOME" -- \
+        cat "$FAKE_HOME/.ssh/id_ed25519"
+
+    expect_output_not_contains "broad grant hides ~/.ssh listing" "id_ed25519" \
+        env HOME="$FAKE_HOME" "$NONO_BIN" run --read "$FAKE_HOME" -- \
+        ls "$FAKE_HOME/.ssh"
+
+    expect_output_not_contains "broad grant hides ~/.bashrc contents" "FIXTURE_TOKEN" \
+        env HOME="$FAKE_HOME" "$NONO_BIN" run --read "$FAKE_HOME" -- \
+        cat "$FAKE_HOME/.bashrc"
+
+    expect_output_contains "rest of the broad grant stays readable" "notes" \
+        env HOME="$FAKE_HOME" "$NONO_BIN" run --read "$FAKE_HOME" -- \
+        cat "$FAKE_HOME/project/notes.txt"
+
+    expect_output_contains "~/.sshfoo is not mistaken for ~/.ssh" "not-a-key" \
+        env HOME="$FAKE_HOME" "$NONO_BIN" run --read "$FAKE_HOME" -- \
+        cat "$FAKE_HOME/.sshfoo/fake"
+
+    expect_output_contains "explicit --read ~/.ssh still allows access" "FIXTURE-PRIVATE-KEY" \
+        env HOME="$FAKE_HOME" "$NONO_BIN" run --read "$FAKE_HOME/.ssh" -- \
+        cat "$FAKE_HOME/.ssh/id_ed25519"
+fi
+
+# =============================================================================
+# User Config Overrides (fixture home)
+# =============================================================================
+
+echo ""
+echo "--- User Config Overrides ---"
+echo "(acknowledged overrides in ~/.config/nono/config.toml lift the protection)"
+echo ""
+
+OVR_HOME="$TMPDIR/override-home"
+mkdir -p "$OVR_HOME/.ssh" "$OVR_HOME/.config/nono"
+echo "FIXTURE-PRIVATE-KEY" > "$OVR_HOME/.ssh/id_ed25519"
+echo "FIXTURE-PUBLIC-KEY" > "$OVR_HOME/.ssh/id_ed25519.pub"
+cat > "$OVR_HOME/.config/nono/config.toml" <<'TOML'
+[overrides.sensitive_paths]
+"~/.ssh/id_ed25519.pub" = { reason = "Public key for git", acknowledged = "2025-01-15", access = "read" }
+TOML
+
+expect_output_contains "applied override is reported" "sensitive path ~/.ssh/id_ed25519.pub (read)" \
+    env HOME="$OVR_HOME" "$NONO_BIN" run --read "$OVR_HOME" --dry-run -- true
+
+if ! is_linux; then
+    skip_test "override reveals ~/.ssh/id_ed25519.pub" "Linux only"
+elif ! command_exists unshare || ! unshare -Urm true 2>/dev/null; then
+    skip_test "override reveals ~/.ssh/id_ed25519.pub" "user namespaces unavailable"
+else
+    expect_output_contains "override reveals the overridden file" "FIXTURE-PUBLIC-KEY" \
+        env HOME="$OVR_HOME" "$NONO_BIN" run --allow "$OVR_HOME" -- \
+        cat "$OVR_HOME/.ssh/id_ed25519.pub"
+
+    expect_output_not_contains "rest of ~/.ssh stays hidden" "FIXTURE-PRIVATE-KEY" \
+        env HOME="$OVR_HOME" "$NONO_BIN" run --allow "$OVR_HOME" -- \
+        cat "$OVR_HOME/.ssh/id_ed25519"
+
+    expect_failure "read-only override refuses writes" \
+        env HOME="$OVR_HOME" "$NONO_BIN" run --allow "$OVR_HOME" -- \
+        sh -c "echo tampered >> '$OVR_HOME/.ssh/id_ed25519.pub'"
+fi
+
 # =============================================================================
 # Summary
 # =============================================================================